@@ -1,16 +1,20 @@
-use crate::cli::{Cli, Command};
-use crate::cloud_client::CloudClient;
+use crate::cli::{Cli, Command, UploadsAction};
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::folder_transfer::{download_folder, upload_folder};
+use crate::cloud_client::remote_copy::copy_between_remotes;
+use crate::cloud_client::remotes::Remotes;
+use crate::cloud_client::upload_state::PendingUpload;
 use crate::errors::AppError;
 use crate::tui::WorkMode;
 use crate::utilities::files::get_path_entries;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing::debug;
 
 pub struct WorkspaceData {
     pub local_path: PathBuf,
-    pub local_entries: Vec<String>,
+    pub local_entries: Vec<Entry>,
     pub cloud_path: PathBuf,
-    pub cloud_entries: Vec<String>,
+    pub cloud_entries: Vec<Entry>,
 }
 
 impl Default for WorkspaceData {
@@ -24,24 +28,28 @@ impl Default for WorkspaceData {
     }
 }
 
-pub struct App<C: CloudClient> {
+pub struct App {
     pub input_command: String,
     pub cursor_position: usize,
     pub work_mode: WorkMode,
     pub logs: Vec<String>,
     pub workspace_data: WorkspaceData,
-    pub cloud_client: C,
+    pub remotes: Remotes,
 }
 
-impl<C: CloudClient> App<C> {
-    pub fn new(cloud_client: C) -> Self {
+impl App {
+    pub fn new(remotes: Remotes) -> Self {
+        let logs = remotes
+            .unavailable()
+            .map(|(name, reason)| format!("Remote {name} is unavailable: {reason}"))
+            .collect();
         Self {
             input_command: String::new(),
             cursor_position: 0,
             work_mode: WorkMode::Read,
-            logs: Vec::new(),
+            logs,
             workspace_data: Default::default(),
-            cloud_client,
+            remotes,
         }
     }
 
@@ -104,29 +112,118 @@ impl<C: CloudClient> App<C> {
         self.workspace_data.local_entries =
             get_path_entries(self.workspace_data.local_path.as_path());
         self.workspace_data.cloud_entries = self
-            .cloud_client
+            .remotes
+            .current()
             .list_entries(self.workspace_data.cloud_path.clone())?;
         Ok(())
     }
 
+    /// Shows the next remote in the cloud pane
+    pub fn switch_to_next_remote(&mut self) {
+        self.remotes.switch_next();
+        self.workspace_data.cloud_path = PathBuf::from("//");
+        if let Err(error) = self.update_workspace_data() {
+            self.logs.push(error.to_string());
+        }
+    }
+
     pub fn execute_command(&mut self, cli: Cli) -> Result<(), AppError> {
         match cli.command {
-            Command::Download { from_path, to_path } => {
+            Command::Download {
+                from_path,
+                to_path,
+                recursive,
+            } => {
                 debug!("Downloading... {:?} {:?}", from_path, to_path);
-                self.cloud_client.download(from_path, to_path)?;
+                let (cloud_client, from_path) = self.remotes.client_for(&from_path)?;
+                if recursive {
+                    let summary = download_folder(cloud_client, &from_path, &to_path)?;
+                    self.logs.extend(summary.report());
+                } else {
+                    cloud_client.download(from_path, to_path)?;
+                }
             }
-            Command::Upload { from_path, to_path } => {
+            Command::Upload {
+                from_path,
+                to_path,
+                recursive,
+                options,
+            } => {
                 debug!("Uploading... {:?} {:?}", from_path, to_path);
-                self.cloud_client.upload(from_path, to_path)?;
+                let options = options.into();
+                let (cloud_client, to_path) = self.remotes.client_for(&to_path)?;
+                if recursive {
+                    let summary = upload_folder(cloud_client, &from_path, &to_path, &options)?;
+                    self.logs.extend(summary.report());
+                } else {
+                    cloud_client.upload(from_path, to_path, options)?;
+                }
             }
             Command::Delete { path } => {
                 debug!("Deleting... {:?}", path);
-                self.cloud_client.delete(path)?;
+                let (cloud_client, path) = self.remotes.client_for(&path)?;
+                cloud_client.delete(path)?;
+            }
+            Command::Mkdir { path, autorename } => {
+                debug!("Creating folder... {:?}", path);
+                let (cloud_client, path) = self.remotes.client_for(&path)?;
+                cloud_client.create_folder(path, autorename)?;
+            }
+            Command::Mv {
+                from_path,
+                to_path,
+                options,
+            } => {
+                debug!("Moving... {:?} {:?}", from_path, to_path);
+                let (remote, from_path, to_path) =
+                    self.resolve_within_remote(&from_path, &to_path)?;
+                self.remotes
+                    .get(remote)?
+                    .move_entry(from_path, to_path, options.into())?;
+            }
+            Command::Cp {
+                from_path,
+                to_path,
+                options,
+            } => {
+                debug!("Copying... {:?} {:?}", from_path, to_path);
+                let (from_remote, from_path) = self.remotes.resolve(&from_path)?;
+                let (to_remote, to_path) = self.remotes.resolve(&to_path)?;
+                if from_remote == to_remote {
+                    self.remotes.get(from_remote)?.copy_entry(
+                        from_path,
+                        to_path,
+                        options.into(),
+                    )?;
+                } else {
+                    let summary = copy_between_remotes(
+                        self.remotes.get(from_remote)?,
+                        &from_path,
+                        self.remotes.get(to_remote)?,
+                        &to_path,
+                        options.into(),
+                    )?;
+                    self.logs.extend(summary.report());
+                }
             }
             // Just update folders entry list without performing any operations
             Command::List => {
                 debug!("Listing entries...");
             }
+            Command::Remote { name } => {
+                debug!("Switching remote... {:?}", name);
+                match name {
+                    Some(name) => {
+                        self.remotes.switch(&name)?;
+                        self.workspace_data.cloud_path = PathBuf::from("//");
+                    }
+                    None => self.list_remotes(),
+                }
+            }
+            Command::Uploads { action } => {
+                debug!("Managing uploads... {:?}", action);
+                self.manage_uploads(action)?;
+            }
             Command::Clear => {
                 debug!("Clearing logs...");
                 self.logs.clear();
@@ -135,4 +232,82 @@ impl<C: CloudClient> App<C> {
         self.update_workspace_data()?;
         Ok(())
     }
+
+    /// Resolves paths of an operation which can't span several remotes
+    fn resolve_within_remote(
+        &self,
+        from_path: &Path,
+        to_path: &Path,
+    ) -> Result<(&str, PathBuf, PathBuf), AppError> {
+        let (from_remote, from_path) = self.remotes.resolve(from_path)?;
+        let (to_remote, to_path) = self.remotes.resolve(to_path)?;
+        if from_remote != to_remote {
+            return Err(AppError::Remote(format!(
+                "unable to relocate entries from {from_remote} to {to_remote}"
+            )));
+        }
+        Ok((from_remote, from_path, to_path))
+    }
+
+    fn list_remotes(&mut self) {
+        let current = self.remotes.current_name();
+        let mut lines: Vec<String> = self
+            .remotes
+            .names()
+            .map(|name| match name == current {
+                true => format!("* {name}"),
+                false => format!("  {name}"),
+            })
+            .collect();
+        lines.extend(
+            self.remotes
+                .unavailable()
+                .map(|(name, reason)| format!("  {name} (unavailable: {reason})")),
+        );
+        self.logs.extend(lines);
+    }
+
+    fn manage_uploads(&mut self, action: UploadsAction) -> Result<(), AppError> {
+        let cloud_client = self.remotes.current();
+        let uploads = cloud_client.pending_uploads()?;
+        match action {
+            UploadsAction::List => {
+                if uploads.is_empty() {
+                    self.logs
+                        .push("There are no interrupted uploads".to_string());
+                }
+                for (number, upload) in uploads.iter().enumerate() {
+                    self.logs.push(format!(
+                        "{number}: {} -> {} ({} of {} bytes)",
+                        upload.from_path.display(),
+                        upload.to_path.display(),
+                        upload.offset,
+                        upload.fingerprint.size()
+                    ));
+                }
+            }
+            UploadsAction::Resume { number } => {
+                let upload = take_pending_upload(uploads, number)?;
+                cloud_client.upload(upload.from_path, upload.to_path, upload.options)?;
+            }
+            UploadsAction::Abandon { number } => {
+                let upload = take_pending_upload(uploads, number)?;
+                cloud_client.abandon_upload(&upload)?;
+            }
+        }
+        Ok(())
+    }
 }
+
+fn take_pending_upload(
+    uploads: Vec<PendingUpload>,
+    number: usize,
+) -> Result<PendingUpload, AppError> {
+    uploads
+        .into_iter()
+        .nth(number)
+        .ok_or_else(|| AppError::UploadState(format!("there is no upload number {number}")))
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,381 @@
+use crate::app::App;
+use crate::cli::Cli;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::memory::MemoryClient;
+use crate::cloud_client::remotes::Remotes;
+use crate::cloud_client::upload_state::{FileFingerprint, PendingUpload};
+use crate::cloud_client::UploadOptions;
+use crate::errors::AppError;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+/// Drives `App` with command strings typed into its input, the way the TUI does,
+/// with a memory storage as the only remote and a temporary local folder
+struct Harness {
+    app: App,
+    storage: MemoryClient,
+    local: TempDir,
+}
+
+impl Harness {
+    fn new() -> Harness {
+        let storage = MemoryClient::new();
+        let remotes = Remotes::new("main".to_string(), Box::new(storage.clone()));
+        let local = tempfile::tempdir().expect("temporary directory is created");
+        let mut app = App::new(remotes);
+        app.workspace_data.local_path = local.path().to_path_buf();
+        Harness {
+            app,
+            storage,
+            local,
+        }
+    }
+
+    /// Adds another remote backed by its own memory storage
+    fn add_remote(&mut self, name: &str) -> MemoryClient {
+        let storage = MemoryClient::new();
+        self.app
+            .remotes
+            .insert(name.to_string(), Box::new(storage.clone()));
+        storage
+    }
+
+    /// Types the command and submits it, returning the lines it has logged
+    /// after the echoed command itself
+    fn run(&mut self, command: &str) -> Vec<String> {
+        let logged = self.app.logs.len();
+        for char in command.chars() {
+            self.app.enter_char(char);
+        }
+        self.app.submit_command();
+
+        assert_eq!(self.app.logs[logged], command);
+        self.app.logs[logged + 1..].to_vec()
+    }
+
+    /// Returns path of a file in the local folder, created with `content` if it is given
+    fn local_file(&self, name: &str, content: Option<&[u8]>) -> String {
+        let path = self.local.path().join(name);
+        if let Some(content) = content {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+        }
+        path.display().to_string()
+    }
+
+    fn cloud_names(&self) -> Vec<String> {
+        names(&self.app.workspace_data.cloud_entries)
+    }
+
+    fn local_names(&self) -> Vec<String> {
+        let mut local_names = names(&self.app.workspace_data.local_entries);
+        local_names.sort();
+        local_names
+    }
+}
+
+fn names(entries: &[Entry]) -> Vec<String> {
+    entries.iter().map(|entry| entry.display_name()).collect()
+}
+
+#[test]
+fn uploaded_file_is_shown_in_cloud_pane() {
+    let mut harness = Harness::new();
+    let from_path = harness.local_file("report.txt", Some(b"quarterly"));
+
+    let logs = harness.run(&format!("upload {from_path} /docs/report.txt"));
+
+    assert!(logs.is_empty());
+    assert_eq!(
+        harness.storage.content("/docs/report.txt").as_deref(),
+        Some(&b"quarterly"[..])
+    );
+    assert_eq!(harness.cloud_names(), ["docs/"]);
+    assert_eq!(harness.local_names(), ["report.txt"]);
+}
+
+#[test]
+fn submitted_input_is_cleared() {
+    let mut harness = Harness::new();
+
+    harness.run("list");
+
+    assert!(harness.app.input_command.is_empty());
+    assert_eq!(harness.app.cursor_position, 0);
+}
+
+#[test]
+fn unknown_command_is_reported() {
+    let mut harness = Harness::new();
+
+    let logs = harness.run("rename /a.txt /b.txt");
+
+    assert_eq!(logs.len(), 1);
+    assert!(logs[0].contains("unrecognized subcommand 'rename'"));
+    assert!(harness.storage.calls().is_empty());
+}
+
+#[test]
+fn downloaded_file_is_shown_in_local_pane() {
+    let mut harness = Harness::new();
+    harness.storage.add_file("/photo.jpg", b"jpeg");
+    let to_path = harness.local_file("photo.jpg", None);
+
+    let logs = harness.run(&format!("download /photo.jpg {to_path}"));
+
+    assert!(logs.is_empty());
+    assert_eq!(fs::read(to_path).unwrap(), b"jpeg");
+    assert_eq!(harness.local_names(), ["photo.jpg"]);
+}
+
+#[test]
+fn deleting_absent_file_is_reported() {
+    let mut harness = Harness::new();
+
+    let logs = harness.run("delete /absent.txt");
+
+    assert_eq!(logs, ["Not found: /absent.txt"]);
+}
+
+#[test]
+fn existing_folder_is_renamed_with_autorename() {
+    let mut harness = Harness::new();
+    harness.storage.add_folder("/docs");
+
+    assert_eq!(harness.run("mkdir /docs"), ["Conflict: /docs"]);
+    assert!(harness.run("mkdir /docs --autorename").is_empty());
+
+    assert_eq!(harness.cloud_names(), ["docs/", "docs (1)/"]);
+}
+
+#[test]
+fn injected_upload_error_is_reported() {
+    let mut harness = Harness::new();
+    let from_path = harness.local_file("big.bin", Some(b"big"));
+    harness
+        .storage
+        .fail_operation("upload", 1, || AppError::InsufficientSpace);
+
+    let logs = harness.run(&format!("upload {from_path} /big.bin"));
+
+    assert_eq!(logs, ["Insufficient space on cloud storage"]);
+    assert!(!harness.storage.exists("/big.bin"));
+    // Workspace isn't refreshed after a failed command
+    assert_eq!(harness.storage.calls(), ["upload /big.bin"]);
+}
+
+#[test]
+fn failed_refresh_is_reported_after_performed_command() {
+    let mut harness = Harness::new();
+    harness.storage.add_file("/old.txt", b"old");
+    harness
+        .storage
+        .fail_operation("list_entries", 1, || AppError::RateLimited {
+            retry_after: Some(3),
+        });
+
+    let logs = harness.run("delete /old.txt");
+
+    assert_eq!(logs, ["Too many requests, retry after 3 seconds"]);
+    assert!(!harness.storage.exists("/old.txt"));
+}
+
+#[test]
+fn recursive_upload_reports_every_file() {
+    let mut harness = Harness::new();
+    harness.local_file("album/a.jpg", Some(b"a"));
+    harness.local_file("album/b.jpg", Some(b"b"));
+    let from_path = harness.local.path().join("album");
+    // Files are uploaded in directory order, so either of them may be the failed one
+    harness.storage.fail_call(2, || AppError::Server {
+        status: 503,
+        message: "unavailable".to_string(),
+    });
+
+    let logs = harness.run(&format!(
+        "upload {} /album --recursive",
+        from_path.display()
+    ));
+
+    assert_eq!(logs.len(), 3);
+    assert!(logs[0].starts_with("Transferred "));
+    assert!(logs[1].starts_with("Failed ") && logs[1].ends_with(": Server error 503: unavailable"));
+    assert_eq!(logs[2], "1 files transferred, 1 failed");
+}
+
+#[test]
+fn entries_are_copied_between_remotes() {
+    let mut harness = Harness::new();
+    let backup = harness.add_remote("backup");
+    harness.storage.add_file("/docs/a.txt", b"a");
+    harness.storage.add_file("/docs/nested/b.txt", b"b");
+
+    let mut logs = harness.run("cp /docs backup:/archive");
+    logs.sort();
+
+    assert_eq!(
+        logs,
+        [
+            "2 files transferred, 0 failed",
+            "Transferred a.txt",
+            "Transferred nested/b.txt",
+        ]
+    );
+    assert_eq!(
+        backup.content("/archive/nested/b.txt").as_deref(),
+        Some(&b"b"[..])
+    );
+    assert!(harness.storage.exists("/docs/a.txt"));
+}
+
+#[test]
+fn exported_file_is_copied_with_its_content() {
+    let mut harness = Harness::new();
+    let backup = harness.add_remote("backup");
+    harness
+        .storage
+        .add_exported_file("/notes.docx", b"document");
+
+    let logs = harness.run("cp /notes.docx backup:/notes.docx");
+
+    assert_eq!(
+        logs,
+        ["Transferred /notes.docx", "1 files transferred, 0 failed"]
+    );
+    assert_eq!(
+        backup.content("/notes.docx").as_deref(),
+        Some(&b"document"[..])
+    );
+}
+
+#[test]
+fn colon_in_path_is_not_taken_for_remote() {
+    let mut harness = Harness::new();
+    harness.add_remote("backup");
+    harness.storage.add_file("/notes:v2.txt", b"v2");
+
+    assert!(harness.run("delete notes:v2.txt").is_empty());
+    assert!(harness.run("mkdir archive:2024").is_empty());
+
+    assert!(!harness.storage.exists("/notes:v2.txt"));
+    assert!(harness.storage.exists("/archive:2024"));
+}
+
+#[test]
+fn entries_are_not_moved_between_remotes() {
+    let mut harness = Harness::new();
+    harness.add_remote("backup");
+    harness.storage.add_file("/a.txt", b"a");
+
+    let logs = harness.run("mv /a.txt backup:/a.txt");
+
+    assert_eq!(
+        logs,
+        ["Remote error: unable to relocate entries from main to backup"]
+    );
+    assert!(harness.storage.exists("/a.txt"));
+}
+
+#[test]
+fn entry_is_moved_within_remote() {
+    let mut harness = Harness::new();
+    harness.storage.add_file("/inbox/a.txt", b"a");
+
+    assert!(harness.run("mv /inbox/a.txt /a.txt").is_empty());
+
+    assert_eq!(harness.cloud_names(), ["a.txt", "inbox/"]);
+}
+
+#[test]
+fn remotes_are_listed_and_switched() {
+    let mut harness = Harness::new();
+    let backup = harness.add_remote("backup");
+    backup.add_file("/backup.tar", b"tar");
+
+    assert_eq!(harness.run("remote"), ["  backup", "* main"]);
+    assert!(harness.run("remote backup").is_empty());
+
+    assert_eq!(harness.app.remotes.current_name(), "backup");
+    assert_eq!(harness.cloud_names(), ["backup.tar"]);
+    assert_eq!(
+        harness.run("remote absent"),
+        ["Remote error: there is no remote named absent"]
+    );
+}
+
+#[test]
+fn pending_uploads_are_listed_and_abandoned() {
+    let mut harness = Harness::new();
+    assert_eq!(
+        harness.run("uploads list"),
+        ["There are no interrupted uploads"]
+    );
+
+    let from_path = harness.local_file("video.mp4", Some(b"0123456789"));
+    harness.storage.add_pending_upload(PendingUpload {
+        remote: "main".to_string(),
+        from_path: PathBuf::from(&from_path),
+        to_path: PathBuf::from("/video.mp4"),
+        session_id: "session".to_string(),
+        offset: 4,
+        fingerprint: FileFingerprint::of(&PathBuf::from(&from_path)).unwrap(),
+        options: UploadOptions::default(),
+    });
+
+    assert_eq!(
+        harness.run("uploads list"),
+        [format!("0: {from_path} -> /video.mp4 (4 of 10 bytes)")]
+    );
+    assert_eq!(
+        harness.run("uploads abandon 1"),
+        ["Upload state error: there is no upload number 1"]
+    );
+    assert!(harness.run("uploads abandon 0").is_empty());
+    assert_eq!(
+        harness.run("uploads list"),
+        ["There are no interrupted uploads"]
+    );
+}
+
+#[test]
+fn logs_are_cleared() {
+    let mut harness = Harness::new();
+    harness.run("delete /absent.txt");
+
+    for char in "clear".chars() {
+        harness.app.enter_char(char);
+    }
+    harness.app.submit_command();
+
+    assert!(harness.app.logs.is_empty());
+}
+
+#[test]
+fn slow_storage_delays_command() {
+    let mut harness = Harness::new();
+    harness.storage.set_latency(Duration::from_millis(20));
+
+    let started = Instant::now();
+    harness.run("list");
+
+    assert!(started.elapsed() >= Duration::from_millis(20));
+    assert_eq!(harness.storage.calls(), ["list_entries //"]);
+}
+
+#[test]
+fn executed_command_returns_error_without_logging_it() {
+    let mut harness = Harness::new();
+    harness
+        .storage
+        .fail_call(1, || AppError::Unauthorized("expired".to_string()));
+
+    let result = harness
+        .app
+        .execute_command(Cli::parse_str("mkdir /docs").unwrap());
+
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    assert!(harness.app.logs.is_empty());
+    assert!(!harness.storage.exists("/docs"));
+}
@@ -1,5 +1,6 @@
+use crate::cloud_client::{RelocationOptions, UploadOptions, WriteMode};
 use crate::APPLICATION_NAME;
-use clap::{Error, Parser, Subcommand};
+use clap::{Args, Error, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Subcommand, Debug)]
@@ -8,20 +9,131 @@ pub enum Command {
     Download {
         from_path: PathBuf,
         to_path: PathBuf,
+        /// Download folder with all its contents
+        #[arg(short, long)]
+        recursive: bool,
     },
     /// Upload file from local machine to cloud storage
     Upload {
         from_path: PathBuf,
         to_path: PathBuf,
+        /// Upload folder with all its contents
+        #[arg(short, long)]
+        recursive: bool,
+        #[command(flatten)]
+        options: UploadArgs,
     },
     /// Delete file on cloud storage
     Delete { path: PathBuf },
+    /// Create folder on cloud storage
+    Mkdir {
+        path: PathBuf,
+        /// Rename the folder if there is a conflict
+        #[arg(long)]
+        autorename: bool,
+    },
+    /// Move file or folder within cloud storage
+    Mv {
+        from_path: PathBuf,
+        to_path: PathBuf,
+        #[command(flatten)]
+        options: RelocationArgs,
+    },
+    /// Copy file or folder within a remote or between two remotes
+    Cp {
+        from_path: PathBuf,
+        to_path: PathBuf,
+        #[command(flatten)]
+        options: RelocationArgs,
+    },
     /// List files on local machine and cloud storage
     List,
+    /// Show remote in the cloud pane, or list remotes if the name is omitted
+    Remote { name: Option<String> },
+    /// Manage interrupted uploads of the current remote
+    Uploads {
+        #[command(subcommand)]
+        action: UploadsAction,
+    },
     /// Clear logs
     Clear,
 }
 
+#[derive(Args, Debug)]
+pub struct UploadArgs {
+    /// What to do if the file already exists: add, overwrite or update:<rev>
+    #[arg(long, default_value = "add")]
+    pub mode: WriteMode,
+    /// Rename the file if there is a conflict
+    #[arg(long)]
+    pub autorename: bool,
+    /// Don't notify users about the modification
+    #[arg(long)]
+    pub mute: bool,
+    /// Treat uploading identical content as a conflict as well
+    #[arg(long)]
+    pub strict_conflict: bool,
+}
+
+impl From<UploadArgs> for UploadOptions {
+    fn from(args: UploadArgs) -> Self {
+        UploadOptions {
+            mode: args.mode,
+            autorename: args.autorename,
+            mute: args.mute,
+            strict_conflict: args.strict_conflict,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct RelocationArgs {
+    /// Rename the entry if there is a conflict at the destination
+    #[arg(long)]
+    pub autorename: bool,
+    /// Allow moves which transfer ownership of the content
+    #[arg(long)]
+    pub allow_ownership_transfer: bool,
+}
+
+impl From<RelocationArgs> for RelocationOptions {
+    fn from(args: RelocationArgs) -> Self {
+        RelocationOptions {
+            autorename: args.autorename,
+            allow_ownership_transfer: args.allow_ownership_transfer,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UploadsAction {
+    /// List interrupted uploads
+    List,
+    /// Continue interrupted upload with specified number
+    Resume { number: usize },
+    /// Forget interrupted upload with specified number
+    Abandon { number: usize },
+}
+
+/// Commands performed instead of starting the TUI
+#[derive(Subcommand, Debug)]
+pub enum StartupCommand {
+    /// Authorize the application to access the cloud storage account of the remote
+    Login,
+    /// Revoke access to the cloud storage account of the remote and delete stored credentials
+    Logout,
+}
+
+#[derive(Parser, Debug)]
+#[command(name = "csu", version, about = "TUI application to perform basic operations with cloud storages", long_about = None)]
+pub struct StartupCli {
+    /// Remote from the configuration file to work with
+    #[arg(long, global = true)]
+    pub remote: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<StartupCommand>,
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "cloud-storage-utilizer", version = "0.0.1", about = "Performs specified request to cloud storage", long_about = None)]
 pub struct Cli {
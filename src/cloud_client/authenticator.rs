@@ -0,0 +1,194 @@
+use crate::cloud_client::http::{send_checked, ApiResponses};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::errors::{AppError, RESPONSE_BODY_ERROR};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Access token is refreshed this long before it actually expires
+const EXPIRATION_MARGIN: Duration = Duration::from_secs(60);
+
+/// Successful response of OAuth token endpoint
+#[derive(Deserialize, Debug)]
+pub struct TokenResult {
+    pub access_token: String,
+    /// Lifetime of the access token in seconds
+    pub expires_in: Option<u64>,
+    /// Returned only when offline access is requested
+    pub refresh_token: Option<String>,
+}
+
+/// Body of unsuccessful token response, e.g. while the user hasn't allowed access yet
+#[derive(Deserialize, Debug)]
+pub struct TokenError {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+pub enum TokenRequestError {
+    /// Token endpoint answered with OAuth error
+    Rejected(TokenError),
+    Failed(AppError),
+}
+
+impl From<TokenRequestError> for AppError {
+    fn from(error: TokenRequestError) -> Self {
+        match error {
+            TokenRequestError::Rejected(error) => {
+                AppError::Authorization(error.error_description.unwrap_or(error.error))
+            }
+            TokenRequestError::Failed(error) => error,
+        }
+    }
+}
+
+/// Sends form `parameters` to the token endpoint at `url`
+pub fn request_token<P: Serialize>(
+    client: &Client,
+    url: &str,
+    parameters: &P,
+) -> Result<TokenResult, TokenRequestError> {
+    let response = client
+        .post(url)
+        .form(parameters)
+        .send()
+        .map_err(|error| TokenRequestError::Failed(AppError::SendRequest(error.to_string())))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(match serde_json::from_str::<TokenError>(&body) {
+            Ok(error) => TokenRequestError::Rejected(error),
+            Err(_) => {
+                TokenRequestError::Failed(AppError::Authorization(format!("{status}: {body}")))
+            }
+        });
+    }
+    response
+        .json::<TokenResult>()
+        .map_err(|_| TokenRequestError::Failed(AppError::Response(RESPONSE_BODY_ERROR.to_string())))
+}
+
+struct AccessToken {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl AccessToken {
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Instant::now() + EXPIRATION_MARGIN >= expires_at)
+    }
+}
+
+/// Provides access token for requests, refreshing it at the token endpoint
+/// when credentials stored by `csu login` allow that.
+/// `P` are form parameters of the refresh request, built from the stored credentials.
+pub struct Authenticator<P> {
+    client: Client,
+    token_url: String,
+    refresh_parameters: Option<P>,
+    access_token: Mutex<Option<AccessToken>>,
+}
+
+impl<P> Debug for Authenticator<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("token_url", &self.token_url)
+            .field("can_refresh", &self.refresh_parameters.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: Serialize> Authenticator<P> {
+    /// Refreshes access token with `refresh_parameters` if there are stored credentials,
+    /// otherwise uses access token from `access_token_variable` as long as it is accepted
+    pub fn build(
+        client: Client,
+        token_url: String,
+        refresh_parameters: Option<P>,
+        access_token_variable: &str,
+    ) -> Result<Authenticator<P>, AppError> {
+        let access_token = match refresh_parameters {
+            Some(_) => None,
+            None => Some(AccessToken {
+                value: std::env::var(access_token_variable)
+                    .map_err(|_| AppError::AbsentAccessToken(access_token_variable.to_string()))?,
+                expires_at: None,
+            }),
+        };
+        Ok(Self {
+            client,
+            token_url,
+            refresh_parameters,
+            access_token: Mutex::new(access_token),
+        })
+    }
+
+    pub fn can_refresh(&self) -> bool {
+        self.refresh_parameters.is_some()
+    }
+
+    /// Returns current access token, refreshing it first if it has expired
+    pub fn access_token(&self) -> Result<String, AppError> {
+        let mut access_token = self
+            .access_token
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match access_token.as_ref() {
+            Some(token) if !token.is_expired() => Ok(token.value.clone()),
+            _ => self.refresh_locked(&mut access_token),
+        }
+    }
+
+    /// Receives new access token regardless of the expiration time of the current one
+    pub fn refresh(&self) -> Result<String, AppError> {
+        let mut access_token = self
+            .access_token
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.refresh_locked(&mut access_token)
+    }
+
+    fn refresh_locked(&self, access_token: &mut Option<AccessToken>) -> Result<String, AppError> {
+        let parameters = self
+            .refresh_parameters
+            .as_ref()
+            .ok_or(AppError::ExpiredAccessToken)?;
+        info!("Refreshing access token...");
+        let token = request_token(&self.client, &self.token_url, parameters)?;
+
+        let value = token.access_token.clone();
+        *access_token = Some(AccessToken {
+            value: token.access_token,
+            expires_at: token
+                .expires_in
+                .map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+        });
+        Ok(value)
+    }
+
+    /// Sends request built by `request` with access token, following the retry policy.
+    /// Request rejected because of expired access token is repeated once with the refreshed one.
+    pub fn send<A: ApiResponses>(
+        &self,
+        retry_policy: &RetryPolicy,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, AppError> {
+        retry_policy.run(idempotent, || {
+            let access_token = self.access_token()?;
+            match send_checked::<A>(request().bearer_auth(access_token)) {
+                Err(AppError::ExpiredAccessToken) if self.can_refresh() => {
+                    info!("Access token has expired, refreshing");
+                    let access_token = self.refresh()?;
+                    send_checked::<A>(request().bearer_auth(access_token))
+                }
+                result => result,
+            }
+        })
+    }
+}
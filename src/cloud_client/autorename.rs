@@ -0,0 +1,34 @@
+use crate::errors::AppError;
+use std::path::{Path, PathBuf};
+
+/// Returns `path` if it isn't taken yet, otherwise the first free path among `name (1).ext`,
+/// `name (2).ext` and so on if `autorename` allows that.
+/// `exists` checks the storage the entry is going to be written to.
+pub fn free_path(
+    path: &Path,
+    autorename: bool,
+    mut exists: impl FnMut(&Path) -> Result<bool, AppError>,
+) -> Result<PathBuf, AppError> {
+    if !exists(path)? {
+        return Ok(path.to_path_buf());
+    }
+    if !autorename {
+        return Err(AppError::Conflict(path.display().to_string()));
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    for number in 1.. {
+        let candidate = path.with_file_name(format!("{stem} ({number}){extension}"));
+        if !exists(&candidate)? {
+            return Ok(candidate);
+        }
+    }
+    unreachable!("one of the numbered names is free")
+}
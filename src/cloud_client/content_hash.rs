@@ -0,0 +1,40 @@
+use std::io::{self, Read};
+
+/// Computes hash of file content the same way a cloud storage reports it in `Entry::hash`
+pub trait ContentHasher {
+    fn update(&mut self, data: &[u8]);
+    fn finish(self: Box<Self>) -> String;
+}
+
+/// Counts and hashes everything read through it
+pub struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Option<Box<dyn ContentHasher>>,
+    read: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R, hasher: Option<Box<dyn ContentHasher>>) -> Self {
+        Self {
+            inner,
+            hasher,
+            read: 0,
+        }
+    }
+
+    /// Returns number of bytes read and their hash, if there was a hasher
+    pub fn finish(self) -> (u64, Option<String>) {
+        (self.read, self.hasher.map(|hasher| hasher.finish()))
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(hasher) = &mut self.hasher {
+            hasher.update(&buf[..read]);
+        }
+        self.read += read as u64;
+        Ok(read)
+    }
+}
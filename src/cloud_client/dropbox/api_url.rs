@@ -1,17 +1,151 @@
+use crate::config::DropboxConfig;
+
+static WEB_URL: &str = "https://www.dropbox.com";
+static DEFAULT_API_URL: &str = "https://api.dropboxapi.com";
+static DEFAULT_CONTENT_URL: &str = "https://content.dropboxapi.com";
+
 pub enum ApiUrl {
+    Authorize,
+    Token,
+    TokenRevoke,
     Download,
     Upload,
     Delete,
+    CreateFolder,
+    Move,
+    Copy,
+    CopyReferenceGet,
+    CopyReferenceSave,
+    GetMetadata,
     ListFolder,
+    ListFolderContinue,
+    UploadSessionStart,
+    UploadSessionAppend,
+    UploadSessionFinish,
+}
+
+/// Dropbox host serving the endpoint
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Host {
+    /// Pages opened in the browser
+    Web,
+    /// RPC endpoints with parameters in JSON body
+    Api,
+    /// Content endpoints with parameters in `Dropbox-API-Arg` header
+    Content,
+}
+
+impl ApiUrl {
+    pub fn host(&self) -> Host {
+        match self {
+            ApiUrl::Authorize => Host::Web,
+            ApiUrl::Token
+            | ApiUrl::TokenRevoke
+            | ApiUrl::Delete
+            | ApiUrl::CreateFolder
+            | ApiUrl::Move
+            | ApiUrl::Copy
+            | ApiUrl::CopyReferenceGet
+            | ApiUrl::CopyReferenceSave
+            | ApiUrl::GetMetadata
+            | ApiUrl::ListFolder
+            | ApiUrl::ListFolderContinue => Host::Api,
+            ApiUrl::Download
+            | ApiUrl::Upload
+            | ApiUrl::UploadSessionStart
+            | ApiUrl::UploadSessionAppend
+            | ApiUrl::UploadSessionFinish => Host::Content,
+        }
+    }
+
+    pub fn path(&self) -> &'static str {
+        match self {
+            ApiUrl::Authorize => "/oauth2/authorize",
+            ApiUrl::Token => "/oauth2/token",
+            ApiUrl::TokenRevoke => "/2/auth/token/revoke",
+            ApiUrl::Download => "/2/files/download",
+            ApiUrl::Upload => "/2/files/upload",
+            ApiUrl::Delete => "/2/files/delete_v2",
+            ApiUrl::CreateFolder => "/2/files/create_folder_v2",
+            ApiUrl::Move => "/2/files/move_v2",
+            ApiUrl::Copy => "/2/files/copy_v2",
+            ApiUrl::CopyReferenceGet => "/2/files/copy_reference/get",
+            ApiUrl::CopyReferenceSave => "/2/files/copy_reference/save",
+            ApiUrl::GetMetadata => "/2/files/get_metadata",
+            ApiUrl::ListFolder => "/2/files/list_folder",
+            ApiUrl::ListFolderContinue => "/2/files/list_folder/continue",
+            ApiUrl::UploadSessionStart => "/2/files/upload_session/start",
+            ApiUrl::UploadSessionAppend => "/2/files/upload_session/append_v2",
+            ApiUrl::UploadSessionFinish => "/2/files/upload_session/finish",
+        }
+    }
 }
 
 impl ApiUrl {
-    pub fn as_url(&self) -> &'static str {
+    /// Whether repeating the request after an unknown outcome can't change the result
+    pub fn is_idempotent(&self) -> bool {
         match self {
-            ApiUrl::Download => "https://content.dropboxapi.com/2/files/download",
-            ApiUrl::Upload => "https://content.dropboxapi.com/2/files/upload",
-            ApiUrl::Delete => "https://api.dropboxapi.com/2/files/delete_v2",
-            ApiUrl::ListFolder => "https://api.dropboxapi.com/2/files/list_folder",
+            ApiUrl::Authorize
+            | ApiUrl::TokenRevoke
+            | ApiUrl::Download
+            | ApiUrl::CopyReferenceGet
+            | ApiUrl::GetMetadata
+            | ApiUrl::ListFolder
+            | ApiUrl::ListFolderContinue
+            | ApiUrl::UploadSessionStart => true,
+            // Authorization code can be exchanged only once
+            ApiUrl::Token
+            | ApiUrl::Upload
+            | ApiUrl::Delete
+            | ApiUrl::CreateFolder
+            | ApiUrl::Move
+            | ApiUrl::Copy
+            | ApiUrl::CopyReferenceSave
+            | ApiUrl::UploadSessionAppend
+            | ApiUrl::UploadSessionFinish => false,
+        }
+    }
+}
+
+/// Base URLs of Dropbox hosts.
+/// API and content hosts can be overridden in the remote configuration or with `DROPBOX_API_URL`
+/// and `DROPBOX_CONTENT_URL`, e.g. to use a mock server.
+#[derive(Debug, Clone)]
+pub struct ApiHosts {
+    api: String,
+    content: String,
+}
+
+impl ApiHosts {
+    pub fn new(api: &str, content: &str) -> Self {
+        Self {
+            api: api.trim_end_matches('/').to_string(),
+            content: content.trim_end_matches('/').to_string(),
         }
     }
+
+    pub fn from_config(config: &DropboxConfig) -> Self {
+        let base = |url: &Option<String>, variable: &str, default: &str| {
+            url.clone()
+                .or_else(|| std::env::var(variable).ok())
+                .unwrap_or(default.to_string())
+        };
+        Self::new(
+            &base(&config.api_url, "DROPBOX_API_URL", DEFAULT_API_URL),
+            &base(
+                &config.content_url,
+                "DROPBOX_CONTENT_URL",
+                DEFAULT_CONTENT_URL,
+            ),
+        )
+    }
+
+    pub fn url(&self, url: &ApiUrl) -> String {
+        let base = match url.host() {
+            Host::Web => WEB_URL,
+            Host::Api => &self.api,
+            Host::Content => &self.content,
+        };
+        format!("{base}{}", url.path())
+    }
 }
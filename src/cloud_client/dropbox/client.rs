@@ -1,126 +1,495 @@
-use crate::cloud_client::dropbox::api_url::ApiUrl;
+use crate::cloud_client::authenticator::Authenticator;
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::dropbox::api_url::{ApiHosts, ApiUrl};
+use crate::cloud_client::dropbox::content_hash::DropboxContentHasher;
+use crate::cloud_client::dropbox::entities::metadata::{FileMetadata, Metadata};
+use crate::cloud_client::dropbox::oauth::StoredCredentials;
+use crate::cloud_client::dropbox::parameters::copy_reference::{
+    CopyReferenceGetParametersBuilder, CopyReferenceSaveParametersBuilder,
+};
+use crate::cloud_client::dropbox::parameters::create_folder::CreateFolderParametersBuilder;
 use crate::cloud_client::dropbox::parameters::delete::DeleteParametersBuilder;
 use crate::cloud_client::dropbox::parameters::download::DownloadParametersBuilder;
+use crate::cloud_client::dropbox::parameters::get_metadata::GetMetadataParametersBuilder;
 use crate::cloud_client::dropbox::parameters::list_folder::ListFolderParametersBuilder;
-use crate::cloud_client::dropbox::parameters::upload::UploadParametersBuilder;
+use crate::cloud_client::dropbox::parameters::list_folder_continue::ListFolderContinueParametersBuilder;
+use crate::cloud_client::dropbox::parameters::relocation::RelocationParametersBuilder;
+use crate::cloud_client::dropbox::parameters::token::TokenParameters;
+use crate::cloud_client::dropbox::parameters::upload::{UploadParameters, UploadParametersBuilder};
+use crate::cloud_client::dropbox::parameters::upload_session::{
+    UploadSessionAppendParametersBuilder, UploadSessionCursorBuilder,
+    UploadSessionFinishParametersBuilder, UploadSessionStartParametersBuilder,
+};
+use crate::cloud_client::dropbox::responses::copy_reference::CopyReferenceGetResult;
+use crate::cloud_client::dropbox::responses::error::ApiErrorResponse;
 use crate::cloud_client::dropbox::responses::list_folder::ListFolderResult;
-use crate::cloud_client::CloudClient;
-use crate::errors::{AppError, BAD_REQUEST_ERROR, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR, OTHER_ERROR, PREPARE_AUTHORIZATION_HEADER_ERROR, UNAUTHORIZED_ERROR};
-use reqwest::blocking::{Body, Client, ClientBuilder};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
-use std::fs::File;
-use std::io::Read;
-use std::io::Write;
-use std::path::PathBuf;
+use crate::cloud_client::dropbox::responses::upload_session::UploadSessionStartResult;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::http::{ApiResponses, ErrorResponse};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::upload_state::{FileFingerprint, PendingUpload, UploadState};
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions};
+use crate::config::{DropboxConfig, RemoteConfig};
+use crate::credentials::CredentialStore;
+use crate::errors::{AppError, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR};
+use crate::utilities::environment::read_positive_variable;
+use crate::utilities::files::{partial_path, write_resumable};
+use bytes::Bytes;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::{CONTENT_TYPE, RANGE};
 use reqwest::StatusCode;
-use tracing::{debug, info, instrument};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::mem;
+use std::path::{Component, Path, PathBuf};
+use tracing::{info, instrument, warn};
 
 static DROPBOX_API_HEADER: &str = "Dropbox-API-Arg";
+static DROPBOX_API_RESULT_HEADER: &str = "Dropbox-API-Result";
+
+/// Dropbox rejects `files/upload` requests with bodies larger than 150 MiB
+const DEFAULT_UPLOAD_SESSION_THRESHOLD: u64 = 150 * 1024 * 1024;
+const DEFAULT_UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
 
 #[derive(Debug)]
 pub struct DropboxClient {
     client: Client,
+    /// Base URLs requests are sent to
+    hosts: ApiHosts,
+    authenticator: Authenticator<TokenParameters>,
+    /// Files larger than this are uploaded with an upload session
+    upload_session_threshold: u64,
+    /// Size of a single upload session chunk
+    upload_chunk_size: u64,
+    /// Keeps track of upload sessions, so they can be resumed after restart
+    upload_state: UploadState,
+    /// Decides which failed requests are repeated and how
+    retry_policy: RetryPolicy,
+    /// Dropbox folder all paths are relative to, the whole Dropbox if absent
+    root: Option<PathBuf>,
 }
 
 impl DropboxClient {
-    pub fn build() -> Result<DropboxClient, AppError> {
-        let token =
-            std::env::var("DROPBOX_ACCESS_TOKEN").map_err(|_| AppError::AbsentAccessToken)?;
-        let token = format!("Bearer {token}");
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(token.as_str()).map_err(|_| {
-                AppError::PrepareClient(PREPARE_AUTHORIZATION_HEADER_ERROR.to_string())
-            })?,
-        );
-
+    /// Builds client of the remote named `name`.
+    /// Settings absent in the configuration are read from environment variables.
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &DropboxConfig,
+        credential_store: &dyn CredentialStore,
+    ) -> Result<DropboxClient, AppError> {
+        // Large transfers may take much longer than the default 30 seconds request timeout
         let client = ClientBuilder::new()
-            .default_headers(headers)
+            .timeout(None)
             .build()
             .map_err(|_| AppError::PrepareClient(BUILD_REQUEST_CLIENT_ERROR.to_string()))?;
+        let hosts = ApiHosts::from_config(config);
+        let refresh_parameters =
+            StoredCredentials::load(credential_store, remote.credential_key(name))?
+                .map(|credentials| credentials.refresh_parameters())
+                .transpose()?;
+        let authenticator = Authenticator::build(
+            client.clone(),
+            hosts.url(&ApiUrl::Token),
+            refresh_parameters,
+            "DROPBOX_ACCESS_TOKEN",
+        )?;
+
+        let upload_session_threshold = match config.upload_session_threshold {
+            Some(threshold) => threshold.get(),
+            None => read_positive_variable(
+                "DROPBOX_UPLOAD_SESSION_THRESHOLD",
+                DEFAULT_UPLOAD_SESSION_THRESHOLD,
+            )?,
+        };
+        let upload_chunk_size = match config.upload_chunk_size {
+            Some(chunk_size) => chunk_size.get(),
+            None => read_positive_variable("DROPBOX_UPLOAD_CHUNK_SIZE", DEFAULT_UPLOAD_CHUNK_SIZE)?,
+        };
 
-        Ok(Self { client })
+        Ok(Self {
+            client,
+            hosts,
+            authenticator,
+            upload_session_threshold,
+            upload_chunk_size,
+            upload_state: UploadState::from_env(name),
+            retry_policy: remote.retry.policy()?,
+            root: remote.root.clone(),
+        })
     }
-}
 
-impl CloudClient for DropboxClient {
-    #[instrument(name = "Dropbox download", skip(self))]
-    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
-        info!("Downloading...");
+    /// Resolves path of the remote into path in Dropbox
+    fn remote_path(&self, path: &Path) -> PathBuf {
+        let Some(root) = &self.root else {
+            return path.to_path_buf();
+        };
+        let relative: PathBuf = path
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect();
+        if relative.as_os_str().is_empty() {
+            root.clone()
+        } else {
+            root.join(relative)
+        }
+    }
+
+    /// Uploads file chunk by chunk with `files/upload_session/*` requests,
+    /// so neither the 150 MiB limit of `files/upload` nor the file size matter.
+    /// Progress is recorded in the upload state, so an interrupted upload of unchanged file
+    /// continues from the last committed offset.
+    #[instrument(name = "Dropbox upload session", skip(self))]
+    fn upload_in_session(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let from_path = fs::canonicalize(from_path).map_err(AppError::Io)?;
+        let fingerprint = FileFingerprint::of(&from_path)?;
+
+        if let Some(mut upload) = self.upload_state.find(&from_path, &to_path)? {
+            if upload.fingerprint == fingerprint {
+                upload.options = options.clone();
+                info!("Resuming upload from {} bytes...", upload.offset);
+                match self.continue_upload_session(upload) {
+                    // Session has expired or has been closed without being committed
+                    Err(error @ (AppError::NotFound(_) | AppError::UploadSessionLost)) => {
+                        info!("Unable to resume upload ({error}), restarting upload")
+                    }
+                    result => return result,
+                }
+            } else {
+                info!("Local file has changed since the last attempt, restarting upload");
+            }
+        }
+
+        let session_id = self.start_upload_session()?;
+        let upload = PendingUpload {
+            remote: self.upload_state.remote().to_string(),
+            from_path,
+            to_path,
+            session_id,
+            offset: 0,
+            fingerprint,
+            options,
+        };
+        self.upload_state.save(&upload)?;
+        self.continue_upload_session(upload)
+    }
+
+    fn start_upload_session(&self) -> Result<String, AppError> {
+        info!("Starting upload session...");
+        let parameters = UploadSessionStartParametersBuilder::default()
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        Ok(self
+            .send_content_request(ApiUrl::UploadSessionStart, &parameters, Bytes::new())?
+            .json::<UploadSessionStartResult>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?
+            .session_id)
+    }
 
+    fn continue_upload_session(&self, mut upload: PendingUpload) -> Result<(), AppError> {
+        let mut file = File::open(&upload.from_path).map_err(AppError::Io)?;
+        file.seek(SeekFrom::Start(upload.offset))
+            .map_err(AppError::Io)?;
+
+        let session_id = upload.session_id.clone();
+        let commit = upload_parameters(self.remote_path(&upload.to_path), &upload.options)?;
+        self.send_session_chunks(
+            &mut file,
+            upload.fingerprint.size(),
+            &session_id,
+            upload.offset,
+            commit,
+            |offset| {
+                upload.offset = offset;
+                self.upload_state.save(&upload)
+            },
+        )?;
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    /// Appends chunks read from `reader` to the upload session starting from `offset`,
+    /// and commits the session with `commit` once `size` bytes are sent.
+    /// `on_append` is called with the new offset after every appended chunk.
+    /// If the session has received only a part of the last chunk, e.g. the response was lost,
+    /// the rest of the chunk is sent from the offset the session expects.
+    fn send_session_chunks(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        session_id: &str,
+        mut offset: u64,
+        commit: UploadParameters,
+        mut on_append: impl FnMut(u64) -> Result<(), AppError>,
+    ) -> Result<Entry, AppError> {
+        let mut chunk = Vec::with_capacity(self.upload_chunk_size as usize);
+        loop {
+            reader
+                .take(self.upload_chunk_size - chunk.len() as u64)
+                .read_to_end(&mut chunk)
+                .map_err(AppError::Io)?;
+            let read = chunk.len() as u64;
+            let content = Bytes::from(mem::take(&mut chunk));
+
+            let cursor = UploadSessionCursorBuilder::default()
+                .session_id(session_id.to_string())
+                .offset(offset)
+                .build()
+                .map_err(|_| AppError::PrepareRequestParameters)?;
+
+            let result = if read < self.upload_chunk_size || offset + read >= size {
+                let parameters = UploadSessionFinishParametersBuilder::default()
+                    .cursor(cursor)
+                    .commit(commit.clone())
+                    .build()
+                    .map_err(|_| AppError::PrepareRequestParameters)?;
+                self.send_content_request(ApiUrl::UploadSessionFinish, &parameters, content.clone())
+                    .map(Some)
+            } else {
+                let parameters = UploadSessionAppendParametersBuilder::default()
+                    .cursor(cursor)
+                    .build()
+                    .map_err(|_| AppError::PrepareRequestParameters)?;
+                self.send_content_request(ApiUrl::UploadSessionAppend, &parameters, content.clone())
+                    .map(|_| None)
+            };
+
+            match result {
+                Ok(Some(response)) => {
+                    info!("File has been uploaded");
+                    return receive_file_metadata(response);
+                }
+                Ok(None) => offset += read,
+                Err(AppError::IncorrectUploadOffset(correct_offset))
+                    if (offset + 1..=offset + read).contains(&correct_offset) =>
+                {
+                    info!("Upload session expects offset {correct_offset}, continuing from it");
+                    chunk = content[(correct_offset - offset) as usize..].to_vec();
+                    offset = correct_offset;
+                }
+                Err(error) => return Err(error),
+            }
+            on_append(offset)?;
+            info!("Uploaded {offset} of {size} bytes");
+        }
+    }
+
+    /// Sends request to content endpoint with parameters in `Dropbox-API-Arg` header.
+    /// Content is shared rather than copied, as it may be up to 150 MiB large.
+    fn send_content_request<P: Serialize>(
+        &self,
+        url: ApiUrl,
+        parameters: &P,
+        body: Bytes,
+    ) -> Result<Response, AppError> {
+        let parameters = serde_json::to_string(parameters).map_err(|_| AppError::PrepareRequest)?;
+
+        self.send_authorized(url.is_idempotent(), || {
+            self.client
+                .post(self.hosts.url(&url))
+                .header(DROPBOX_API_HEADER, parameters.as_str())
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .body(body.clone())
+        })
+    }
+
+    /// Sends request to RPC endpoint with parameters in JSON body
+    fn send_rpc_request<P: Serialize>(
+        &self,
+        url: ApiUrl,
+        parameters: &P,
+    ) -> Result<Response, AppError> {
+        self.send_authorized(url.is_idempotent(), || {
+            self.client.post(self.hosts.url(&url)).json(parameters)
+        })
+    }
+
+    /// Requests file content, starting from `offset` byte if it is specified
+    fn send_download_request(
+        &self,
+        from_path: PathBuf,
+        offset: Option<u64>,
+    ) -> Result<Response, AppError> {
         let parameters = DownloadParametersBuilder::default()
             .path(from_path)
             .build()
             .map_err(|_| AppError::PrepareRequestParameters)?;
+        let parameters =
+            serde_json::to_string(&parameters).map_err(|_| AppError::PrepareRequest)?;
+
+        self.send_authorized(ApiUrl::Download.is_idempotent(), || {
+            let request = self
+                .client
+                .post(self.hosts.url(&ApiUrl::Download))
+                .header(DROPBOX_API_HEADER, parameters.as_str());
+            match offset {
+                Some(offset) => request.header(RANGE, format!("bytes={offset}-")),
+                None => request,
+            }
+        })
+    }
+
+    /// Sends request built by `request` with access token, following the retry policy.
+    /// Request rejected because of expired access token is repeated once with the refreshed one.
+    fn send_authorized(
+        &self,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, AppError> {
+        self.authenticator
+            .send::<DropboxApi>(&self.retry_policy, idempotent, request)
+    }
+
+    /// Lists cloud folder page by page, so very large folders can be consumed lazily
+    /// With `recursive` set, pages contain the contents of all subfolders as well
+    pub fn list_folder_pages(&self, path: PathBuf, recursive: bool) -> ListFolderPages<'_> {
+        ListFolderPages {
+            client: self,
+            state: ListFolderPagesState::Start(path, recursive),
+        }
+    }
+
+    #[instrument(name = "Dropbox list folder", skip(self))]
+    fn list_folder(&self, path: PathBuf, recursive: bool) -> Result<ListFolderResult, AppError> {
+        let parameters = ListFolderParametersBuilder::default()
+            .path(path)
+            .recursive(recursive)
+            .limit(Some(2000))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
 
-        let response = self
-            .client
-            .post(ApiUrl::Download.as_url())
-            .header(
-                DROPBOX_API_HEADER,
-                serde_json::to_string(&parameters).map_err(|_| AppError::PrepareRequest)?,
-            )
-            .send()
-            .map_err(|error| AppError::SendRequest(error.to_string()))?;
-
-        debug!("Response: {:?}", response);
-        match response.status() {
-            StatusCode::OK => {
-                info!("File has been downloaded");
-
-                let bytes = response
-                    .bytes()
-                    .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
-
-                let mut file = File::create(to_path).map_err(AppError::Io)?;
-                file.write_all(bytes.as_ref()).map_err(AppError::Io)?;
-
-                info!("File has been saved");
-                Ok(())
+        let response = self.send_rpc_request(ApiUrl::ListFolder, &parameters)?;
+        Self::receive_list_folder_result(response)
+    }
+
+    #[instrument(name = "Dropbox list folder continue", skip(self))]
+    fn list_folder_continue(&self, cursor: String) -> Result<ListFolderResult, AppError> {
+        let parameters = ListFolderContinueParametersBuilder::default()
+            .cursor(cursor)
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+
+        let response = self.send_rpc_request(ApiUrl::ListFolderContinue, &parameters)?;
+        Self::receive_list_folder_result(response)
+    }
+
+    fn receive_list_folder_result(response: Response) -> Result<ListFolderResult, AppError> {
+        info!("Cloud folder entries has been received");
+        response
+            .json::<ListFolderResult>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    fn relocate(
+        &self,
+        url: ApiUrl,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let parameters = RelocationParametersBuilder::default()
+            .from_path(self.remote_path(&from_path))
+            .to_path(self.remote_path(&to_path))
+            .autorename(options.autorename)
+            .allow_ownership_transfer(options.allow_ownership_transfer)
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+
+        self.send_rpc_request(url, &parameters)?;
+        Ok(())
+    }
+}
+
+impl CloudClient for DropboxClient {
+    fn backend(&self) -> &'static str {
+        "dropbox"
+    }
+
+    /// Downloads into the partial file next to `to_path`, resuming with a `Range` request
+    /// when the partial file was left by an interrupted download of the same file revision
+    #[instrument(name = "Dropbox download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        let from_path = self.remote_path(&from_path);
+        let partial_path = partial_path(&to_path).map_err(AppError::Io)?;
+        let revision_path = revision_path(&partial_path);
+
+        if let Some((offset, saved_revision)) = resumable_download(&partial_path, &revision_path) {
+            info!("Resuming download from {offset} bytes...");
+            match self.send_download_request(from_path.clone(), Some(offset)) {
+                Ok(response)
+                    if response.status() == StatusCode::PARTIAL_CONTENT
+                        && download_revision(&response).is_ok_and(|rev| rev == saved_revision) =>
+                {
+                    return save_download(response, &to_path, &revision_path, true);
+                }
+                // Revision is either different or unknown, so the partial file can't be trusted
+                Ok(_) => {
+                    info!("Cloud file has changed since the last attempt, restarting download")
+                }
+                Err(error) => info!("Unable to resume download ({error}), restarting download"),
             }
-            StatusCode::BAD_REQUEST => Err(AppError::Request(BAD_REQUEST_ERROR.to_string())),
-            StatusCode::UNAUTHORIZED => Err(AppError::Request(UNAUTHORIZED_ERROR.to_string())),
-            _ => Err(AppError::Request(OTHER_ERROR.to_string())),
         }
+
+        info!("Downloading...");
+        let response = self.send_download_request(from_path, None)?;
+        let revision = download_revision(&response)?;
+        fs::write(&revision_path, revision).map_err(AppError::Io)?;
+        save_download(response, &to_path, &revision_path, false)
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let response = self.send_download_request(self.remote_path(&path), None)?;
+        Ok(Box::new(response))
     }
 
     #[instrument(name = "Dropbox upload", skip(self))]
-    fn upload(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let file_size = std::fs::metadata(&from_path).map_err(AppError::Io)?.len();
+        if file_size > self.upload_session_threshold {
+            return self.upload_in_session(from_path, to_path, options);
+        }
+
         info!("Reading original file");
         let mut file = File::open(from_path).map_err(AppError::Io)?;
+        self.upload_stream(&mut file, file_size, to_path, options)?;
+        Ok(())
+    }
+
+    /// Files larger than the upload session threshold are sent in chunks, but unlike uploads
+    /// of local files such uploads can't be resumed
+    #[instrument(name = "Dropbox upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let parameters = upload_parameters(self.remote_path(&to_path), &options)?;
+        if size > self.upload_session_threshold {
+            let session_id = self.start_upload_session()?;
+            return self.send_session_chunks(reader, size, &session_id, 0, parameters, |_| Ok(()));
+        }
+
         let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes).map_err(AppError::Io)?;
+        reader.read_to_end(&mut bytes).map_err(AppError::Io)?;
 
         info!("Uploading...");
-        let parameters = UploadParametersBuilder::default()
-            .path(to_path)
-            .build()
-            .map_err(|_| AppError::PrepareRequestParameters)?;
 
-        let response = self
-            .client
-            .post(ApiUrl::Upload.as_url())
-            .header(
-                DROPBOX_API_HEADER,
-                serde_json::to_string(&parameters).map_err(|_| AppError::PrepareRequest)?,
-            )
-            .header(CONTENT_TYPE, "application/octet-stream")
-            .body(Body::from(bytes.to_vec()))
-            .send()
-            .map_err(|error| AppError::SendRequest(error.to_string()))?;
-
-        debug!("Response: {:?}", response);
-        match response.status() {
-            StatusCode::OK => {
-                info!("File has been uploaded");
-                Ok(())
-            }
-            StatusCode::BAD_REQUEST => Err(AppError::Request(BAD_REQUEST_ERROR.to_string())),
-            StatusCode::UNAUTHORIZED => Err(AppError::Request(UNAUTHORIZED_ERROR.to_string())),
-            _ => Err(AppError::Request(OTHER_ERROR.to_string())),
-        }
+        let response = self.send_content_request(ApiUrl::Upload, &parameters, bytes.into())?;
+
+        info!("File has been uploaded");
+        receive_file_metadata(response)
     }
 
     #[instrument(name = "Dropbox delete", skip(self))]
@@ -128,59 +497,265 @@ impl CloudClient for DropboxClient {
         info!("Deleting...");
 
         let parameters = DeleteParametersBuilder::default()
-            .path(path)
+            .path(self.remote_path(&path))
             .build()
             .map_err(|_| AppError::PrepareRequestParameters)?;
 
-        let response = self
-            .client
-            .post(ApiUrl::Delete.as_url())
-            .json(&parameters)
-            .send()
-            .map_err(|error| AppError::SendRequest(error.to_string()))?;
-
-        debug!("Response: {:?}", response);
-        match response.status() {
-            StatusCode::OK => {
-                info!("File has been deleted");
-                Ok(())
-            }
-            StatusCode::BAD_REQUEST => Err(AppError::Request(BAD_REQUEST_ERROR.to_string())),
-            StatusCode::UNAUTHORIZED => Err(AppError::Request(UNAUTHORIZED_ERROR.to_string())),
-            _ => Err(AppError::Request(OTHER_ERROR.to_string())),
+        self.send_rpc_request(ApiUrl::Delete, &parameters)?;
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "Dropbox create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        info!("Creating folder...");
+
+        let parameters = CreateFolderParametersBuilder::default()
+            .path(self.remote_path(&path))
+            .autorename(autorename)
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+
+        self.send_rpc_request(ApiUrl::CreateFolder, &parameters)?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "Dropbox move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Moving...");
+        self.relocate(ApiUrl::Move, from_path, to_path, options)?;
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    #[instrument(name = "Dropbox copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Copying...");
+        self.relocate(ApiUrl::Copy, from_path, to_path, options)?;
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    #[instrument(name = "Dropbox get metadata", skip(self))]
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let parameters = GetMetadataParametersBuilder::default()
+            .path(self.remote_path(&path))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+
+        let metadata = self
+            .send_rpc_request(ApiUrl::GetMetadata, &parameters)?
+            .json::<Metadata>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        match metadata {
+            Metadata::File(file) => Ok(file.into()),
+            Metadata::Folder(folder) => Ok(folder.into()),
+            Metadata::Deleted(_) => Err(AppError::NotFound(path.display().to_string())),
         }
     }
 
-    fn list_entries(&self, path: PathBuf) -> Result<Vec<String>, AppError> {
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
         info!("Listing entries...");
 
-        let parameters = ListFolderParametersBuilder::default()
-            .path(path.clone())
-            .limit(Some(2000))
+        let mut entries = Vec::new();
+        for page in self.list_folder_pages(self.remote_path(&path), false) {
+            entries.extend(page?.into_entries());
+        }
+
+        info!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    /// Uses a single recursive `files/list_folder` listing instead of listing every subfolder
+    fn list_entries_recursive(&self, path: PathBuf) -> Result<Vec<(PathBuf, Entry)>, AppError> {
+        info!("Listing entries recursively...");
+
+        let path = self.remote_path(&path);
+        let mut entries = Vec::new();
+        for page in self.list_folder_pages(path.clone(), true) {
+            entries.extend(page?.into_entries_relative_to(&path));
+        }
+
+        info!("Received {} entries", entries.len());
+        Ok(entries)
+    }
+
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        self.upload_state.load()
+    }
+
+    /// Dropbox has no way to cancel upload session explicitly, it just expires after a week
+    fn abandon_upload(&self, upload: &PendingUpload) -> Result<(), AppError> {
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    fn content_hasher(&self) -> Option<Box<dyn ContentHasher>> {
+        Some(Box::<DropboxContentHasher>::default())
+    }
+
+    /// Copy references let entries be copied between different Dropbox accounts
+    fn copy_reference(&self, path: PathBuf) -> Result<Option<String>, AppError> {
+        let parameters = CopyReferenceGetParametersBuilder::default()
+            .path(self.remote_path(&path))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+
+        let result = self
+            .send_rpc_request(ApiUrl::CopyReferenceGet, &parameters)?
+            .json::<CopyReferenceGetResult>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        Ok(Some(result.copy_reference))
+    }
+
+    #[instrument(name = "Dropbox save copy reference", skip(self, reference))]
+    fn save_copy_reference(&self, reference: &str, to_path: PathBuf) -> Result<(), AppError> {
+        let parameters = CopyReferenceSaveParametersBuilder::default()
+            .copy_reference(reference.to_string())
+            .path(self.remote_path(&to_path))
             .build()
             .map_err(|_| AppError::PrepareRequestParameters)?;
 
-        let response = self
-            .client
-            .post(ApiUrl::ListFolder.as_url())
-            .json(&parameters)
-            .send()
-            .map_err(|error| AppError::SendRequest(error.to_string()))?;
-
-        debug!("Response: {:?}", response);
-        match response.status() {
-            StatusCode::OK => {
-                info!("Cloud folder entries has been received");
-                let list_folder = response
-                    .json::<ListFolderResult>()
-                    .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?
-                    .get_simple_list();
-                info!("List: {:?}", list_folder);
-                Ok(list_folder)
+        self.send_rpc_request(ApiUrl::CopyReferenceSave, &parameters)?;
+        info!("Entry has been copied by reference");
+        Ok(())
+    }
+}
+
+/// Prepares parameters of `files/upload` request, also used to commit upload sessions
+fn upload_parameters(
+    to_path: PathBuf,
+    options: &UploadOptions,
+) -> Result<UploadParameters, AppError> {
+    UploadParametersBuilder::default()
+        .path(to_path)
+        .mode(Some(options.mode.clone().into()))
+        .autorename(Some(options.autorename))
+        .mute(Some(options.mute))
+        .strict_conflict(Some(options.strict_conflict))
+        .build()
+        .map_err(|_| AppError::PrepareRequestParameters)
+}
+
+fn receive_file_metadata(response: Response) -> Result<Entry, AppError> {
+    response
+        .json::<FileMetadata>()
+        .map(Entry::from)
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+}
+
+/// Responses of Dropbox API
+struct DropboxApi;
+
+impl ApiResponses for DropboxApi {
+    fn decode_error(response: ErrorResponse) -> AppError {
+        let ErrorResponse {
+            status,
+            retry_after,
+            body,
+            ..
+        } = response;
+        match status {
+            // Malformed requests are described with plain text
+            StatusCode::BAD_REQUEST => AppError::Request(body),
+            StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited { retry_after },
+            status if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message: body,
+            },
+            status => match serde_json::from_str::<ApiErrorResponse>(&body) {
+                Ok(error) => error.into_app_error(status),
+                Err(_) => AppError::Request(format!("{status}: {body}")),
+            },
+        }
+    }
+}
+
+/// Returns path of the sidecar file recording the revision of the file being downloaded
+fn revision_path(partial_path: &Path) -> PathBuf {
+    let mut revision_path = partial_path.as_os_str().to_os_string();
+    revision_path.push(".rev");
+    PathBuf::from(revision_path)
+}
+
+/// Returns size and recorded revision of the partial file left by an interrupted download
+fn resumable_download(partial_path: &Path, revision_path: &Path) -> Option<(u64, String)> {
+    let offset = fs::metadata(partial_path).ok()?.len();
+    let revision = fs::read_to_string(revision_path).ok()?;
+    (offset > 0).then(|| (offset, revision.trim().to_string()))
+}
+
+/// Extracts file revision from `Dropbox-API-Result` header of download response
+fn download_revision(response: &Response) -> Result<String, AppError> {
+    response
+        .headers()
+        .get(DROPBOX_API_RESULT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| serde_json::from_str::<FileMetadata>(value).ok())
+        .map(|result| result.rev)
+        .ok_or_else(|| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+}
+
+/// Writes response body into place, keeping the partial file on failure so it can be resumed
+fn save_download(
+    mut response: Response,
+    to_path: &Path,
+    revision_path: &Path,
+    append: bool,
+) -> Result<(), AppError> {
+    info!("Saving file...");
+    let written = write_resumable(&mut response, to_path, append).map_err(AppError::Io)?;
+    let _ = fs::remove_file(revision_path);
+
+    info!("File has been saved ({written} bytes)");
+    Ok(())
+}
+
+/// Iterator over the pages of a cloud folder listing.
+/// The first page is requested with `files/list_folder`, every next one with
+/// `files/list_folder/continue` until the response reports `has_more == false`.
+pub struct ListFolderPages<'a> {
+    client: &'a DropboxClient,
+    state: ListFolderPagesState,
+}
+
+enum ListFolderPagesState {
+    Start(PathBuf, bool),
+    Continue(String),
+    Done,
+}
+
+impl Iterator for ListFolderPages<'_> {
+    type Item = Result<ListFolderResult, AppError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page = match std::mem::replace(&mut self.state, ListFolderPagesState::Done) {
+            ListFolderPagesState::Start(path, recursive) => {
+                self.client.list_folder(path, recursive)
+            }
+            ListFolderPagesState::Continue(cursor) => self.client.list_folder_continue(cursor),
+            ListFolderPagesState::Done => return None,
+        };
+
+        if let Ok(result) = &page {
+            if result.has_more() {
+                self.state = ListFolderPagesState::Continue(result.cursor().to_string());
             }
-            StatusCode::BAD_REQUEST => Err(AppError::Request(BAD_REQUEST_ERROR.to_string())),
-            StatusCode::UNAUTHORIZED => Err(AppError::Request(UNAUTHORIZED_ERROR.to_string())),
-            _ => Err(AppError::Request(OTHER_ERROR.to_string())),
         }
+        Some(page)
     }
 }
@@ -0,0 +1,44 @@
+use crate::cloud_client::content_hash::ContentHasher;
+use sha2::{Digest, Sha256};
+
+/// Dropbox hashes content in blocks of 4 MiB
+const BLOCK_SIZE: usize = 4 * 1024 * 1024;
+
+/// Computes `content_hash` of Dropbox file metadata: SHA-256 of the concatenated SHA-256 hashes
+/// of every 4 MiB block of the file.
+/// More details [here](https://www.dropbox.com/developers/reference/content-hash)
+#[derive(Default)]
+pub struct DropboxContentHasher {
+    overall: Sha256,
+    block: Sha256,
+    block_size: usize,
+}
+
+impl ContentHasher for DropboxContentHasher {
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let taken = data.len().min(BLOCK_SIZE - self.block_size);
+            self.block.update(&data[..taken]);
+            self.block_size += taken;
+            data = &data[taken..];
+
+            if self.block_size == BLOCK_SIZE {
+                self.overall
+                    .update(std::mem::take(&mut self.block).finalize());
+                self.block_size = 0;
+            }
+        }
+    }
+
+    fn finish(mut self: Box<Self>) -> String {
+        if self.block_size > 0 {
+            let block = std::mem::take(&mut self.block).finalize();
+            self.overall.update(block);
+        }
+        self.overall
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
@@ -1,45 +1,67 @@
-use de::Error;
-use serde::{de, Deserialize, Deserializer, Serialize};
-use serde_json::Value;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 
-#[derive(Serialize, Deserialize, Debug, PartialEq)]
-pub enum Tag {
-    File,
-    Folder,
-    Deleted,
+/// Metadata of cloud entry, distinguished by ".tag" field of received response
+/// More details [here](https://www.dropbox.com/developers/documentation/http/documentation#files-list_folder)
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = ".tag", rename_all = "lowercase")]
+pub enum Metadata {
+    File(FileMetadata),
+    Folder(FolderMetadata),
+    Deleted(DeletedMetadata),
 }
 
-/// Contains only "tag" and "name" fields from received response
-/// More details [here](https://www.dropbox.com/developers/documentation/http/documentation#files-list_folder)
-#[derive(Debug)]
-pub struct Metadata {
-    pub tag: Tag,
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FileMetadata {
+    pub name: String,
+    pub id: String,
+    pub path_display: Option<String>,
+    pub path_lower: Option<String>,
+    pub size: u64,
+    pub rev: String,
+    pub client_modified: DateTime<Utc>,
+    pub server_modified: DateTime<Utc>,
+    pub content_hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FolderMetadata {
     pub name: String,
+    pub id: String,
+    pub path_display: Option<String>,
+    pub path_lower: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct DeletedMetadata {
+    pub name: String,
+    pub path_display: Option<String>,
+    pub path_lower: Option<String>,
+}
+
+impl From<FileMetadata> for Entry {
+    fn from(file: FileMetadata) -> Self {
+        Entry {
+            name: file.name,
+            kind: EntryKind::File,
+            size: file.size,
+            modified: Some(file.server_modified),
+            hash: file.content_hash,
+            id: Some(file.id),
+        }
+    }
 }
 
-impl<'de> Deserialize<'de> for Metadata {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        let value: Value = Deserialize::deserialize(deserializer)?;
-        let tag = value
-            .get(".tag")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::missing_field(".tag"))?;
-        let tag_enum = match tag {
-            "file" => Tag::File,
-            "folder" => Tag::Folder,
-            "deleted" => Tag::Deleted,
-            _ => return Err(Error::unknown_variant(tag, &["file", "folder", "deleted"])),
-        };
-        let name = value
-            .get("name")
-            .and_then(|v| v.as_str())
-            .ok_or_else(|| Error::missing_field("name"))?;
-        Ok(Metadata {
-            tag: tag_enum,
-            name: name.to_string(),
-        })
+impl From<FolderMetadata> for Entry {
+    fn from(folder: FolderMetadata) -> Self {
+        Entry {
+            name: folder.name,
+            kind: EntryKind::Folder,
+            size: 0,
+            modified: None,
+            hash: None,
+            id: Some(folder.id),
+        }
     }
 }
@@ -0,0 +1,570 @@
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::dropbox::content_hash::DropboxContentHasher;
+use crate::cloud_client::mock_http::{HttpRequest, MockHttp, Reply};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+
+pub static ACCESS_TOKEN: &str = "mock-access-token";
+pub static REFRESH_TOKEN: &str = "mock-refresh-token";
+static MODIFIED: &str = "2024-05-01T12:00:00Z";
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Dropbox API served on a local port, keeping files in memory.
+/// Serves both API and content endpoints, and the token endpoint accepting `REFRESH_TOKEN`.
+/// Answers are shaped after the real API, including its error bodies.
+pub struct MockDropbox {
+    server: MockHttp,
+    state: Arc<Mutex<MockState>>,
+}
+
+/// Request received by the mock server
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub path: String,
+    /// Parameters from `Dropbox-API-Arg` header or JSON body
+    pub arg: Value,
+    pub headers: Vec<(String, String)>,
+}
+
+impl MockRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Response queued to be returned instead of handling the next request to the endpoint
+struct CannedResponse {
+    path: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+struct MockFile {
+    path_display: String,
+    /// `None` for folders
+    content: Option<Vec<u8>>,
+    id: u64,
+    rev: u64,
+}
+
+struct Listing {
+    entries: Vec<Value>,
+    offset: usize,
+}
+
+#[derive(Default)]
+struct MockState {
+    /// Entries by their lowercased paths, as Dropbox paths are case-insensitive
+    files: BTreeMap<String, MockFile>,
+    sessions: HashMap<String, Vec<u8>>,
+    listings: Vec<Listing>,
+    canned: VecDeque<CannedResponse>,
+    requests: Vec<MockRequest>,
+    page_size: Option<usize>,
+    counter: u64,
+}
+
+impl Reply {
+    /// Endpoint-specific error, e.g. `error(&["path", "not_found"])`.
+    /// Union variants are nested under their tags, like lookup errors of the real API.
+    fn error(tags: &[&str]) -> Self {
+        let mut error = json!({});
+        for tag in tags.iter().rev() {
+            error = if error == json!({}) {
+                json!({ ".tag": tag })
+            } else {
+                json!({ ".tag": tag, *tag: error })
+            };
+        }
+        Self::json(
+            409,
+            json!({ "error_summary": format!("{}/...", tags.join("/")), "error": error }),
+        )
+    }
+
+    /// Write failure of uploads, where the reason is flattened next to the `path` tag
+    fn write_error(reason: &str, conflict: Option<&str>) -> Self {
+        let mut reason_value = json!({ ".tag": reason });
+        if let Some(conflict) = conflict {
+            reason_value[reason] = json!({ ".tag": conflict });
+        }
+        let summary = match conflict {
+            Some(conflict) => format!("path/{reason}/{conflict}/..."),
+            None => format!("path/{reason}/..."),
+        };
+        Self::json(
+            409,
+            json!({
+                "error_summary": summary,
+                "error": { ".tag": "path", "reason": reason_value, "upload_session_id": "unused" },
+            }),
+        )
+    }
+
+    /// Malformed requests are rejected with plain text
+    fn bad_request(message: &str) -> Self {
+        Self::new(400, message).header("Content-Type", "text/plain")
+    }
+}
+
+impl MockDropbox {
+    pub fn start() -> MockDropbox {
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let handled_state = state.clone();
+        let server = MockHttp::start(move |request| {
+            let mut state = handled_state.lock().unwrap_or_else(PoisonError::into_inner);
+            state.handle(request)
+        });
+        MockDropbox { server, state }
+    }
+
+    /// Base URL of both API and content hosts
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds file together with its parent folders
+    pub fn add_file(&self, path: &str, content: &[u8]) {
+        let mut state = self.state();
+        state.ensure_parents(path);
+        state.store(path, Some(content.to_vec()));
+    }
+
+    pub fn add_folder(&self, path: &str) {
+        let mut state = self.state();
+        state.ensure_parents(path);
+        state.store(path, None);
+    }
+
+    /// Returns content of the file, `None` if there is no such file
+    pub fn content(&self, path: &str) -> Option<Vec<u8>> {
+        self.state()
+            .files
+            .get(&path.to_lowercase())
+            .and_then(|file| file.content.clone())
+    }
+
+    /// Returns revision of the file as reported in its metadata
+    pub fn revision(&self, path: &str) -> Option<String> {
+        self.state()
+            .files
+            .get(&path.to_lowercase())
+            .filter(|file| file.content.is_some())
+            .map(|file| format!("{:09x}", file.rev))
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.state().files.contains_key(&path.to_lowercase())
+    }
+
+    /// Limits the number of entries in every page of folder listings
+    pub fn set_page_size(&self, page_size: usize) {
+        self.state().page_size = Some(page_size);
+    }
+
+    /// Answers the next request to the endpoint, e.g. `/2/files/delete_v2`,
+    /// with the given response instead of handling it
+    pub fn respond_once(&self, path: &str, status: u16, body: &str) {
+        self.respond_once_with_headers(path, status, &[], body);
+    }
+
+    pub fn respond_once_with_headers(
+        &self,
+        path: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: &str,
+    ) {
+        self.state().canned.push_back(CannedResponse {
+            path: path.to_string(),
+            status,
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.to_string(),
+        });
+    }
+
+    /// Returns all received requests in order
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state().requests.clone()
+    }
+
+    /// Returns received requests to the endpoint
+    pub fn requests_to(&self, path: &str) -> Vec<MockRequest> {
+        self.requests()
+            .into_iter()
+            .filter(|request| request.path == path)
+            .collect()
+    }
+}
+
+impl MockState {
+    fn handle(&mut self, request: HttpRequest) -> Reply {
+        let HttpRequest {
+            target: path,
+            headers,
+            body,
+            ..
+        } = request;
+        let path = path.as_str();
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+        };
+        let is_content = matches!(
+            path,
+            "/2/files/download"
+                | "/2/files/upload"
+                | "/2/files/upload_session/start"
+                | "/2/files/upload_session/append_v2"
+                | "/2/files/upload_session/finish"
+        );
+        let arg = if is_content {
+            header("Dropbox-API-Arg").and_then(|arg| serde_json::from_str(&arg).ok())
+        } else {
+            serde_json::from_slice(&body).ok()
+        };
+        self.requests.push(MockRequest {
+            path: path.to_string(),
+            arg: arg.clone().unwrap_or(Value::Null),
+            headers: headers.clone(),
+        });
+
+        if let Some(index) = self.canned.iter().position(|canned| canned.path == path) {
+            let canned = self.canned.remove(index).expect("canned response exists");
+            return Reply {
+                status: canned.status,
+                headers: canned.headers,
+                body: canned.body.into_bytes(),
+            };
+        }
+
+        if path == "/oauth2/token" {
+            let form = String::from_utf8_lossy(&body).to_string();
+            return if form.contains(&format!("refresh_token={REFRESH_TOKEN}")) {
+                Reply::json(
+                    200,
+                    json!({ "access_token": ACCESS_TOKEN, "token_type": "bearer", "expires_in": 14400 }),
+                )
+            } else {
+                Reply::json(
+                    400,
+                    json!({ "error": "invalid_grant", "error_description": "refresh token is malformed" }),
+                )
+            };
+        }
+        if header("Authorization") != Some(format!("Bearer {ACCESS_TOKEN}")) {
+            return Reply::json(
+                401,
+                json!({ "error_summary": "invalid_access_token/...", "error": { ".tag": "invalid_access_token" } }),
+            );
+        }
+        let Some(arg) = arg else {
+            return Reply::bad_request(&format!(
+                "Error in call to API function \"{}\": request body could not be decoded as JSON",
+                path.trim_start_matches("/2/")
+            ));
+        };
+        let text = |name: &str| arg[name].as_str().unwrap_or_default().to_string();
+
+        match path {
+            "/2/files/get_metadata" => match self.files.get(&key(&text("path"))) {
+                Some(file) => Reply::json(200, metadata(file)),
+                None => Reply::error(&["path", "not_found"]),
+            },
+            "/2/files/download" => self.download(&text("path"), header("Range")),
+            "/2/files/upload" => self.commit(&arg, body),
+            "/2/files/delete_v2" => {
+                let path = key(&text("path"));
+                let Some(file) = self.files.get(&path) else {
+                    return Reply::error(&["path_lookup", "not_found"]);
+                };
+                let result = json!({ "metadata": metadata(file) });
+                self.files
+                    .retain(|other, _| other != &path && !other.starts_with(&format!("{path}/")));
+                Reply::json(200, result)
+            }
+            "/2/files/create_folder_v2" => {
+                let path = text("path");
+                let path = match self.free_path(&path, arg["autorename"].as_bool()) {
+                    Some(path) => path,
+                    None => return Reply::error(&["path", "conflict", "folder"]),
+                };
+                if !self.files.contains_key(&key(parent(&path))) {
+                    self.ensure_parents(&path);
+                }
+                let file = self.store(&path, None);
+                Reply::json(200, json!({ "metadata": metadata(file) }))
+            }
+            "/2/files/list_folder" => self.list_folder(
+                &text("path"),
+                arg["recursive"].as_bool().unwrap_or(false),
+                arg["limit"].as_u64(),
+            ),
+            "/2/files/list_folder/continue" => self.list_folder_continue(&text("cursor")),
+            "/2/files/upload_session/start" => {
+                self.counter += 1;
+                let session_id = format!("session-{}", self.counter);
+                self.sessions.insert(session_id.clone(), body);
+                Reply::json(200, json!({ "session_id": session_id }))
+            }
+            "/2/files/upload_session/append_v2" => match self.append(&arg["cursor"], body) {
+                Ok(()) => Reply::json(200, Value::Null),
+                Err(reply) => reply,
+            },
+            "/2/files/upload_session/finish" => {
+                let session_id = arg["cursor"]["session_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                if let Err(reply) = self.append(&arg["cursor"], body) {
+                    return reply;
+                }
+                let content = self.sessions.remove(&session_id).unwrap_or_default();
+                self.commit(&arg["commit"], content)
+            }
+            _ => Reply::bad_request(&format!("Unknown API function: \"{path}\"")),
+        }
+    }
+
+    fn store(&mut self, path: &str, content: Option<Vec<u8>>) -> &MockFile {
+        self.counter += 1;
+        let counter = self.counter;
+        let file = self.files.entry(key(path)).or_insert(MockFile {
+            path_display: path.to_string(),
+            content: None,
+            id: counter,
+            rev: 0,
+        });
+        file.content = content;
+        file.rev = counter;
+        file
+    }
+
+    fn ensure_parents(&mut self, path: &str) {
+        let mut parent_path = String::new();
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        for segment in segments.iter().take(segments.len().saturating_sub(1)) {
+            parent_path = format!("{parent_path}/{segment}");
+            if !self.files.contains_key(&key(&parent_path)) {
+                self.store(&parent_path.clone(), None);
+            }
+        }
+    }
+
+    /// Returns `path` if it is free, or the first free `name (n).ext` with `autorename`
+    fn free_path(&self, path: &str, autorename: Option<bool>) -> Option<String> {
+        if !self.files.contains_key(&key(path)) {
+            return Some(path.to_string());
+        }
+        if autorename != Some(true) {
+            return None;
+        }
+        let (stem, extension) = match path.rsplit_once('.') {
+            Some((stem, extension)) if !extension.contains('/') => {
+                (stem.to_string(), format!(".{extension}"))
+            }
+            _ => (path.to_string(), String::new()),
+        };
+        (1..)
+            .map(|number| format!("{stem} ({number}){extension}"))
+            .find(|candidate| !self.files.contains_key(&key(candidate)))
+    }
+
+    fn download(&self, path: &str, range: Option<String>) -> Reply {
+        let Some(file) = self.files.get(&key(path)) else {
+            return Reply::error(&["path", "not_found"]);
+        };
+        let Some(content) = &file.content else {
+            return Reply::error(&["path", "not_file"]);
+        };
+        let offset = range
+            .as_deref()
+            .and_then(|range| range.strip_prefix("bytes="))
+            .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+        let (status, body) = match offset {
+            Some(offset) => (206, content[offset.min(content.len())..].to_vec()),
+            None => (200, content.clone()),
+        };
+        Reply {
+            status,
+            headers: vec![
+                (
+                    "Content-Type".to_string(),
+                    "application/octet-stream".to_string(),
+                ),
+                ("Dropbox-API-Result".to_string(), metadata(file).to_string()),
+            ],
+            body,
+        }
+    }
+
+    /// Writes uploaded content according to `files/upload` parameters
+    fn commit(&mut self, arg: &Value, content: Vec<u8>) -> Reply {
+        let path = arg["path"].as_str().unwrap_or_default().to_string();
+        let mode = match &arg["mode"] {
+            Value::String(mode) => mode.clone(),
+            Value::Object(mode) => mode[".tag"].as_str().unwrap_or("add").to_string(),
+            _ => "add".to_string(),
+        };
+        let autorename = arg["autorename"].as_bool();
+
+        let existing = self.files.get(&key(&path));
+        let path = match (mode.as_str(), existing) {
+            (_, None) => path,
+            (_, Some(file)) if file.content.is_none() => {
+                return Reply::write_error("conflict", Some("folder"))
+            }
+            ("overwrite", Some(_)) => path,
+            ("update", Some(file)) if arg["mode"]["update"] == format!("{:09x}", file.rev) => path,
+            // Uploading identical content isn't a conflict unless it is strict
+            (_, Some(file))
+                if file.content.as_deref() == Some(content.as_slice())
+                    && arg["strict_conflict"].as_bool() != Some(true) =>
+            {
+                return Reply::json(200, metadata(file));
+            }
+            _ => match self.free_path(&path, autorename) {
+                Some(path) => path,
+                None => return Reply::write_error("conflict", Some("file")),
+            },
+        };
+
+        self.ensure_parents(&path);
+        let file = self.store(&path, Some(content));
+        Reply::json(200, metadata(file))
+    }
+
+    fn append(&mut self, cursor: &Value, body: Vec<u8>) -> Result<(), Reply> {
+        let session_id = cursor["session_id"].as_str().unwrap_or_default();
+        let Some(content) = self.sessions.get_mut(session_id) else {
+            return Err(Reply::error(&["not_found"]));
+        };
+        if cursor["offset"].as_u64() != Some(content.len() as u64) {
+            return Err(Reply::json(
+                409,
+                json!({
+                    "error_summary": "incorrect_offset/...",
+                    "error": { ".tag": "incorrect_offset", "correct_offset": content.len() },
+                }),
+            ));
+        }
+        content.extend(body);
+        Ok(())
+    }
+
+    fn list_folder(&mut self, path: &str, recursive: bool, limit: Option<u64>) -> Reply {
+        let folder = key(path);
+        if !folder.is_empty() {
+            match self.files.get(&folder) {
+                Some(file) if file.content.is_none() => {}
+                Some(_) => return Reply::error(&["path", "not_folder"]),
+                None => return Reply::error(&["path", "not_found"]),
+            }
+        }
+
+        let prefix = format!("{folder}/");
+        let mut entries: Vec<Value> = self
+            .files
+            .iter()
+            .filter(|(path, _)| match path.strip_prefix(&prefix) {
+                Some(relative) => recursive || !relative.contains('/'),
+                // Recursive listing includes the folder itself
+                None => recursive && **path == folder,
+            })
+            .map(|(_, file)| metadata(file))
+            .collect();
+        entries.sort_by_key(|entry| entry["path_lower"].as_str().map(str::to_string));
+
+        let page_size = self
+            .page_size
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .min(limit.unwrap_or(u64::MAX) as usize);
+        self.listings.push(Listing { entries, offset: 0 });
+        self.next_page(self.listings.len() - 1, page_size)
+    }
+
+    fn list_folder_continue(&mut self, cursor: &str) -> Reply {
+        let parsed = cursor
+            .strip_prefix("cursor-")
+            .and_then(|cursor| cursor.split_once('-'))
+            .and_then(|(index, page_size)| Some((index.parse().ok()?, page_size.parse().ok()?)));
+        match parsed {
+            Some((index, page_size)) if index < self.listings.len() => {
+                self.next_page(index, page_size)
+            }
+            _ => Reply::error(&["reset"]),
+        }
+    }
+
+    fn next_page(&mut self, index: usize, page_size: usize) -> Reply {
+        let listing = &mut self.listings[index];
+        let end = (listing.offset + page_size).min(listing.entries.len());
+        let page = listing.entries[listing.offset..end].to_vec();
+        listing.offset = end;
+        Reply::json(
+            200,
+            json!({
+                "entries": page,
+                "cursor": format!("cursor-{index}-{page_size}"),
+                "has_more": end < listing.entries.len(),
+            }),
+        )
+    }
+}
+
+/// Dropbox paths are case-insensitive, the root is the empty path
+fn key(path: &str) -> String {
+    path.trim_end_matches('/').to_lowercase()
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn metadata(file: &MockFile) -> Value {
+    let name = file
+        .path_display
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    match &file.content {
+        Some(content) => {
+            let mut hasher = Box::<DropboxContentHasher>::default();
+            hasher.update(content);
+            json!({
+                ".tag": "file",
+                "name": name,
+                "id": format!("id:{}", file.id),
+                "path_display": file.path_display,
+                "path_lower": file.path_display.to_lowercase(),
+                "size": content.len(),
+                "rev": format!("{:09x}", file.rev),
+                "client_modified": MODIFIED,
+                "server_modified": MODIFIED,
+                "content_hash": hasher.finish(),
+            })
+        }
+        None => json!({
+            ".tag": "folder",
+            "name": name,
+            "id": format!("id:{}", file.id),
+            "path_display": file.path_display,
+            "path_lower": file.path_display.to_lowercase(),
+        }),
+    }
+}
@@ -1,5 +1,11 @@
 pub mod api_url;
 pub mod client;
+pub mod content_hash;
 pub mod entities;
+#[cfg(test)]
+pub mod mock_server;
+pub mod oauth;
 pub mod parameters;
 pub mod responses;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,178 @@
+use crate::cloud_client::authenticator::request_token;
+use crate::cloud_client::dropbox::api_url::{ApiHosts, ApiUrl};
+use crate::cloud_client::dropbox::parameters::token::{
+    GrantType, TokenParameters, TokenParametersBuilder,
+};
+use crate::config::DropboxConfig;
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::BufRead;
+use std::io::{self, Write};
+use tracing::info;
+
+/// Length of PKCE code verifier, must be between 43 and 128 characters
+const CODE_VERIFIER_LENGTH: usize = 64;
+
+/// Long-lived credentials received after login
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StoredCredentials {
+    pub app_key: String,
+    pub refresh_token: String,
+}
+
+impl StoredCredentials {
+    pub fn load(
+        store: &dyn CredentialStore,
+        key: &str,
+    ) -> Result<Option<StoredCredentials>, AppError> {
+        store
+            .load(key)?
+            .map(|secret| {
+                serde_json::from_str(&secret)
+                    .map_err(|error| AppError::Credentials(error.to_string()))
+            })
+            .transpose()
+    }
+
+    pub fn save(&self, store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+        let secret = serde_json::to_string(self)
+            .map_err(|error| AppError::Credentials(error.to_string()))?;
+        store.save(key, &secret)
+    }
+
+    pub fn delete(store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+        store.delete(key)
+    }
+
+    /// Parameters of the request receiving new access token with the refresh token
+    pub fn refresh_parameters(&self) -> Result<TokenParameters, AppError> {
+        TokenParametersBuilder::default()
+            .grant_type(GrantType::RefreshToken)
+            .client_id(self.app_key.clone())
+            .refresh_token(Some(self.refresh_token.clone()))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)
+    }
+}
+
+/// Runs OAuth2 authorization code flow with PKCE in the terminal:
+/// prints the authorization URL, reads the pasted code and stores the received refresh token
+/// under `key`. App key from the remote configuration takes precedence over `DROPBOX_APP_KEY`.
+pub fn login(
+    store: &dyn CredentialStore,
+    key: &str,
+    config: &DropboxConfig,
+) -> Result<(), AppError> {
+    let hosts = ApiHosts::from_config(config);
+    let app_key = match &config.app_key {
+        Some(app_key) => app_key.clone(),
+        None => std::env::var("DROPBOX_APP_KEY").map_err(|_| AppError::AbsentAppKey)?,
+    };
+
+    let code_verifier: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(CODE_VERIFIER_LENGTH)
+        .map(char::from)
+        .collect();
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+
+    let authorize_url = Url::parse_with_params(
+        &hosts.url(&ApiUrl::Authorize),
+        [
+            ("client_id", app_key.as_str()),
+            ("response_type", "code"),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("token_access_type", "offline"),
+        ],
+    )
+    .map_err(|error| AppError::Authorization(error.to_string()))?;
+
+    println!("Open the following URL in your browser and allow access:\n\n{authorize_url}\n");
+    print!("Paste the authorization code: ");
+    io::stdout().flush().map_err(AppError::Io)?;
+    let mut code = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut code)
+        .map_err(AppError::Io)?;
+
+    let parameters = TokenParametersBuilder::default()
+        .grant_type(GrantType::AuthorizationCode)
+        .client_id(app_key.clone())
+        .code(Some(code.trim().to_string()))
+        .code_verifier(Some(code_verifier))
+        .build()
+        .map_err(|_| AppError::PrepareRequestParameters)?;
+    let token = request_token(&Client::new(), &hosts.url(&ApiUrl::Token), &parameters)?;
+
+    let refresh_token = token
+        .refresh_token
+        .ok_or_else(|| AppError::Authorization("refresh token is absent".to_string()))?;
+    StoredCredentials {
+        app_key,
+        refresh_token,
+    }
+    .save(store, key)?;
+
+    println!("Logged in successfully");
+    Ok(())
+}
+
+/// Revokes stored refresh token with all its access tokens and deletes it from the store.
+/// Credentials are deleted even if revoking fails, e.g. when they were already revoked.
+pub fn logout(
+    store: &dyn CredentialStore,
+    key: &str,
+    config: &DropboxConfig,
+) -> Result<(), AppError> {
+    let Some(credentials) = StoredCredentials::load(store, key)? else {
+        println!("There are no stored credentials");
+        return Ok(());
+    };
+
+    let client = Client::new();
+    let hosts = ApiHosts::from_config(config);
+    let revoked = credentials
+        .refresh_parameters()
+        .and_then(|parameters| {
+            request_token(&client, &hosts.url(&ApiUrl::Token), &parameters).map_err(AppError::from)
+        })
+        .and_then(|token| revoke_access_token(&client, &hosts, &token.access_token));
+    if let Err(error) = revoked {
+        println!("Unable to revoke token: {error}");
+    }
+
+    StoredCredentials::delete(store, key)?;
+    println!("Logged out successfully");
+    Ok(())
+}
+
+/// Disables the access token together with the refresh token it was received with
+fn revoke_access_token(
+    client: &Client,
+    hosts: &ApiHosts,
+    access_token: &str,
+) -> Result<(), AppError> {
+    info!("Revoking access token...");
+    let response = client
+        .post(hosts.url(&ApiUrl::TokenRevoke))
+        .bearer_auth(access_token)
+        .send()
+        .map_err(|error| AppError::SendRequest(error.to_string()))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(AppError::Authorization(format!("{status}: {body}")));
+    }
+    Ok(())
+}
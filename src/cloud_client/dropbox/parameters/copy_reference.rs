@@ -0,0 +1,14 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct CopyReferenceGetParameters {
+    path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct CopyReferenceSaveParameters {
+    copy_reference: String,
+    path: PathBuf,
+}
@@ -0,0 +1,10 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct CreateFolderParameters {
+    path: PathBuf,
+    #[builder(default)]
+    autorename: bool,
+}
@@ -0,0 +1,8 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct GetMetadataParameters {
+    path: PathBuf,
+}
@@ -6,6 +6,8 @@ use std::path::PathBuf;
 pub struct ListFolderParameters {
     path: PathBuf,
     #[builder(default)]
+    recursive: bool,
+    #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     limit: Option<i32>,
 }
@@ -0,0 +1,7 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct ListFolderContinueParameters {
+    cursor: String,
+}
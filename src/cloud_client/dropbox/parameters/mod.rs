@@ -1,4 +1,11 @@
+pub mod copy_reference;
+pub mod create_folder;
 pub mod delete;
 pub mod download;
+pub mod get_metadata;
 pub mod list_folder;
+pub mod list_folder_continue;
+pub mod relocation;
+pub mod token;
 pub mod upload;
+pub mod upload_session;
@@ -0,0 +1,14 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Parameters of both `files/move_v2` and `files/copy_v2` requests
+#[derive(Serialize, Deserialize, Builder)]
+pub struct RelocationParameters {
+    from_path: PathBuf,
+    to_path: PathBuf,
+    #[builder(default)]
+    autorename: bool,
+    #[builder(default)]
+    allow_ownership_transfer: bool,
+}
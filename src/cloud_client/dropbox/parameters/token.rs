@@ -0,0 +1,25 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum GrantType {
+    AuthorizationCode,
+    RefreshToken,
+}
+
+/// Form parameters of `oauth2/token` request
+#[derive(Serialize, Deserialize, Builder)]
+pub struct TokenParameters {
+    grant_type: GrantType,
+    client_id: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code_verifier: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+}
@@ -1,15 +1,27 @@
+use crate::cloud_client::WriteMode;
 use derive_builder::Builder;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = ".tag", rename_all = "lowercase")]
 pub enum UploadMode {
     Add,
     Overwrite,
-    Update,
+    Update { update: String },
 }
 
-#[derive(Serialize, Deserialize, Builder)]
+impl From<WriteMode> for UploadMode {
+    fn from(mode: WriteMode) -> Self {
+        match mode {
+            WriteMode::Add => UploadMode::Add,
+            WriteMode::Overwrite => UploadMode::Overwrite,
+            WriteMode::Update(update) => UploadMode::Update { update },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Builder, Clone)]
 pub struct UploadParameters {
     path: PathBuf,
     #[builder(default)]
@@ -17,5 +29,11 @@ pub struct UploadParameters {
     mode: Option<UploadMode>,
     #[builder(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
-    auto_rename: Option<bool>,
+    autorename: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mute: Option<bool>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strict_conflict: Option<bool>,
 }
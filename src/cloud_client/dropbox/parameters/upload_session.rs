@@ -0,0 +1,28 @@
+use crate::cloud_client::dropbox::parameters::upload::UploadParameters;
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct UploadSessionStartParameters {
+    #[builder(default)]
+    close: bool,
+}
+
+#[derive(Serialize, Deserialize, Builder, Clone)]
+pub struct UploadSessionCursor {
+    session_id: String,
+    offset: u64,
+}
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct UploadSessionAppendParameters {
+    cursor: UploadSessionCursor,
+    #[builder(default)]
+    close: bool,
+}
+
+#[derive(Serialize, Deserialize, Builder)]
+pub struct UploadSessionFinishParameters {
+    cursor: UploadSessionCursor,
+    commit: UploadParameters,
+}
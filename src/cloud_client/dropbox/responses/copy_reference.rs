@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct CopyReferenceGetResult {
+    /// Reference which can be saved into another Dropbox account
+    pub copy_reference: String,
+}
@@ -0,0 +1,67 @@
+use crate::cloud_client::dropbox::responses::upload_session::UploadSessionError;
+use crate::errors::AppError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Body of an endpoint-specific error response
+/// More details [here](https://www.dropbox.com/developers/documentation/http/documentation#error-handling)
+#[derive(Deserialize, Debug)]
+pub struct ApiErrorResponse {
+    pub error_summary: String,
+    #[serde(default)]
+    pub error: Value,
+}
+
+impl ApiErrorResponse {
+    /// Returns chain of ".tag" values of the nested error union,
+    /// e.g. `["path", "conflict", "file"]` for `path/conflict/file/...` summary
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags = Vec::new();
+        let mut current = &self.error;
+        while let Some(tag) = current.get(".tag").and_then(|tag| tag.as_str()) {
+            tags.push(tag);
+            current = match current.get(tag) {
+                // Some unions wrap the nested error into "reason" field (e.g. upload write failure)
+                Some(nested) if nested.get(".tag").is_none() => match nested.get("reason") {
+                    Some(reason) => reason,
+                    None => nested,
+                },
+                Some(nested) => nested,
+                // Struct variants are flattened next to the tag (e.g. upload write failure)
+                None => match current.get("reason") {
+                    Some(reason) => reason,
+                    None => break,
+                },
+            };
+        }
+        tags
+    }
+
+    pub fn into_app_error(self, status: StatusCode) -> AppError {
+        let tags = self.tags();
+        let summary = self.error_summary.trim_end_matches(['/', '.']).to_string();
+
+        if status == StatusCode::UNAUTHORIZED {
+            return match tags.first() {
+                Some(&"expired_access_token") => AppError::ExpiredAccessToken,
+                _ => AppError::Unauthorized(summary),
+            };
+        }
+
+        if tags.contains(&"not_found") {
+            AppError::NotFound(summary)
+        } else if tags.contains(&"conflict") {
+            AppError::Conflict(summary)
+        } else if tags.contains(&"insufficient_space") {
+            AppError::InsufficientSpace
+        } else if let Some(error) = serde_json::from_value::<UploadSessionError>(self.error)
+            .ok()
+            .and_then(UploadSessionError::into_app_error)
+        {
+            error
+        } else {
+            AppError::Api(summary)
+        }
+    }
+}
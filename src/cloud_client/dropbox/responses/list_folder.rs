@@ -1,5 +1,7 @@
-use crate::cloud_client::dropbox::entities::metadata::{Metadata, Tag};
+use crate::cloud_client::dropbox::entities::metadata::Metadata;
+use crate::cloud_client::entry::Entry;
 use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Deserialize, Debug)]
 pub struct ListFolderResult {
@@ -9,18 +11,50 @@ pub struct ListFolderResult {
 }
 
 impl ListFolderResult {
-    pub fn get_simple_list(&self) -> Vec<String> {
+    /// Returns entries of the page, skipping deleted ones
+    pub fn into_entries(self) -> Vec<Entry> {
         self.entries
-            .iter()
-            .clone()
-            .filter(|md| md.tag != Tag::Deleted)
-            .map(|md| {
-                if md.tag == Tag::Folder {
-                    md.name.to_string() + "/"
-                } else {
-                    md.name.to_string()
-                }
+            .into_iter()
+            .filter_map(|md| match md {
+                Metadata::File(file) => Some(file.into()),
+                Metadata::Folder(folder) => Some(folder.into()),
+                Metadata::Deleted(_) => None,
             })
             .collect()
     }
+
+    /// Returns entries of the recursive listing of `root` folder with their paths relative to it,
+    /// skipping deleted ones and the `root` folder itself
+    pub fn into_entries_relative_to(self, root: &Path) -> Vec<(PathBuf, Entry)> {
+        let root_depth = normal_components(root).count();
+        self.entries
+            .into_iter()
+            .filter_map(|md| {
+                let (path_display, entry) = match md {
+                    Metadata::File(file) => (file.path_display.clone(), Entry::from(file)),
+                    Metadata::Folder(folder) => (folder.path_display.clone(), Entry::from(folder)),
+                    Metadata::Deleted(_) => return None,
+                };
+                let relative_path: PathBuf = normal_components(Path::new(&path_display?))
+                    .skip(root_depth)
+                    .collect();
+                (!relative_path.as_os_str().is_empty()).then_some((relative_path, entry))
+            })
+            .collect()
+    }
+
+    /// Returns the cursor to pass to `files/list_folder/continue` for the next page
+    pub fn cursor(&self) -> &str {
+        &self.cursor
+    }
+
+    /// Whether more entries are available via `files/list_folder/continue`
+    pub fn has_more(&self) -> bool {
+        self.has_more
+    }
+}
+
+fn normal_components(path: &Path) -> impl Iterator<Item = Component<'_>> {
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_)))
 }
@@ -1 +1,4 @@
+pub mod copy_reference;
+pub mod error;
 pub mod list_folder;
+pub mod upload_session;
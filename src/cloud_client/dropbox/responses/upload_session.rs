@@ -0,0 +1,38 @@
+use crate::errors::AppError;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct UploadSessionStartResult {
+    pub session_id: String,
+}
+
+/// Reason the upload session can't accept the request,
+/// `files/upload_session/finish` wraps it into `lookup_failed`.
+/// Missing session is reported as `not_found` like any other missing entity.
+#[derive(Deserialize, Debug)]
+#[serde(tag = ".tag", rename_all = "snake_case")]
+pub enum UploadSessionError {
+    Closed,
+    IncorrectOffset {
+        correct_offset: u64,
+    },
+    LookupFailed {
+        lookup_failed: Box<UploadSessionError>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl UploadSessionError {
+    /// Returns the error only if it is caused by the upload session itself
+    pub fn into_app_error(self) -> Option<AppError> {
+        match self {
+            UploadSessionError::Closed => Some(AppError::UploadSessionLost),
+            UploadSessionError::IncorrectOffset { correct_offset } => {
+                Some(AppError::IncorrectUploadOffset(correct_offset))
+            }
+            UploadSessionError::LookupFailed { lookup_failed } => lookup_failed.into_app_error(),
+            UploadSessionError::Other => None,
+        }
+    }
+}
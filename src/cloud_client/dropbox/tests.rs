@@ -0,0 +1,525 @@
+use crate::cloud_client::dropbox::client::DropboxClient;
+use crate::cloud_client::dropbox::mock_server::{MockDropbox, ACCESS_TOKEN, REFRESH_TOKEN};
+use crate::cloud_client::dropbox::oauth::StoredCredentials;
+use crate::cloud_client::{CloudClient, UploadOptions, WriteMode};
+use crate::config::{BackendConfig, DropboxConfig, RemoteConfig, RetryConfig};
+use crate::credentials::file::FileCredentialStore;
+use crate::errors::AppError;
+use crate::utilities::files::partial_path;
+use std::fs;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const REMOTE: &str = "mock";
+const UPLOAD_SESSION_THRESHOLD: u64 = 16;
+const UPLOAD_CHUNK_SIZE: u64 = 8;
+
+/// Client of a fresh mock server logged in with stored credentials
+struct Fixture {
+    server: MockDropbox,
+    client: DropboxClient,
+    directory: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let server = MockDropbox::start();
+        let directory = tempfile::tempdir().expect("temporary directory is created");
+
+        let store = FileCredentialStore::new(directory.path().join("credentials"), None);
+        StoredCredentials {
+            app_key: "mock-app-key".to_string(),
+            refresh_token: REFRESH_TOKEN.to_string(),
+        }
+        .save(&store, REMOTE)
+        .expect("credentials are stored");
+
+        let config = DropboxConfig {
+            upload_session_threshold: NonZeroU64::new(UPLOAD_SESSION_THRESHOLD),
+            upload_chunk_size: NonZeroU64::new(UPLOAD_CHUNK_SIZE),
+            api_url: Some(server.url()),
+            content_url: Some(server.url()),
+            ..DropboxConfig::default()
+        };
+        let remote = RemoteConfig {
+            backend: BackendConfig::Dropbox(DropboxConfig::default()),
+            credentials: None,
+            root: None,
+            retry: RetryConfig {
+                max_attempts: NonZeroU32::new(3),
+                max_elapsed_secs: NonZeroU64::new(10),
+                initial_backoff_ms: NonZeroU64::new(1),
+                max_backoff_ms: NonZeroU64::new(1),
+            },
+        };
+        let client =
+            DropboxClient::build(REMOTE, &remote, &config, &store).expect("client is built");
+
+        Fixture {
+            server,
+            client,
+            directory,
+        }
+    }
+
+    fn local_path(&self, name: &str) -> PathBuf {
+        self.directory.path().join(name)
+    }
+
+    fn upload_bytes(
+        &self,
+        path: &str,
+        content: &[u8],
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        self.client
+            .upload_stream(
+                &mut &content[..],
+                content.len() as u64,
+                PathBuf::from(path),
+                options,
+            )
+            .map(|_| ())
+    }
+}
+
+fn names(fixture: &Fixture, path: &str) -> Vec<String> {
+    let mut names: Vec<String> = fixture
+        .client
+        .list_entries(PathBuf::from(path))
+        .expect("folder is listed")
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn uploaded_file_is_downloaded() {
+    let fixture = Fixture::new();
+    let from_path = fixture.local_path("report.txt");
+    fs::write(&from_path, b"quarterly").unwrap();
+
+    fixture
+        .client
+        .upload(
+            from_path,
+            PathBuf::from("/Docs/report.txt"),
+            UploadOptions::default(),
+        )
+        .expect("file is uploaded");
+    assert_eq!(
+        fixture.server.content("/docs/report.txt").as_deref(),
+        Some(&b"quarterly"[..])
+    );
+
+    let to_path = fixture.local_path("downloaded.txt");
+    fixture
+        .client
+        .download(PathBuf::from("/Docs/report.txt"), to_path.clone())
+        .expect("file is downloaded");
+    assert_eq!(fs::read(to_path).unwrap(), b"quarterly");
+}
+
+#[test]
+fn access_token_is_received_with_refresh_token() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/a.txt", b"a");
+
+    fixture.client.metadata(PathBuf::from("/a.txt")).unwrap();
+
+    let requests = fixture.server.requests();
+    assert_eq!(requests[0].path, "/oauth2/token");
+    assert_eq!(
+        requests[1].header("Authorization"),
+        Some(format!("Bearer {ACCESS_TOKEN}").as_str())
+    );
+}
+
+#[test]
+fn large_upload_is_sent_in_session_chunks() {
+    let fixture = Fixture::new();
+    let content: Vec<u8> = (0..30).collect();
+
+    let entry = fixture
+        .client
+        .upload_stream(
+            &mut content.as_slice(),
+            content.len() as u64,
+            PathBuf::from("/large.bin"),
+            UploadOptions::default(),
+        )
+        .expect("file is uploaded");
+
+    assert_eq!(entry.size, 30);
+    assert_eq!(fixture.server.content("/large.bin"), Some(content));
+    assert_eq!(
+        fixture
+            .server
+            .requests_to("/2/files/upload_session/append_v2")
+            .len(),
+        3
+    );
+    assert_eq!(
+        fixture
+            .server
+            .requests_to("/2/files/upload_session/finish")
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn uploaded_entry_hash_matches_content_hasher() {
+    let fixture = Fixture::new();
+    let mut hasher = fixture.client.content_hasher().unwrap();
+    hasher.update(b"hashed");
+
+    fixture
+        .upload_bytes("/hashed.txt", b"hashed", UploadOptions::default())
+        .unwrap();
+
+    let entry = fixture
+        .client
+        .metadata(PathBuf::from("/hashed.txt"))
+        .unwrap();
+    assert_eq!(entry.hash, Some(hasher.finish()));
+}
+
+#[test]
+fn interrupted_download_is_resumed_with_range() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/movie.bin", b"0123456789");
+
+    // Partial file and revision left behind by an interrupted download
+    let to_path = fixture.local_path("movie.bin");
+    let partial_path = partial_path(&to_path).unwrap();
+    let mut revision_path = partial_path.clone().into_os_string();
+    revision_path.push(".rev");
+    fs::write(&partial_path, b"0123").unwrap();
+    fs::write(
+        &revision_path,
+        fixture.server.revision("/movie.bin").unwrap(),
+    )
+    .unwrap();
+
+    fixture
+        .client
+        .download(PathBuf::from("/movie.bin"), to_path.clone())
+        .unwrap();
+
+    let downloads = fixture.server.requests_to("/2/files/download");
+    assert_eq!(downloads.len(), 1);
+    assert_eq!(downloads[0].header("Range"), Some("bytes=4-"));
+    assert_eq!(fs::read(to_path).unwrap(), b"0123456789");
+}
+
+#[test]
+fn changed_file_is_downloaded_again() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/movie.bin", b"0123456789");
+
+    let to_path = fixture.local_path("movie.bin");
+    let partial_path = partial_path(&to_path).unwrap();
+    let mut revision_path = partial_path.clone().into_os_string();
+    revision_path.push(".rev");
+    fs::write(&partial_path, b"abcd").unwrap();
+    fs::write(&revision_path, "stale").unwrap();
+
+    fixture
+        .client
+        .download(PathBuf::from("/movie.bin"), to_path.clone())
+        .unwrap();
+
+    assert_eq!(fixture.server.requests_to("/2/files/download").len(), 2);
+    assert_eq!(fs::read(to_path).unwrap(), b"0123456789");
+}
+
+#[test]
+fn download_without_revision_is_restarted() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/movie.bin", b"0123456789");
+
+    let to_path = fixture.local_path("movie.bin");
+    let partial_path = partial_path(&to_path).unwrap();
+    let mut revision_path = partial_path.clone().into_os_string();
+    revision_path.push(".rev");
+    fs::write(&partial_path, b"0123").unwrap();
+    fs::write(
+        &revision_path,
+        fixture.server.revision("/movie.bin").unwrap(),
+    )
+    .unwrap();
+    // Resumed content comes without `Dropbox-API-Result` header
+    fixture
+        .server
+        .respond_once("/2/files/download", 206, "456789");
+
+    fixture
+        .client
+        .download(PathBuf::from("/movie.bin"), to_path.clone())
+        .unwrap();
+
+    let downloads = fixture.server.requests_to("/2/files/download");
+    assert_eq!(downloads.len(), 2);
+    assert_eq!(downloads[1].header("Range"), None);
+    assert_eq!(fs::read(to_path).unwrap(), b"0123456789");
+}
+
+#[test]
+fn deleted_file_is_gone() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/Trash/old.txt", b"old");
+
+    fixture
+        .client
+        .delete(PathBuf::from("/Trash/old.txt"))
+        .unwrap();
+
+    assert!(!fixture.server.exists("/Trash/old.txt"));
+    assert!(fixture.server.exists("/Trash"));
+}
+
+#[test]
+fn deleting_absent_file_is_not_found() {
+    let fixture = Fixture::new();
+
+    let result = fixture.client.delete(PathBuf::from("/absent.txt"));
+
+    assert!(
+        matches!(result, Err(AppError::NotFound(summary)) if summary.starts_with("path_lookup/not_found"))
+    );
+}
+
+#[test]
+fn folder_is_listed() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/Photos/a.jpg", b"a");
+    fixture.server.add_file("/Photos/b.jpg", b"bb");
+    fixture.server.add_file("/Photos/2023/c.jpg", b"ccc");
+    fixture.server.add_file("/notes.txt", b"n");
+
+    assert_eq!(names(&fixture, "/Photos"), ["2023", "a.jpg", "b.jpg"]);
+    assert_eq!(names(&fixture, ""), ["Photos", "notes.txt"]);
+
+    let entries = fixture
+        .client
+        .list_entries(PathBuf::from("/Photos"))
+        .unwrap();
+    let folder = entries.iter().find(|entry| entry.name == "2023").unwrap();
+    let file = entries.iter().find(|entry| entry.name == "b.jpg").unwrap();
+    assert!(folder.is_folder());
+    assert_eq!(file.size, 2);
+}
+
+#[test]
+fn listing_follows_pages() {
+    let fixture = Fixture::new();
+    for index in 0..7 {
+        fixture
+            .server
+            .add_file(&format!("/Many/{index}.txt"), index.to_string().as_bytes());
+    }
+    fixture.server.set_page_size(3);
+
+    assert_eq!(names(&fixture, "/Many").len(), 7);
+    assert_eq!(
+        fixture
+            .server
+            .requests_to("/2/files/list_folder/continue")
+            .len(),
+        2
+    );
+
+    let pages: Vec<_> = fixture
+        .client
+        .list_folder_pages(PathBuf::from("/Many"), false)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(pages.len(), 3);
+}
+
+#[test]
+fn recursive_listing_includes_nested_entries() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/Root/a.txt", b"a");
+    fixture.server.add_file("/Root/Nested/Deeper/b.txt", b"b");
+    fixture.server.set_page_size(2);
+
+    let mut paths: Vec<PathBuf> = fixture
+        .client
+        .list_entries_recursive(PathBuf::from("/Root"))
+        .unwrap()
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    paths.sort();
+
+    assert_eq!(
+        paths,
+        [
+            PathBuf::from("Nested"),
+            PathBuf::from("Nested/Deeper"),
+            PathBuf::from("Nested/Deeper/b.txt"),
+            PathBuf::from("a.txt"),
+        ]
+    );
+}
+
+#[test]
+fn listing_absent_folder_is_not_found() {
+    let fixture = Fixture::new();
+
+    let result = fixture.client.list_entries(PathBuf::from("/absent"));
+
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[test]
+fn upload_conflict_is_reported_from_flattened_reason() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/taken.txt", b"first");
+
+    let result = fixture.upload_bytes("/taken.txt", b"second", UploadOptions::default());
+
+    assert!(matches!(result, Err(AppError::Conflict(summary)) if summary == "path/conflict/file"));
+    assert_eq!(
+        fixture.server.content("/taken.txt").as_deref(),
+        Some(&b"first"[..])
+    );
+}
+
+#[test]
+fn upload_modes_resolve_conflicts() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/taken.txt", b"first");
+
+    let autorename = UploadOptions {
+        autorename: true,
+        ..UploadOptions::default()
+    };
+    fixture
+        .upload_bytes("/taken.txt", b"second", autorename)
+        .unwrap();
+    assert_eq!(
+        fixture.server.content("/taken (1).txt").as_deref(),
+        Some(&b"second"[..])
+    );
+
+    let overwrite = UploadOptions {
+        mode: WriteMode::Overwrite,
+        ..UploadOptions::default()
+    };
+    fixture
+        .upload_bytes("/taken.txt", b"third", overwrite)
+        .unwrap();
+    assert_eq!(
+        fixture.server.content("/taken.txt").as_deref(),
+        Some(&b"third"[..])
+    );
+
+    let stale = UploadOptions {
+        mode: WriteMode::Update("000000001".to_string()),
+        ..UploadOptions::default()
+    };
+    let result = fixture.upload_bytes("/taken.txt", b"fourth", stale);
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+}
+
+#[test]
+fn malformed_request_is_described_with_plain_text() {
+    let fixture = Fixture::new();
+    fixture.server.respond_once(
+        "/2/files/get_metadata",
+        400,
+        "Error in call to API function \"files/get_metadata\": unknown field",
+    );
+
+    let result = fixture.client.metadata(PathBuf::from("/a.txt"));
+
+    assert!(matches!(result, Err(AppError::Request(message)) if message.contains("unknown field")));
+}
+
+#[test]
+fn insufficient_space_is_reported() {
+    let fixture = Fixture::new();
+    fixture.server.respond_once(
+        "/2/files/upload",
+        409,
+        r#"{"error_summary": "path/insufficient_space/..", "error": {".tag": "path", "reason": {".tag": "insufficient_space"}, "upload_session_id": "id"}}"#,
+    );
+
+    let result = fixture.upload_bytes("/big.bin", b"big", UploadOptions::default());
+
+    assert!(matches!(result, Err(AppError::InsufficientSpace)));
+}
+
+#[test]
+fn rate_limited_request_is_retried() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/a.txt", b"a");
+    fixture.server.respond_once_with_headers(
+        "/2/files/get_metadata",
+        429,
+        &[("Retry-After", "0")],
+        r#"{"error_summary": "too_many_requests/..", "error": {"reason": {".tag": "too_many_requests"}, "retry_after": 0}}"#,
+    );
+
+    let entry = fixture.client.metadata(PathBuf::from("/a.txt")).unwrap();
+
+    assert_eq!(entry.name, "a.txt");
+    assert_eq!(fixture.server.requests_to("/2/files/get_metadata").len(), 2);
+}
+
+#[test]
+fn server_error_is_not_retried_for_non_idempotent_request() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/a.txt", b"a");
+    fixture
+        .server
+        .respond_once("/2/files/delete_v2", 503, "Service Unavailable");
+
+    let result = fixture.client.delete(PathBuf::from("/a.txt"));
+
+    assert!(matches!(result, Err(AppError::Server { status: 503, .. })));
+    assert!(fixture.server.exists("/a.txt"));
+}
+
+#[test]
+fn expired_access_token_is_refreshed() {
+    let fixture = Fixture::new();
+    fixture.server.add_file("/a.txt", b"a");
+    fixture.client.metadata(PathBuf::from("/a.txt")).unwrap();
+    fixture.server.respond_once(
+        "/2/files/get_metadata",
+        401,
+        r#"{"error_summary": "expired_access_token/..", "error": {".tag": "expired_access_token"}}"#,
+    );
+
+    fixture.client.metadata(PathBuf::from("/a.txt")).unwrap();
+
+    assert_eq!(fixture.server.requests_to("/oauth2/token").len(), 2);
+}
+
+#[test]
+fn existing_folder_is_a_conflict_unless_renamed() {
+    let fixture = Fixture::new();
+    fixture.server.add_folder("/Projects");
+
+    let result = fixture
+        .client
+        .create_folder(PathBuf::from("/Projects"), false);
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    fixture
+        .client
+        .create_folder(PathBuf::from("/Projects"), true)
+        .unwrap();
+    assert!(fixture.server.exists("/Projects (1)"));
+    assert_eq!(
+        fixture.server.requests_to("/2/files/create_folder_v2")[1].arg["autorename"],
+        true
+    );
+}
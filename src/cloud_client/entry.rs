@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EntryKind {
+    File,
+    Folder,
+}
+
+/// Backend-neutral description of a file or folder, either local or on a cloud storage
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub kind: EntryKind,
+    /// Size in bytes, always zero for folders
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// Content hash in the format of the storage it was received from
+    pub hash: Option<String>,
+    /// Identifier of the entry on the cloud storage
+    pub id: Option<String>,
+}
+
+impl Entry {
+    pub fn is_folder(&self) -> bool {
+        self.kind == EntryKind::Folder
+    }
+
+    /// Returns name with "/" appended to folders
+    pub fn display_name(&self) -> String {
+        if self.is_folder() {
+            self.name.to_string() + "/"
+        } else {
+            self.name.to_string()
+        }
+    }
+}
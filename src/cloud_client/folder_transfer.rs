@@ -0,0 +1,107 @@
+use crate::cloud_client::{CloudClient, UploadOptions};
+use crate::errors::AppError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// Outcome of transferring every file of a folder
+#[derive(Debug, Default)]
+pub struct TransferSummary {
+    pub transferred: Vec<PathBuf>,
+    pub failed: Vec<(PathBuf, AppError)>,
+}
+
+impl TransferSummary {
+    pub fn record(&mut self, path: PathBuf, result: Result<(), AppError>) {
+        match result {
+            Ok(_) => self.transferred.push(path),
+            Err(error) => self.failed.push((path, error)),
+        }
+    }
+
+    /// Returns a line per file followed by the totals
+    pub fn report(&self) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .transferred
+            .iter()
+            .map(|path| format!("Transferred {}", path.display()))
+            .collect();
+        lines.extend(
+            self.failed
+                .iter()
+                .map(|(path, error)| format!("Failed {}: {error}", path.display())),
+        );
+        lines.push(format!(
+            "{} files transferred, {} failed",
+            self.transferred.len(),
+            self.failed.len()
+        ));
+        lines
+    }
+}
+
+/// Uploads local folder with all its subfolders to the cloud storage
+pub fn upload_folder<C: CloudClient + ?Sized>(
+    cloud_client: &C,
+    from_path: &Path,
+    to_path: &Path,
+    options: &UploadOptions,
+) -> Result<TransferSummary, AppError> {
+    if !from_path.is_dir() {
+        return Err(AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{} is not a folder", from_path.display()),
+        )));
+    }
+
+    let mut summary = TransferSummary::default();
+    for entry in WalkDir::new(from_path).min_depth(1) {
+        let entry = entry.map_err(|error| AppError::Io(error.into()))?;
+        let relative_path = entry
+            .path()
+            .strip_prefix(from_path)
+            .unwrap_or(entry.path())
+            .to_path_buf();
+        let cloud_path = to_path.join(&relative_path);
+
+        if entry.file_type().is_dir() {
+            match cloud_client.create_folder(cloud_path, false) {
+                Ok(_) | Err(AppError::Conflict(_)) => {}
+                // Real problems surface on uploading the files of the folder
+                Err(error) => debug!("Unable to create folder {:?}: {}", relative_path, error),
+            }
+        } else {
+            let result =
+                cloud_client.upload(entry.path().to_path_buf(), cloud_path, options.clone());
+            summary.record(relative_path, result);
+        }
+    }
+    Ok(summary)
+}
+
+/// Downloads cloud folder with all its subfolders to the local machine
+pub fn download_folder<C: CloudClient + ?Sized>(
+    cloud_client: &C,
+    from_path: &Path,
+    to_path: &Path,
+) -> Result<TransferSummary, AppError> {
+    let entries = cloud_client.list_entries_recursive(from_path.to_path_buf())?;
+    fs::create_dir_all(to_path).map_err(AppError::Io)?;
+
+    let mut summary = TransferSummary::default();
+    for (relative_path, entry) in entries {
+        let local_path = to_path.join(&relative_path);
+        if entry.is_folder() {
+            fs::create_dir_all(&local_path).map_err(AppError::Io)?;
+        } else {
+            let result = local_path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(AppError::Io)
+                .and_then(|_| cloud_client.download(from_path.join(&relative_path), local_path));
+            summary.record(relative_path, result);
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,60 @@
+use crate::config::GoogleDriveConfig;
+
+pub static AUTHORIZE_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+pub static REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
+static DEFAULT_API_URL: &str = "https://www.googleapis.com/drive/v3";
+static DEFAULT_UPLOAD_URL: &str = "https://www.googleapis.com/upload/drive/v3";
+static DEFAULT_TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+
+/// Endpoints of Google Drive API.
+/// Base URLs can be overridden in the remote configuration, e.g. to use a mock server.
+#[derive(Debug, Clone)]
+pub struct ApiUrls {
+    api: String,
+    upload: String,
+    token: String,
+}
+
+impl ApiUrls {
+    pub fn new(config: &GoogleDriveConfig) -> Self {
+        let base = |url: &Option<String>, default: &str| {
+            url.as_deref()
+                .unwrap_or(default)
+                .trim_end_matches('/')
+                .to_string()
+        };
+        Self {
+            api: base(&config.api_url, DEFAULT_API_URL),
+            upload: base(&config.upload_url, DEFAULT_UPLOAD_URL),
+            token: base(&config.token_url, DEFAULT_TOKEN_URL),
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn files(&self) -> String {
+        format!("{}/files", self.api)
+    }
+
+    pub fn file(&self, id: &str) -> String {
+        format!("{}/files/{id}", self.api)
+    }
+
+    pub fn export(&self, id: &str) -> String {
+        format!("{}/files/{id}/export", self.api)
+    }
+
+    pub fn copy(&self, id: &str) -> String {
+        format!("{}/files/{id}/copy", self.api)
+    }
+
+    pub fn upload_files(&self) -> String {
+        format!("{}/files", self.upload)
+    }
+
+    pub fn upload_file(&self, id: &str) -> String {
+        format!("{}/files/{id}", self.upload)
+    }
+}
@@ -0,0 +1,893 @@
+use crate::cloud_client::authenticator::Authenticator;
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::google_drive::api_url::ApiUrls;
+use crate::cloud_client::google_drive::content_hash::DriveContentHasher;
+use crate::cloud_client::google_drive::export::{
+    is_google_apps_file, ExportFormats, FOLDER_MIME_TYPE,
+};
+use crate::cloud_client::google_drive::oauth::StoredCredentials;
+use crate::cloud_client::google_drive::parameters::file_metadata::{
+    FileMetadataParameters, FileMetadataParametersBuilder,
+};
+use crate::cloud_client::google_drive::parameters::token::TokenParameters;
+use crate::cloud_client::google_drive::responses::error::ApiErrorResponse;
+use crate::cloud_client::google_drive::responses::file::{
+    DriveFile, FileList, FILE_FIELDS, FILE_LIST_FIELDS,
+};
+use crate::cloud_client::http::{ApiResponses, ErrorResponse};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::upload_state::{FileFingerprint, PendingUpload, UploadState};
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::{GoogleDriveConfig, RemoteConfig};
+use crate::credentials::CredentialStore;
+use crate::errors::{AppError, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR};
+use crate::utilities::files::write_atomically;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::{CONTENT_RANGE, LOCATION, RANGE};
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use tracing::{debug, info, instrument, warn};
+
+/// Alias of My Drive folder accepted instead of its id
+static ROOT_ID: &str = "root";
+static UPLOAD_CONTENT_LENGTH_HEADER: &str = "X-Upload-Content-Length";
+static PAGE_SIZE: &str = "1000";
+
+/// Chunks of resumable uploads, except the last one, have to be multiples of 256 KiB
+const UPLOAD_CHUNK_ALIGNMENT: u64 = 256 * 1024;
+const DEFAULT_UPLOAD_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Client of Google Drive, which identifies files by ids rather than by paths.
+/// Paths are resolved folder by folder and the resolved ids are cached.
+/// Several files of a folder may have the same name, such files are listed as `name [id]`
+/// and have to be addressed that way.
+#[derive(Debug)]
+pub struct GoogleDriveClient {
+    client: Client,
+    urls: ApiUrls,
+    authenticator: Authenticator<TokenParameters>,
+    export_formats: ExportFormats,
+    /// Size of a single resumable upload request
+    upload_chunk_size: u64,
+    /// Keeps track of resumable uploads, so they can be resumed after restart
+    upload_state: UploadState,
+    /// Decides which failed requests are repeated and how
+    retry_policy: RetryPolicy,
+    /// Drive folder all paths are relative to, the whole My Drive if absent
+    root: Option<PathBuf>,
+    /// Ids of resolved paths relative to My Drive
+    ids: Mutex<HashMap<PathBuf, String>>,
+}
+
+/// File the upload session writes to
+enum UploadTarget {
+    Create(FileMetadataParameters),
+    /// Replaces content of the existing file with this id
+    Update(String),
+}
+
+/// Outcome of a request sending content to the upload session
+enum ChunkResult {
+    Complete(DriveFile),
+    /// Upload continues from this offset
+    Incomplete(u64),
+}
+
+impl GoogleDriveClient {
+    /// Builds client of the remote named `name`
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &GoogleDriveConfig,
+        credential_store: &dyn CredentialStore,
+    ) -> Result<GoogleDriveClient, AppError> {
+        // Upload sessions answer with `308 Resume Incomplete`, which isn't a redirect
+        let client = ClientBuilder::new()
+            .timeout(None)
+            .redirect(Policy::none())
+            .build()
+            .map_err(|_| AppError::PrepareClient(BUILD_REQUEST_CLIENT_ERROR.to_string()))?;
+        let urls = ApiUrls::new(config);
+        let refresh_parameters =
+            StoredCredentials::load(credential_store, remote.credential_key(name))?
+                .map(|credentials| credentials.refresh_parameters())
+                .transpose()?;
+        let authenticator = Authenticator::build(
+            client.clone(),
+            urls.token().to_string(),
+            refresh_parameters,
+            "GOOGLE_DRIVE_ACCESS_TOKEN",
+        )?;
+
+        let upload_chunk_size = config
+            .upload_chunk_size
+            .map_or(DEFAULT_UPLOAD_CHUNK_SIZE, |size| size.get())
+            / UPLOAD_CHUNK_ALIGNMENT
+            * UPLOAD_CHUNK_ALIGNMENT;
+
+        Ok(Self {
+            client,
+            urls,
+            authenticator,
+            export_formats: ExportFormats::new(&config.export)?,
+            upload_chunk_size: upload_chunk_size.max(UPLOAD_CHUNK_ALIGNMENT),
+            upload_state: UploadState::from_env(name),
+            retry_policy: remote.retry.policy()?,
+            root: remote.root.clone(),
+            ids: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Resolves path of the remote into path relative to My Drive
+    fn drive_path(&self, path: &Path) -> PathBuf {
+        self.root
+            .iter()
+            .flat_map(|root| root.components())
+            .chain(path.components())
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect()
+    }
+
+    /// Sends request built by `request` with access token, following the retry policy
+    fn send_authorized(
+        &self,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, AppError> {
+        self.authenticator
+            .send::<DriveApi>(&self.retry_policy, idempotent, request)
+    }
+
+    fn get_file(&self, id: &str) -> Result<DriveFile, AppError> {
+        let file = self
+            .send_authorized(true, || {
+                self.client
+                    .get(self.urls.file(id))
+                    .query(&[("fields", FILE_FIELDS)])
+            })?
+            .json::<DriveFile>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        if file.trashed {
+            return Err(AppError::NotFound(file.name));
+        }
+        Ok(file)
+    }
+
+    /// Lists files matching the `query`, page by page
+    fn list_files(&self, query: &str) -> Result<Vec<DriveFile>, AppError> {
+        let mut files = Vec::new();
+        let mut page_token: Option<String> = None;
+        loop {
+            let page = self
+                .send_authorized(true, || {
+                    let request = self.client.get(self.urls.files()).query(&[
+                        ("q", query),
+                        ("fields", FILE_LIST_FIELDS),
+                        ("pageSize", PAGE_SIZE),
+                        ("orderBy", "folder,name"),
+                    ]);
+                    match &page_token {
+                        Some(token) => request.query(&[("pageToken", token)]),
+                        None => request,
+                    }
+                })?
+                .json::<FileList>()
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+            files.extend(page.files);
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => return Ok(files),
+            }
+        }
+    }
+
+    fn list_children(&self, folder_id: &str) -> Result<Vec<DriveFile>, AppError> {
+        self.list_files(&format!(
+            "'{}' in parents and trashed = false",
+            escape(folder_id)
+        ))
+    }
+
+    /// Finds the file called `name` in the folder, `name [id]` addresses one of several files
+    /// with the same name. `path` is the path of the file used in errors.
+    fn find_child(
+        &self,
+        folder_id: &str,
+        name: &str,
+        path: &Path,
+    ) -> Result<Option<DriveFile>, AppError> {
+        if let Some((base_name, id)) = split_duplicate_name(name) {
+            match self.get_file(id) {
+                Ok(file)
+                    if file.name == base_name && file.parents.iter().any(|p| p == folder_id) =>
+                {
+                    return Ok(Some(file))
+                }
+                // The name may be just similar to the one of duplicates
+                Ok(_) | Err(AppError::NotFound(_) | AppError::Api(_)) => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        let mut files = self.list_files(&format!(
+            "name = '{}' and '{}' in parents and trashed = false",
+            escape(name),
+            escape(folder_id)
+        ))?;
+        match files.len() {
+            0 => Ok(None),
+            1 => Ok(files.pop()),
+            count => Err(AppError::Conflict(format!(
+                "there are {count} entries named {}, address one of them as `name [id]`",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Returns id of the file at path relative to My Drive
+    fn resolve(&self, drive_path: &Path) -> Result<String, AppError> {
+        if let Some(id) = self.cached_id(drive_path) {
+            return Ok(id);
+        }
+        let id = match (drive_path.parent(), drive_path.file_name()) {
+            (Some(parent), Some(name)) => {
+                let parent_id = self.resolve(parent)?;
+                self.find_child(&parent_id, &name.to_string_lossy(), drive_path)?
+                    .ok_or_else(|| AppError::NotFound(drive_path.display().to_string()))?
+                    .id
+            }
+            // Id of My Drive is resolved, as files list it among their parents
+            _ => self.get_file(ROOT_ID)?.id,
+        };
+        self.remember(drive_path, &id);
+        Ok(id)
+    }
+
+    /// Returns the file at path relative to My Drive
+    fn file(&self, drive_path: &Path) -> Result<DriveFile, AppError> {
+        self.resolved(|| match (drive_path.parent(), drive_path.file_name()) {
+            _ if self.cached_id(drive_path).is_some() => self.get_file(&self.resolve(drive_path)?),
+            (Some(parent), Some(name)) => {
+                let parent_id = self.resolve(parent)?;
+                let file = self
+                    .find_child(&parent_id, &name.to_string_lossy(), drive_path)?
+                    .ok_or_else(|| AppError::NotFound(drive_path.display().to_string()))?;
+                self.remember(drive_path, &file.id);
+                Ok(file)
+            }
+            _ => self.get_file(ROOT_ID),
+        })
+    }
+
+    /// Runs `operation` resolving paths, and repeats it once with empty cache if some path
+    /// isn't found, as cached ids may be outdated by changes made elsewhere
+    fn resolved<T>(&self, operation: impl Fn() -> Result<T, AppError>) -> Result<T, AppError> {
+        match operation() {
+            Err(AppError::NotFound(_)) if !self.lock_ids().is_empty() => {
+                debug!("Path is not found, resolving it again");
+                self.lock_ids().clear();
+                operation()
+            }
+            result => result,
+        }
+    }
+
+    /// Returns id of the folder at path relative to My Drive, creating absent folders
+    fn ensure_folder(&self, drive_path: &Path) -> Result<String, AppError> {
+        match self.resolved(|| self.resolve(drive_path)) {
+            Err(AppError::NotFound(_)) => {}
+            result => return result,
+        }
+        let (Some(parent), Some(name)) = (drive_path.parent(), drive_path.file_name()) else {
+            return self.resolve(drive_path);
+        };
+
+        let parent_id = self.ensure_folder(parent)?;
+        let folder = self.create_file(folder_metadata(&name.to_string_lossy(), &parent_id)?)?;
+        self.remember(drive_path, &folder.id);
+        Ok(folder.id)
+    }
+
+    /// Returns `name` if the folder has no file with it, or the first free name among
+    /// `name (1).ext`, `name (2).ext` and so on if `autorename` allows that
+    fn free_name(
+        &self,
+        folder_id: &str,
+        drive_path: &Path,
+        autorename: bool,
+    ) -> Result<String, AppError> {
+        let path = free_path(drive_path, autorename, |candidate| {
+            match self.find_child(folder_id, &file_name(candidate), drive_path) {
+                Ok(file) => Ok(file.is_some()),
+                Err(AppError::Conflict(_)) => Ok(true),
+                Err(error) => Err(error),
+            }
+        })?;
+        Ok(file_name(&path))
+    }
+
+    fn create_file(&self, metadata: FileMetadataParameters) -> Result<DriveFile, AppError> {
+        self.send_authorized(false, || {
+            self.client
+                .post(self.urls.files())
+                .query(&[("fields", FILE_FIELDS)])
+                .json(&metadata)
+        })?
+        .json::<DriveFile>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    fn copy_file(
+        &self,
+        file: &DriveFile,
+        name: &str,
+        folder_id: &str,
+    ) -> Result<DriveFile, AppError> {
+        let metadata = FileMetadataParametersBuilder::default()
+            .name(Some(name.to_string()))
+            .parents(Some(vec![folder_id.to_string()]))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        self.send_authorized(false, || {
+            self.client
+                .post(self.urls.copy(&file.id))
+                .query(&[("fields", FILE_FIELDS)])
+                .json(&metadata)
+        })?
+        .json::<DriveFile>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Drive can't copy folders, so their content is copied file by file
+    fn copy_folder(&self, folder: &DriveFile, name: &str, parent_id: &str) -> Result<(), AppError> {
+        let copy = self.create_file(folder_metadata(name, parent_id)?)?;
+        for child in self.list_children(&folder.id)? {
+            if child.is_folder() {
+                self.copy_folder(&child, &child.name, &copy.id)?;
+            } else {
+                self.copy_file(&child, &child.name, &copy.id)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decides which file the upload writes to according to the write mode
+    fn upload_target(
+        &self,
+        to_path: &Path,
+        options: &UploadOptions,
+    ) -> Result<UploadTarget, AppError> {
+        let drive_path = self.drive_path(to_path);
+        let existing = match &options.mode {
+            WriteMode::Add => None,
+            WriteMode::Overwrite => match self.file(&drive_path) {
+                Ok(file) => Some(file),
+                Err(AppError::NotFound(_)) => None,
+                Err(error) => return Err(error),
+            },
+            // Drive has no conditional uploads, so the checksum is compared beforehand
+            WriteMode::Update(revision) => {
+                let file = self.file(&drive_path)?;
+                if file.md5_checksum.as_ref() != Some(revision) {
+                    return Err(AppError::Conflict(format!(
+                        "{} has changed since revision {revision}",
+                        to_path.display()
+                    )));
+                }
+                Some(file)
+            }
+        };
+
+        match existing {
+            Some(file) if file.is_folder() => Err(AppError::Conflict(format!(
+                "{} is a folder",
+                to_path.display()
+            ))),
+            Some(file) => Ok(UploadTarget::Update(file.id)),
+            None => {
+                let parent_id = self.ensure_folder(drive_path.parent().unwrap_or(Path::new("")))?;
+                let name = self.free_name(&parent_id, &drive_path, options.autorename)?;
+                let metadata = FileMetadataParametersBuilder::default()
+                    .name(Some(name))
+                    .parents(Some(vec![parent_id]))
+                    .build()
+                    .map_err(|_| AppError::PrepareRequestParameters)?;
+                Ok(UploadTarget::Create(metadata))
+            }
+        }
+    }
+
+    /// Starts resumable upload of `size` bytes and returns URL of the upload session
+    fn start_upload_session(&self, target: &UploadTarget, size: u64) -> Result<String, AppError> {
+        info!("Starting upload session...");
+        let query = [("uploadType", "resumable"), ("fields", FILE_FIELDS)];
+        let response = self.send_authorized(true, || {
+            let request = match target {
+                UploadTarget::Create(metadata) => self
+                    .client
+                    .post(self.urls.upload_files())
+                    .query(&query)
+                    .json(metadata),
+                UploadTarget::Update(id) => self
+                    .client
+                    .patch(self.urls.upload_file(id))
+                    .query(&query)
+                    .json(&serde_json::Map::new()),
+            };
+            request.header(UPLOAD_CONTENT_LENGTH_HEADER, size)
+        })?;
+
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Sends `bytes` to the upload session, or just asks for its state if they are empty
+    fn send_chunk(
+        &self,
+        session_url: &str,
+        bytes: &[u8],
+        offset: u64,
+        size: u64,
+    ) -> Result<ChunkResult, AppError> {
+        let content_range = if bytes.is_empty() {
+            format!("bytes */{size}")
+        } else {
+            format!("bytes {offset}-{}/{size}", offset + bytes.len() as u64 - 1)
+        };
+        let response = self.send_authorized(true, || {
+            self.client
+                .put(session_url)
+                .header(CONTENT_RANGE, content_range.as_str())
+                .body(bytes.to_vec())
+        })?;
+
+        if response.status() != StatusCode::PERMANENT_REDIRECT {
+            return response
+                .json::<DriveFile>()
+                .map(ChunkResult::Complete)
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()));
+        }
+        // `Range: bytes=0-<last received byte>` is absent until the first byte is received
+        let received = response
+            .headers()
+            .get(RANGE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit('-').next())
+            .and_then(|last| last.parse::<u64>().ok())
+            .map_or(0, |last| last + 1);
+        Ok(ChunkResult::Incomplete(received))
+    }
+
+    /// Sends content read from `reader` to the upload session starting from `offset`,
+    /// until all `size` bytes are received. Bytes the server didn't keep are sent again.
+    /// `on_chunk` is called with the new offset after every accepted chunk.
+    fn send_chunks(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        session_url: &str,
+        mut offset: u64,
+        mut on_chunk: impl FnMut(u64) -> Result<(), AppError>,
+    ) -> Result<DriveFile, AppError> {
+        let mut chunk = Vec::with_capacity(self.upload_chunk_size as usize);
+        loop {
+            let missing = self.upload_chunk_size - chunk.len() as u64;
+            reader
+                .take(missing)
+                .read_to_end(&mut chunk)
+                .map_err(AppError::Io)?;
+            if chunk.is_empty() && offset < size {
+                return Err(AppError::Io(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    format!("expected {size} bytes, but {offset} were read"),
+                )));
+            }
+
+            match self.send_chunk(session_url, &chunk, offset, size)? {
+                ChunkResult::Complete(file) => {
+                    info!("File has been uploaded");
+                    return Ok(file);
+                }
+                ChunkResult::Incomplete(received) => {
+                    let kept = received.saturating_sub(offset).min(chunk.len() as u64);
+                    chunk.drain(..kept as usize);
+                    offset += kept;
+                    on_chunk(offset)?;
+                    info!("Uploaded {offset} of {size} bytes");
+                }
+            }
+        }
+    }
+
+    /// Uploads local file with resumable upload, recording its progress in the upload state,
+    /// so an interrupted upload of unchanged file continues from the last received byte
+    #[instrument(name = "Google Drive upload session", skip(self))]
+    fn upload_in_session(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let from_path = fs::canonicalize(from_path).map_err(AppError::Io)?;
+        let fingerprint = FileFingerprint::of(&from_path)?;
+
+        if let Some(upload) = self.upload_state.find(&from_path, &to_path)? {
+            if upload.fingerprint == fingerprint {
+                info!("Resuming upload...");
+                match self.resume_upload(upload) {
+                    // Session has expired
+                    Err(error @ (AppError::NotFound(_) | AppError::Api(_))) => {
+                        info!("Unable to resume upload ({error}), restarting upload")
+                    }
+                    result => return result,
+                }
+            } else {
+                info!("Local file has changed since the last attempt, restarting upload");
+            }
+        }
+
+        let target = self.upload_target(&to_path, &options)?;
+        let session_url = self.start_upload_session(&target, fingerprint.size())?;
+        let upload = PendingUpload {
+            remote: self.upload_state.remote().to_string(),
+            from_path,
+            to_path,
+            session_id: session_url,
+            offset: 0,
+            fingerprint,
+            options,
+        };
+        self.upload_state.save(&upload)?;
+        self.continue_upload(upload)
+    }
+
+    /// Asks the session which bytes it has received and continues from there
+    fn resume_upload(&self, mut upload: PendingUpload) -> Result<(), AppError> {
+        let size = upload.fingerprint.size();
+        match self.send_chunk(&upload.session_id, &[], upload.offset, size)? {
+            ChunkResult::Complete(_) => {
+                info!("File has been uploaded");
+                self.upload_state.remove(&upload.from_path, &upload.to_path)
+            }
+            ChunkResult::Incomplete(received) => {
+                info!("Resuming upload from {received} bytes...");
+                upload.offset = received;
+                self.continue_upload(upload)
+            }
+        }
+    }
+
+    fn continue_upload(&self, mut upload: PendingUpload) -> Result<(), AppError> {
+        let mut file = File::open(&upload.from_path).map_err(AppError::Io)?;
+        file.seek(SeekFrom::Start(upload.offset))
+            .map_err(AppError::Io)?;
+
+        let session_url = upload.session_id.clone();
+        self.send_chunks(
+            &mut file,
+            upload.fingerprint.size(),
+            &session_url,
+            upload.offset,
+            |offset| {
+                upload.offset = offset;
+                self.upload_state.save(&upload)
+            },
+        )?;
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    /// Requests file content, Google Docs editors files are exported in the configured format
+    fn send_download_request(&self, file: &DriveFile) -> Result<Response, AppError> {
+        if file.is_folder() {
+            return Err(AppError::Request(format!("{} is a folder", file.name)));
+        }
+        if is_google_apps_file(&file.mime_type) {
+            let mime_type = self.export_formats.mime_type(&file.mime_type)?;
+            info!("Exporting as {mime_type}...");
+            return self.send_authorized(true, || {
+                self.client
+                    .get(self.urls.export(&file.id))
+                    .query(&[("mimeType", mime_type)])
+            });
+        }
+        self.send_authorized(true, || {
+            self.client
+                .get(self.urls.file(&file.id))
+                .query(&[("alt", "media")])
+        })
+    }
+
+    fn lock_ids(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, String>> {
+        self.ids.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn cached_id(&self, drive_path: &Path) -> Option<String> {
+        self.lock_ids().get(drive_path).cloned()
+    }
+
+    fn remember(&self, drive_path: &Path, id: &str) {
+        self.lock_ids()
+            .insert(drive_path.to_path_buf(), id.to_string());
+    }
+
+    /// Forgets ids of the path and everything under it
+    fn forget(&self, drive_path: &Path) {
+        self.lock_ids()
+            .retain(|path, _| !path.starts_with(drive_path));
+    }
+}
+
+impl CloudClient for GoogleDriveClient {
+    fn backend(&self) -> &'static str {
+        "drive"
+    }
+
+    #[instrument(name = "Google Drive download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        info!("Downloading...");
+        let file = self.file(&self.drive_path(&from_path))?;
+        let mut response = self.send_download_request(&file)?;
+        let written = write_atomically(&mut response, &to_path).map_err(AppError::Io)?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let file = self.file(&self.drive_path(&path))?;
+        Ok(Box::new(self.send_download_request(&file)?))
+    }
+
+    #[instrument(name = "Google Drive upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        self.upload_in_session(from_path, to_path, options)
+    }
+
+    /// Content is sent with resumable upload as well, but unlike uploads of local files
+    /// such uploads can't be resumed after restart
+    #[instrument(name = "Google Drive upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let target = self.upload_target(&to_path, &options)?;
+        let session_url = self.start_upload_session(&target, size)?;
+        let file = self.send_chunks(reader, size, &session_url, 0, |_| Ok(()))?;
+        Ok(file.into())
+    }
+
+    /// Files are moved to the trash, so they can be restored
+    #[instrument(name = "Google Drive delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        let drive_path = self.drive_path(&path);
+        if drive_path.as_os_str().is_empty() {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+
+        info!("Deleting...");
+        let file = self.file(&drive_path)?;
+        let metadata = FileMetadataParametersBuilder::default()
+            .trashed(Some(true))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        self.send_authorized(true, || {
+            self.client
+                .patch(self.urls.file(&file.id))
+                .query(&[("fields", "id")])
+                .json(&metadata)
+        })?;
+        self.forget(&drive_path);
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "Google Drive create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        info!("Creating folder...");
+        let drive_path = self.drive_path(&path);
+        let parent = drive_path.parent().unwrap_or(Path::new(""));
+        let parent_id = self.ensure_folder(parent)?;
+        let name = self.free_name(&parent_id, &drive_path, autorename)?;
+
+        let folder = self.create_file(folder_metadata(&name, &parent_id)?)?;
+        self.remember(&parent.join(name), &folder.id);
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "Google Drive move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Moving...");
+        let source_path = self.drive_path(&from_path);
+        let file = self.file(&source_path)?;
+        let destination_path = self.drive_path(&to_path);
+        let parent_id = self.ensure_folder(destination_path.parent().unwrap_or(Path::new("")))?;
+        let name = self.free_name(&parent_id, &destination_path, options.autorename)?;
+
+        let metadata = FileMetadataParametersBuilder::default()
+            .name(Some(name))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        let mut query = vec![("fields", "id".to_string())];
+        if !file.parents.contains(&parent_id) {
+            query.push(("addParents", parent_id));
+            query.push(("removeParents", file.parents.join(",")));
+        }
+        self.send_authorized(false, || {
+            self.client
+                .patch(self.urls.file(&file.id))
+                .query(&query)
+                .json(&metadata)
+        })?;
+        self.forget(&source_path);
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    #[instrument(name = "Google Drive copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Copying...");
+        let file = self.file(&self.drive_path(&from_path))?;
+        let destination_path = self.drive_path(&to_path);
+        let parent_id = self.ensure_folder(destination_path.parent().unwrap_or(Path::new("")))?;
+        let name = self.free_name(&parent_id, &destination_path, options.autorename)?;
+
+        if file.is_folder() {
+            self.copy_folder(&file, &name, &parent_id)?;
+        } else {
+            self.copy_file(&file, &name, &parent_id)?;
+        }
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let drive_path = self.drive_path(&path);
+        let file = self.file(&drive_path)?;
+        Ok(file.into_entry(file_name(&drive_path)))
+    }
+
+    /// Files sharing their name with others are listed as `name [id]`
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        info!("Listing entries...");
+        let drive_path = self.drive_path(&path);
+        let folder = self.file(&drive_path)?;
+        if !folder.is_folder() {
+            return Err(AppError::Request(format!(
+                "{} is not a folder",
+                path.display()
+            )));
+        }
+
+        let children = self.list_children(&folder.id)?;
+        let mut name_counts = BTreeMap::new();
+        for child in &children {
+            *name_counts.entry(child.name.clone()).or_insert(0) += 1;
+        }
+        let entries: Vec<Entry> = children
+            .into_iter()
+            .map(|child| {
+                let name = if name_counts[&child.name] > 1 {
+                    format!("{} [{}]", child.name, child.id)
+                } else {
+                    child.name.clone()
+                };
+                self.remember(&drive_path.join(&name), &child.id);
+                child.into_entry(name)
+            })
+            .collect();
+
+        debug!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        self.upload_state.load()
+    }
+
+    /// Unfinished upload sessions expire after a week
+    fn abandon_upload(&self, upload: &PendingUpload) -> Result<(), AppError> {
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    fn content_hasher(&self) -> Option<Box<dyn ContentHasher>> {
+        Some(Box::<DriveContentHasher>::default())
+    }
+}
+
+fn folder_metadata(name: &str, parent_id: &str) -> Result<FileMetadataParameters, AppError> {
+    FileMetadataParametersBuilder::default()
+        .name(Some(name.to_string()))
+        .mime_type(Some(FOLDER_MIME_TYPE.to_string()))
+        .parents(Some(vec![parent_id.to_string()]))
+        .build()
+        .map_err(|_| AppError::PrepareRequestParameters)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Splits `name [id]` used to address one of several files with the same name
+fn split_duplicate_name(name: &str) -> Option<(&str, &str)> {
+    let (base_name, id) = name.strip_suffix(']')?.rsplit_once(" [")?;
+    let is_id = !id.is_empty()
+        && id
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '-' || char == '_');
+    is_id.then_some((base_name, id))
+}
+
+/// Escapes string literal of `files.list` query
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+/// Responses of Google Drive API
+struct DriveApi;
+
+impl ApiResponses for DriveApi {
+    /// `308 Resume Incomplete` of upload sessions is a successful response as well
+    fn is_success(status: StatusCode) -> bool {
+        status.is_success() || status == StatusCode::PERMANENT_REDIRECT
+    }
+
+    fn decode_error(response: ErrorResponse) -> AppError {
+        let ErrorResponse {
+            status,
+            retry_after,
+            body,
+            ..
+        } = response;
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited { retry_after },
+            status if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message: body,
+            },
+            status => match serde_json::from_str::<ApiErrorResponse>(&body) {
+                Ok(error) => error.into_app_error(status, retry_after),
+                Err(_) if status == StatusCode::NOT_FOUND => AppError::NotFound(body),
+                Err(_) => AppError::Request(format!("{status}: {body}")),
+            },
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::cloud_client::content_hash::ContentHasher;
+use md5::{Digest, Md5};
+
+/// Computes `md5Checksum` of Drive files, which Google Docs editors files don't have
+#[derive(Default)]
+pub struct DriveContentHasher {
+    md5: Md5,
+}
+
+impl ContentHasher for DriveContentHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.md5.update(data);
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        self.md5
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
@@ -0,0 +1,94 @@
+use crate::errors::AppError;
+use std::collections::BTreeMap;
+
+/// Prefix of MIME types of Google Docs editors files, which have no content of their own
+static GOOGLE_APPS_PREFIX: &str = "application/vnd.google-apps.";
+pub static FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+
+/// Kinds of Google Docs editors files with the format they are exported to by default
+const DEFAULT_FORMATS: [(&str, &str); 4] = [
+    ("document", "docx"),
+    ("spreadsheet", "xlsx"),
+    ("presentation", "pptx"),
+    ("drawing", "png"),
+];
+
+/// MIME types of export formats known by their file extension
+/// More details [here](https://developers.google.com/drive/api/guides/ref-export-formats)
+const EXTENSION_MIME_TYPES: [(&str, &str); 19] = [
+    (
+        "docx",
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    ),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("rtf", "application/rtf"),
+    ("pdf", "application/pdf"),
+    ("txt", "text/plain"),
+    ("html", "text/html"),
+    ("zip", "application/zip"),
+    ("epub", "application/epub+zip"),
+    ("md", "text/markdown"),
+    (
+        "xlsx",
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    ),
+    ("ods", "application/x-vnd.oasis.opendocument.spreadsheet"),
+    ("csv", "text/csv"),
+    ("tsv", "text/tab-separated-values"),
+    (
+        "pptx",
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+    ),
+    ("odp", "application/vnd.oasis.opendocument.presentation"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("svg", "image/svg+xml"),
+    ("json", "application/vnd.google-apps.script+json"),
+];
+
+/// Whether the file is Google Docs editors file, which has to be exported to be downloaded
+pub fn is_google_apps_file(mime_type: &str) -> bool {
+    mime_type.starts_with(GOOGLE_APPS_PREFIX) && mime_type != FOLDER_MIME_TYPE
+}
+
+/// Chooses MIME type Google Docs editors files are exported to
+#[derive(Debug)]
+pub struct ExportFormats {
+    /// Export MIME type by the kind of file, e.g. `document`
+    formats: BTreeMap<String, String>,
+}
+
+impl ExportFormats {
+    /// Formats are configured as file extensions or MIME types, the defaults are used for
+    /// the absent kinds
+    pub fn new(configured: &BTreeMap<String, String>) -> Result<ExportFormats, AppError> {
+        let mut formats = BTreeMap::new();
+        let defaults = DEFAULT_FORMATS
+            .iter()
+            .map(|(kind, format)| (kind.to_string(), format.to_string()));
+        for (kind, format) in defaults.chain(configured.clone()) {
+            formats.insert(kind, export_mime_type(&format)?);
+        }
+        Ok(Self { formats })
+    }
+
+    /// Returns MIME type the file of `mime_type` is exported to
+    pub fn mime_type(&self, mime_type: &str) -> Result<&str, AppError> {
+        let kind = mime_type.trim_start_matches(GOOGLE_APPS_PREFIX);
+        self.formats
+            .get(kind)
+            .map(String::as_str)
+            .ok_or_else(|| AppError::Request(format!("Google {kind} files can't be downloaded")))
+    }
+}
+
+fn export_mime_type(format: &str) -> Result<String, AppError> {
+    if format.contains('/') {
+        return Ok(format.to_string());
+    }
+    EXTENSION_MIME_TYPES
+        .iter()
+        .find(|(extension, _)| extension.eq_ignore_ascii_case(format.trim_start_matches('.')))
+        .map(|(_, mime_type)| mime_type.to_string())
+        .ok_or_else(|| AppError::Config(format!("unknown export format {format}")))
+}
@@ -0,0 +1,9 @@
+pub mod api_url;
+pub mod client;
+pub mod content_hash;
+pub mod export;
+pub mod oauth;
+pub mod parameters;
+pub mod responses;
+#[cfg(test)]
+mod tests;
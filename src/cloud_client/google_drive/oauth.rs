@@ -0,0 +1,213 @@
+use crate::cloud_client::authenticator::request_token;
+use crate::cloud_client::google_drive::api_url::{ApiUrls, AUTHORIZE_URL, REVOKE_URL};
+use crate::cloud_client::google_drive::parameters::token::{
+    GrantType, TokenParameters, TokenParametersBuilder,
+};
+use crate::config::GoogleDriveConfig;
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use tracing::info;
+
+/// Length of PKCE code verifier, must be between 43 and 128 characters
+const CODE_VERIFIER_LENGTH: usize = 64;
+const STATE_LENGTH: usize = 32;
+/// Full access to files of the user
+static DRIVE_SCOPE: &str = "https://www.googleapis.com/auth/drive";
+
+/// Long-lived credentials received after login
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StoredCredentials {
+    pub client_id: String,
+    pub client_secret: Option<String>,
+    pub refresh_token: String,
+}
+
+impl StoredCredentials {
+    pub fn load(
+        store: &dyn CredentialStore,
+        key: &str,
+    ) -> Result<Option<StoredCredentials>, AppError> {
+        store
+            .load(key)?
+            .map(|secret| {
+                serde_json::from_str(&secret)
+                    .map_err(|error| AppError::Credentials(error.to_string()))
+            })
+            .transpose()
+    }
+
+    pub fn save(&self, store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+        let secret = serde_json::to_string(self)
+            .map_err(|error| AppError::Credentials(error.to_string()))?;
+        store.save(key, &secret)
+    }
+
+    /// Parameters of the request receiving new access token with the refresh token
+    pub fn refresh_parameters(&self) -> Result<TokenParameters, AppError> {
+        TokenParametersBuilder::default()
+            .grant_type(GrantType::RefreshToken)
+            .client_id(self.client_id.clone())
+            .client_secret(self.client_secret.clone())
+            .refresh_token(Some(self.refresh_token.clone()))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)
+    }
+}
+
+/// Runs OAuth2 authorization code flow with PKCE for desktop applications:
+/// prints the authorization URL and receives the code with the redirect to a local port,
+/// as Google doesn't show codes to be pasted. The refresh token is stored under `key`.
+pub fn login(
+    store: &dyn CredentialStore,
+    key: &str,
+    config: &GoogleDriveConfig,
+) -> Result<(), AppError> {
+    let client_id = match &config.client_id {
+        Some(client_id) => client_id.clone(),
+        None => std::env::var("GOOGLE_CLIENT_ID")
+            .map_err(|_| AppError::Config("Google client id is absent".to_string()))?,
+    };
+    let client_secret = config
+        .client_secret
+        .clone()
+        .or_else(|| std::env::var("GOOGLE_CLIENT_SECRET").ok());
+
+    let code_verifier = random_string(CODE_VERIFIER_LENGTH);
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+    let state = random_string(STATE_LENGTH);
+
+    let listener = TcpListener::bind("127.0.0.1:0").map_err(AppError::Io)?;
+    let redirect_uri = format!(
+        "http://127.0.0.1:{}",
+        listener.local_addr().map_err(AppError::Io)?.port()
+    );
+
+    let authorize_url = Url::parse_with_params(
+        AUTHORIZE_URL,
+        [
+            ("client_id", client_id.as_str()),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", DRIVE_SCOPE),
+            ("code_challenge", code_challenge.as_str()),
+            ("code_challenge_method", "S256"),
+            ("access_type", "offline"),
+            ("prompt", "consent"),
+            ("state", state.as_str()),
+        ],
+    )
+    .map_err(|error| AppError::Authorization(error.to_string()))?;
+
+    println!("Open the following URL in your browser and allow access:\n\n{authorize_url}\n");
+    let code = receive_code(&listener, &state)?;
+
+    let parameters = TokenParametersBuilder::default()
+        .grant_type(GrantType::AuthorizationCode)
+        .client_id(client_id.clone())
+        .client_secret(client_secret.clone())
+        .code(Some(code))
+        .code_verifier(Some(code_verifier))
+        .redirect_uri(Some(redirect_uri))
+        .build()
+        .map_err(|_| AppError::PrepareRequestParameters)?;
+    let token = request_token(&Client::new(), ApiUrls::new(config).token(), &parameters)?;
+
+    let refresh_token = token
+        .refresh_token
+        .ok_or_else(|| AppError::Authorization("refresh token is absent".to_string()))?;
+    StoredCredentials {
+        client_id,
+        client_secret,
+        refresh_token,
+    }
+    .save(store, key)?;
+
+    println!("Logged in successfully");
+    Ok(())
+}
+
+/// Waits for the browser to be redirected to the local port with the authorization code
+fn receive_code(listener: &TcpListener, state: &str) -> Result<String, AppError> {
+    let (mut stream, _) = listener.accept().map_err(AppError::Io)?;
+    let mut request_line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut request_line)
+        .map_err(AppError::Io)?;
+
+    // Request line looks like `GET /?state=...&code=... HTTP/1.1`
+    let target = request_line.split_whitespace().nth(1).unwrap_or_default();
+    let url = Url::parse(&format!("http://127.0.0.1{target}"))
+        .map_err(|error| AppError::Authorization(error.to_string()))?;
+    let parameter = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    };
+
+    let result = match (parameter("state"), parameter("code"), parameter("error")) {
+        (_, _, Some(error)) => Err(AppError::Authorization(error)),
+        (Some(received), Some(code), _) if received == state => Ok(code),
+        _ => Err(AppError::Authorization(
+            "unexpected authorization response".to_string(),
+        )),
+    };
+
+    let message = match &result {
+        Ok(_) => "Access has been allowed, you can close this page.",
+        Err(_) => "Access has not been allowed, see the terminal for details.",
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{message}",
+        message.len()
+    );
+    result
+}
+
+/// Revokes stored refresh token and deletes it from the store.
+/// Credentials are deleted even if revoking fails, e.g. when they were already revoked.
+pub fn logout(store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+    let Some(credentials) = StoredCredentials::load(store, key)? else {
+        println!("There are no stored credentials");
+        return Ok(());
+    };
+
+    info!("Revoking refresh token...");
+    let revoked = Client::new()
+        .post(REVOKE_URL)
+        .form(&[("token", credentials.refresh_token.as_str())])
+        .send()
+        .map_err(|error| AppError::SendRequest(error.to_string()))
+        .and_then(|response| {
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(AppError::Authorization(response.status().to_string()))
+            }
+        });
+    if let Err(error) = revoked {
+        println!("Unable to revoke token: {error}");
+    }
+
+    store.delete(key)?;
+    println!("Logged out successfully");
+    Ok(())
+}
+
+fn random_string(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
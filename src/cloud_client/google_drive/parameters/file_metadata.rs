@@ -0,0 +1,21 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// Body of requests creating, updating and copying files
+#[derive(Serialize, Deserialize, Builder, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FileMetadataParameters {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mime_type: Option<String>,
+    /// Parents can be set only when the file is created or copied
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parents: Option<Vec<String>>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trashed: Option<bool>,
+}
@@ -0,0 +1,2 @@
+pub mod file_metadata;
+pub mod token;
@@ -0,0 +1,62 @@
+use crate::errors::AppError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Body of error response
+/// More details [here](https://developers.google.com/drive/api/guides/handle-errors)
+#[derive(Deserialize, Debug)]
+pub struct ApiErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiError {
+    #[serde(default)]
+    pub message: String,
+    #[serde(default)]
+    pub errors: Vec<ApiErrorDetail>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiErrorDetail {
+    #[serde(default)]
+    pub reason: String,
+}
+
+impl ApiErrorResponse {
+    pub fn reasons(&self) -> Vec<&str> {
+        self.error
+            .errors
+            .iter()
+            .map(|detail| detail.reason.as_str())
+            .collect()
+    }
+
+    pub fn into_app_error(self, status: StatusCode, retry_after: Option<u64>) -> AppError {
+        let reasons = self.reasons();
+        let message = self.error.message.trim_end_matches('.').to_string();
+
+        if status == StatusCode::UNAUTHORIZED {
+            return AppError::ExpiredAccessToken;
+        }
+        // Drive reports rate limits with 403 as well as with 429
+        if reasons
+            .iter()
+            .any(|reason| matches!(*reason, "rateLimitExceeded" | "userRateLimitExceeded"))
+        {
+            return AppError::RateLimited { retry_after };
+        }
+
+        if status == StatusCode::NOT_FOUND {
+            AppError::NotFound(message)
+        } else if reasons.contains(&"storageQuotaExceeded") {
+            AppError::InsufficientSpace
+        } else if status == StatusCode::FORBIDDEN {
+            AppError::Unauthorized(message)
+        } else if status == StatusCode::CONFLICT || status == StatusCode::PRECONDITION_FAILED {
+            AppError::Conflict(message)
+        } else {
+            AppError::Api(message)
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::cloud_client::google_drive::export::FOLDER_MIME_TYPE;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Fields of files requested from the API, the rest of them are not returned
+pub static FILE_FIELDS: &str = "id,name,mimeType,size,modifiedTime,md5Checksum,parents,trashed";
+pub static FILE_LIST_FIELDS: &str =
+    "nextPageToken,files(id,name,mimeType,size,modifiedTime,md5Checksum,parents,trashed)";
+
+/// Drive file, folders are files of the special MIME type
+/// More details [here](https://developers.google.com/drive/api/reference/rest/v3/files)
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveFile {
+    pub id: String,
+    pub name: String,
+    pub mime_type: String,
+    /// Size in bytes encoded as string, absent for folders and Google Docs editors files
+    pub size: Option<String>,
+    pub modified_time: Option<DateTime<Utc>>,
+    pub md5_checksum: Option<String>,
+    #[serde(default)]
+    pub parents: Vec<String>,
+    #[serde(default)]
+    pub trashed: bool,
+}
+
+impl DriveFile {
+    pub fn is_folder(&self) -> bool {
+        self.mime_type == FOLDER_MIME_TYPE
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+            .as_deref()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Converts file into entry shown with `name`, which differs from the file name
+    /// when there are several files with the same name in the folder
+    pub fn into_entry(self, name: String) -> Entry {
+        let is_folder = self.is_folder();
+        Entry {
+            name,
+            kind: if is_folder {
+                EntryKind::Folder
+            } else {
+                EntryKind::File
+            },
+            size: if is_folder { 0 } else { self.size() },
+            modified: self.modified_time,
+            hash: self.md5_checksum,
+            id: Some(self.id),
+        }
+    }
+}
+
+impl From<DriveFile> for Entry {
+    fn from(file: DriveFile) -> Self {
+        let name = file.name.clone();
+        file.into_entry(name)
+    }
+}
+
+/// Page of `files.list` response
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileList {
+    #[serde(default)]
+    pub files: Vec<DriveFile>,
+    pub next_page_token: Option<String>,
+}
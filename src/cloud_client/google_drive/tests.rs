@@ -0,0 +1,333 @@
+use crate::cloud_client::google_drive::client::GoogleDriveClient;
+use crate::cloud_client::google_drive::oauth::StoredCredentials;
+use crate::cloud_client::mock_http::{Reply, ScriptedHttp};
+use crate::cloud_client::{CloudClient, UploadOptions};
+use crate::config::{BackendConfig, GoogleDriveConfig, RemoteConfig, RetryConfig};
+use crate::credentials::file::FileCredentialStore;
+use crate::errors::AppError;
+use serde_json::{json, Value};
+use std::io::Read;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const REMOTE: &str = "mock";
+static FOLDER: &str = "application/vnd.google-apps.folder";
+static DOCX: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+
+/// Client of a mock server logged in with stored credentials, uploading in 256 KiB chunks.
+/// The first request of every test receives access token.
+struct Fixture {
+    server: ScriptedHttp,
+    client: GoogleDriveClient,
+    _directory: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let server = ScriptedHttp::start();
+        let directory = tempfile::tempdir().expect("temporary directory is created");
+
+        let store = FileCredentialStore::new(directory.path().join("credentials"), None);
+        StoredCredentials {
+            client_id: "mock-client".to_string(),
+            client_secret: None,
+            refresh_token: "refresh".to_string(),
+        }
+        .save(&store, REMOTE)
+        .expect("credentials are stored");
+
+        // Chunk size is rounded up to the smallest one allowed, 256 KiB
+        let config = GoogleDriveConfig {
+            upload_chunk_size: NonZeroU64::new(1),
+            api_url: Some(server.url()),
+            upload_url: Some(format!("{}/upload", server.url())),
+            token_url: Some(format!("{}/token", server.url())),
+            ..GoogleDriveConfig::default()
+        };
+        let remote = RemoteConfig {
+            backend: BackendConfig::GoogleDrive(GoogleDriveConfig::default()),
+            credentials: None,
+            root: None,
+            retry: RetryConfig {
+                max_attempts: NonZeroU32::new(3),
+                max_elapsed_secs: NonZeroU64::new(10),
+                initial_backoff_ms: NonZeroU64::new(1),
+                max_backoff_ms: NonZeroU64::new(1),
+            },
+        };
+        let client =
+            GoogleDriveClient::build(REMOTE, &remote, &config, &store).expect("client is built");
+
+        server.reply(
+            "POST",
+            "/token",
+            Reply::json(200, json!({"access_token": "access", "expires_in": 3600})),
+        );
+        server.reply(
+            "GET",
+            "/files/root",
+            Reply::json(200, folder("root-id", "")),
+        );
+        Fixture {
+            server,
+            client,
+            _directory: directory,
+        }
+    }
+
+    /// Answers the next `files.list` request with the files
+    fn list(&self, files: Vec<Value>) {
+        self.server
+            .reply("GET", "/files", Reply::json(200, json!({ "files": files })));
+    }
+
+    fn targets(&self) -> Vec<String> {
+        self.server
+            .requests()
+            .into_iter()
+            .map(|request| format!("{} {}", request.method, request.path()))
+            .collect()
+    }
+
+    /// Returns queries of `files.list` requests
+    fn list_queries(&self) -> Vec<String> {
+        self.server
+            .requests()
+            .into_iter()
+            .filter(|request| request.path() == "/files")
+            .filter_map(|request| request.query("q"))
+            .collect()
+    }
+}
+
+fn file(id: &str, name: &str, parent: &str) -> Value {
+    json!({
+        "id": id,
+        "name": name,
+        "mimeType": "text/plain",
+        "size": "1",
+        "parents": [parent],
+    })
+}
+
+fn folder(id: &str, name: &str) -> Value {
+    json!({"id": id, "name": name, "mimeType": FOLDER})
+}
+
+#[test]
+fn resolved_path_is_cached() {
+    let fixture = Fixture::new();
+    fixture.list(vec![folder("docs-id", "docs")]);
+    fixture.list(vec![file("a-id", "a.txt", "docs-id")]);
+    fixture.server.reply(
+        "GET",
+        "/files/a-id",
+        Reply::json(200, file("a-id", "a.txt", "docs-id")),
+    );
+
+    for _ in 0..2 {
+        let entry = fixture
+            .client
+            .metadata(PathBuf::from("/docs/a.txt"))
+            .expect("file is found");
+        assert_eq!(entry.id.as_deref(), Some("a-id"));
+    }
+
+    assert_eq!(
+        fixture.targets(),
+        [
+            "POST /token",
+            "GET /files/root",
+            "GET /files",
+            "GET /files",
+            "GET /files/a-id"
+        ]
+    );
+    assert_eq!(
+        fixture.list_queries(),
+        [
+            "name = 'docs' and 'root-id' in parents and trashed = false",
+            "name = 'a.txt' and 'docs-id' in parents and trashed = false"
+        ]
+    );
+    assert!(fixture.server.requests()[1]
+        .header("Authorization")
+        .is_some_and(|authorization| authorization == "Bearer access"));
+}
+
+#[test]
+fn outdated_cached_id_is_resolved_again() {
+    let fixture = Fixture::new();
+    fixture.list(vec![file("old-id", "a.txt", "root-id")]);
+    fixture.server.reply(
+        "GET",
+        "/files/old-id",
+        Reply::json(200, file("old-id", "a.txt", "root-id")),
+    );
+    // The second lookup is answered by the cached id
+    for _ in 0..2 {
+        fixture
+            .client
+            .metadata(PathBuf::from("/a.txt"))
+            .expect("file is found");
+    }
+
+    // The file has been replaced elsewhere
+    fixture.server.reply(
+        "GET",
+        "/files/old-id",
+        Reply::json(
+            404,
+            json!({"error": {"code": 404, "message": "File not found: old-id."}}),
+        ),
+    );
+    fixture.server.reply(
+        "GET",
+        "/files/root",
+        Reply::json(200, folder("root-id", "")),
+    );
+    fixture.list(vec![file("new-id", "a.txt", "root-id")]);
+
+    let entry = fixture
+        .client
+        .metadata(PathBuf::from("/a.txt"))
+        .expect("file is found again");
+
+    assert_eq!(entry.id.as_deref(), Some("new-id"));
+    assert!(fixture.server.is_done());
+}
+
+#[test]
+fn files_with_same_name_are_listed_with_their_ids() {
+    let fixture = Fixture::new();
+    fixture.list(vec![
+        file("a1", "a.txt", "root-id"),
+        file("a2", "a.txt", "root-id"),
+        file("b1", "b.txt", "root-id"),
+    ]);
+
+    let entries = fixture
+        .client
+        .list_entries(PathBuf::from("/"))
+        .expect("folder is listed");
+
+    let names: Vec<String> = entries.iter().map(|entry| entry.display_name()).collect();
+    assert_eq!(names, ["a.txt [a1]", "a.txt [a2]", "b.txt"]);
+    assert_eq!(
+        fixture.list_queries(),
+        ["'root-id' in parents and trashed = false"]
+    );
+}
+
+#[test]
+fn duplicate_is_addressed_with_its_id() {
+    let fixture = Fixture::new();
+    fixture.server.reply(
+        "GET",
+        "/files/a2",
+        Reply::json(200, file("a2", "a.txt", "root-id")),
+    );
+    fixture.list(vec![
+        file("a1", "a.txt", "root-id"),
+        file("a2", "a.txt", "root-id"),
+    ]);
+
+    let entry = fixture
+        .client
+        .metadata(PathBuf::from("/a.txt [a2]"))
+        .expect("duplicate is found");
+    let result = fixture.client.metadata(PathBuf::from("/a.txt"));
+
+    assert_eq!(entry.id.as_deref(), Some("a2"));
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+    assert_eq!(
+        fixture.targets(),
+        [
+            "POST /token",
+            "GET /files/root",
+            "GET /files/a2",
+            "GET /files"
+        ]
+    );
+}
+
+#[test]
+fn resumable_upload_resends_bytes_server_did_not_keep() {
+    let fixture = Fixture::new();
+    let session_url = format!("{}/upload/session", fixture.server.url());
+    fixture.list(Vec::new());
+    fixture.server.reply(
+        "POST",
+        "/upload/files",
+        Reply::new(200, "").header("Location", &session_url),
+    );
+    // Only a part of the first chunk has been received
+    fixture.server.reply(
+        "PUT",
+        "/upload/session",
+        Reply::new(308, "").header("Range", "bytes=0-99999"),
+    );
+    fixture.server.reply(
+        "PUT",
+        "/upload/session",
+        Reply::json(200, file("big-id", "big.bin", "root-id")),
+    );
+
+    let content: Vec<u8> = (0..300 * 1024).map(|index| index as u8).collect();
+    let entry = fixture
+        .client
+        .upload_stream(
+            &mut &content[..],
+            content.len() as u64,
+            PathBuf::from("/big.bin"),
+            UploadOptions::default(),
+        )
+        .expect("content is uploaded");
+
+    assert_eq!(entry.id.as_deref(), Some("big-id"));
+    let requests = fixture.server.requests();
+    let start = &requests[3];
+    assert_eq!(start.query("uploadType").as_deref(), Some("resumable"));
+    assert_eq!(start.header("X-Upload-Content-Length"), Some("307200"));
+    let chunks = &requests[4..];
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+        chunks[0].header("Content-Range"),
+        Some("bytes 0-262143/307200")
+    );
+    assert_eq!(chunks[0].body, &content[..262144]);
+    assert_eq!(
+        chunks[1].header("Content-Range"),
+        Some("bytes 100000-307199/307200")
+    );
+    assert_eq!(chunks[1].body, &content[100000..]);
+}
+
+#[test]
+fn google_docs_file_is_exported() {
+    let fixture = Fixture::new();
+    fixture.list(vec![json!({
+        "id": "doc-id",
+        "name": "notes",
+        "mimeType": "application/vnd.google-apps.document",
+        "parents": ["root-id"],
+    })]);
+    fixture
+        .server
+        .reply("GET", "/files/doc-id/export", Reply::new(200, "document"));
+
+    let mut content = String::new();
+    fixture
+        .client
+        .open_download(PathBuf::from("/notes"))
+        .expect("file is exported")
+        .read_to_string(&mut content)
+        .unwrap();
+
+    assert_eq!(content, "document");
+    let requests = fixture.server.requests();
+    let export = requests.last().unwrap();
+    assert_eq!(export.path(), "/files/doc-id/export");
+    assert_eq!(export.query("mimeType").as_deref(), Some(DOCX));
+}
@@ -0,0 +1,63 @@
+use crate::errors::AppError;
+use reqwest::blocking::{RequestBuilder, Response};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{StatusCode, Url};
+use tracing::{debug, warn};
+
+/// Unsuccessful response of cloud storage API with its body read
+#[derive(Debug)]
+pub struct ErrorResponse {
+    pub status: StatusCode,
+    /// URL of the response, i.e. of the request after redirects
+    pub url: Url,
+    /// Seconds to wait from `Retry-After` header
+    pub retry_after: Option<u64>,
+    pub body: String,
+}
+
+impl ErrorResponse {
+    fn read(response: Response) -> Self {
+        let status = response.status();
+        let url = response.url().clone();
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let body = response.text().unwrap_or_default();
+        warn!("Request failed with {status}: {body}");
+        Self {
+            status,
+            url,
+            retry_after,
+            body,
+        }
+    }
+}
+
+/// Tells successful responses of cloud storage API from failed ones,
+/// which are described by error bodies specific to the API
+pub trait ApiResponses {
+    fn is_success(status: StatusCode) -> bool {
+        status.is_success()
+    }
+
+    /// Turns unsuccessful response into the error describing what actually went wrong
+    fn decode_error(response: ErrorResponse) -> AppError;
+}
+
+pub fn send_checked<A: ApiResponses>(request: RequestBuilder) -> Result<Response, AppError> {
+    let response = request
+        .send()
+        .map_err(|error| AppError::SendRequest(error.to_string()))?;
+    check_response::<A>(response)
+}
+
+pub fn check_response<A: ApiResponses>(response: Response) -> Result<Response, AppError> {
+    debug!("Response: {:?}", response);
+    if A::is_success(response.status()) {
+        Ok(response)
+    } else {
+        Err(A::decode_error(ErrorResponse::read(response)))
+    }
+}
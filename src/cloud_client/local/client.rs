@@ -0,0 +1,309 @@
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::cloud_client::upload_state::PendingUpload;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::RemoteConfig;
+use crate::errors::AppError;
+use crate::utilities::files::{write_atomically, write_atomically_exact};
+use chrono::{DateTime, Utc};
+use std::fs::{self, File, Metadata};
+use std::io::{ErrorKind, Read};
+use std::path::{Component, Path, PathBuf};
+use tracing::{info, instrument, warn};
+use walkdir::WalkDir;
+
+/// Uses a folder on the local machine, e.g. a NAS mount or a USB disk, as a cloud storage.
+/// All paths are resolved within the root folder and can't escape it.
+#[derive(Debug)]
+pub struct LocalFsClient {
+    root: PathBuf,
+}
+
+impl LocalFsClient {
+    pub fn new(root: &Path) -> Result<LocalFsClient, AppError> {
+        let root = fs::canonicalize(root).map_err(AppError::Io)?;
+        if !root.is_dir() {
+            return Err(AppError::Config(format!(
+                "{} is not a folder",
+                root.display()
+            )));
+        }
+        Ok(Self { root })
+    }
+
+    /// Builds client of the remote named `name`, which has to specify its root folder
+    pub fn build(name: &str, remote: &RemoteConfig) -> Result<LocalFsClient, AppError> {
+        let root = remote
+            .root
+            .as_ref()
+            .ok_or_else(|| AppError::Config(format!("local remote {name} requires root")))?;
+        Self::new(root)
+    }
+
+    /// Resolves path of the remote into path on the local machine.
+    /// `..` can't lead out of the root folder, neither can symbolic links inside it.
+    fn local_path(&self, path: &Path) -> Result<PathBuf, AppError> {
+        let mut relative = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => relative.push(name),
+                Component::ParentDir if !relative.pop() => return Err(outside_root(path)),
+                _ => {}
+            }
+        }
+        let local_path = self.root.join(relative);
+
+        // Symbolic links are followed for the part of the path which already exists
+        let existing = local_path
+            .ancestors()
+            .find_map(|ancestor| fs::canonicalize(ancestor).ok())
+            .ok_or_else(|| outside_root(path))?;
+        if !existing.starts_with(&self.root) {
+            return Err(outside_root(path));
+        }
+        Ok(local_path)
+    }
+
+    /// Returns the destination path, renamed if there is a conflict and `autorename` allows that
+    fn destination_path(&self, path: &Path, autorename: bool) -> Result<PathBuf, AppError> {
+        let path = free_path(path, autorename, |candidate| {
+            Ok(self.local_path(candidate)?.exists())
+        })?;
+        self.local_path(&path)
+    }
+}
+
+impl CloudClient for LocalFsClient {
+    fn backend(&self) -> &'static str {
+        "local"
+    }
+
+    #[instrument(name = "Local download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        let mut file = open_file(&self.local_path(&from_path)?, &from_path)?;
+        let written = write_atomically(&mut file, &to_path).map_err(AppError::Io)?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let file = open_file(&self.local_path(&path)?, &path)?;
+        Ok(Box::new(file))
+    }
+
+    #[instrument(name = "Local upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let mut file = File::open(&from_path).map_err(AppError::Io)?;
+        let size = file.metadata().map_err(AppError::Io)?.len();
+        self.upload_stream(&mut file, size, to_path, options)?;
+        Ok(())
+    }
+
+    /// Revisions are not kept for local files, so `update` mode is not supported
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let local_path = match options.mode {
+            WriteMode::Add => self.destination_path(&to_path, options.autorename)?,
+            WriteMode::Overwrite => self.local_path(&to_path)?,
+            WriteMode::Update(_) => {
+                return Err(AppError::Request(
+                    "local remote doesn't support update mode".to_string(),
+                ))
+            }
+        };
+        if let Some(parent) = local_path.parent() {
+            fs::create_dir_all(parent).map_err(AppError::Io)?;
+        }
+
+        info!("Uploading...");
+        // Short read must not replace the file being overwritten
+        write_atomically_exact(reader, &local_path, size).map_err(AppError::Io)?;
+
+        info!("File has been uploaded");
+        local_entry(&local_path)
+    }
+
+    #[instrument(name = "Local delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        let local_path = self.local_path(&path)?;
+        if local_path == self.root {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+
+        let metadata = entry_metadata(&local_path, &path)?;
+        if metadata.is_dir() {
+            fs::remove_dir_all(&local_path).map_err(AppError::Io)?;
+        } else {
+            fs::remove_file(&local_path).map_err(AppError::Io)?;
+        }
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "Local create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        let local_path = self.destination_path(&path, autorename)?;
+        fs::create_dir_all(local_path).map_err(AppError::Io)?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "Local move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let source = self.local_path(&from_path)?;
+        entry_metadata(&source, &from_path)?;
+        let destination = self.destination_path(&to_path, options.autorename)?;
+        if destination.starts_with(&source) {
+            return Err(AppError::Request(
+                "folder can't be moved into itself".to_string(),
+            ));
+        }
+
+        fs::rename(source, destination).map_err(AppError::Io)?;
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    #[instrument(name = "Local copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let source = self.local_path(&from_path)?;
+        let metadata = entry_metadata(&source, &from_path)?;
+        if is_symlink(&source)? {
+            return Err(AppError::Request(format!(
+                "{} is a symbolic link and can't be copied",
+                from_path.display()
+            )));
+        }
+        let destination = self.destination_path(&to_path, options.autorename)?;
+
+        if !metadata.is_dir() {
+            fs::copy(source, destination).map_err(AppError::Io)?;
+        } else if destination.starts_with(&source) {
+            return Err(AppError::Request(
+                "folder can't be copied into itself".to_string(),
+            ));
+        } else {
+            copy_folder(&source, &destination)?;
+        }
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let local_path = self.local_path(&path)?;
+        entry_metadata(&local_path, &path)?;
+        local_entry(&local_path)
+    }
+
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        let local_path = self.local_path(&path)?;
+        entry_metadata(&local_path, &path)?;
+
+        let mut entries = fs::read_dir(local_path)
+            .map_err(AppError::Io)?
+            .map(|entry| local_entry(&entry.map_err(AppError::Io)?.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        entries.sort_by(|first, second| first.name.cmp(&second.name));
+        Ok(entries)
+    }
+
+    /// Local files are written in one go, so there is nothing to resume
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn abandon_upload(&self, _upload: &PendingUpload) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+fn outside_root(path: &Path) -> AppError {
+    AppError::Request(format!("{} leads outside of the remote", path.display()))
+}
+
+/// Reads metadata of the entry, reporting absent entry with the path of the remote
+fn entry_metadata(local_path: &Path, path: &Path) -> Result<Metadata, AppError> {
+    fs::metadata(local_path).map_err(|error| match error.kind() {
+        ErrorKind::NotFound => AppError::NotFound(path.display().to_string()),
+        _ => AppError::Io(error),
+    })
+}
+
+fn open_file(local_path: &Path, path: &Path) -> Result<File, AppError> {
+    if entry_metadata(local_path, path)?.is_dir() {
+        return Err(AppError::Request(format!("{} is a folder", path.display())));
+    }
+    File::open(local_path).map_err(AppError::Io)
+}
+
+fn local_entry(local_path: &Path) -> Result<Entry, AppError> {
+    let metadata = fs::metadata(local_path).map_err(AppError::Io)?;
+    let is_dir = metadata.is_dir();
+    Ok(Entry {
+        name: local_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        kind: if is_dir {
+            EntryKind::Folder
+        } else {
+            EntryKind::File
+        },
+        size: if is_dir { 0 } else { metadata.len() },
+        modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        hash: None,
+        id: None,
+    })
+}
+
+fn is_symlink(local_path: &Path) -> Result<bool, AppError> {
+    Ok(fs::symlink_metadata(local_path)
+        .map_err(AppError::Io)?
+        .file_type()
+        .is_symlink())
+}
+
+/// Copies the folder with its content. Symbolic links inside it are skipped, since they may
+/// lead outside of the remote.
+fn copy_folder(source: &Path, destination: &Path) -> Result<(), AppError> {
+    for entry in WalkDir::new(source) {
+        let entry = entry.map_err(|error| AppError::Io(error.into()))?;
+        if entry.path_is_symlink() {
+            warn!("Symbolic link {} is skipped", entry.path().display());
+            continue;
+        }
+        let target = destination.join(entry.path().strip_prefix(source).unwrap_or(entry.path()));
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(target).map_err(AppError::Io)?;
+        } else {
+            fs::copy(entry.path(), target).map_err(AppError::Io)?;
+        }
+    }
+    Ok(())
+}
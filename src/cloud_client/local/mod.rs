@@ -0,0 +1,3 @@
+pub mod client;
+#[cfg(test)]
+mod tests;
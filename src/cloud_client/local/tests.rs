@@ -0,0 +1,228 @@
+use crate::cloud_client::local::client::LocalFsClient;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::errors::AppError;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// Client of the `root` folder inside a temporary directory, which also has an `outside` folder
+struct Fixture {
+    client: LocalFsClient,
+    directory: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let directory = tempfile::tempdir().expect("temporary directory is created");
+        fs::create_dir(directory.path().join("root")).unwrap();
+        fs::create_dir(directory.path().join("outside")).unwrap();
+        fs::write(directory.path().join("outside/secret.txt"), b"secret").unwrap();
+        let client = LocalFsClient::new(&directory.path().join("root")).unwrap();
+        Fixture { client, directory }
+    }
+
+    fn root(&self) -> PathBuf {
+        self.directory.path().join("root")
+    }
+
+    fn upload(&self, path: &str, content: &[u8], options: UploadOptions) -> Result<(), AppError> {
+        self.client
+            .upload_stream(
+                &mut &content[..],
+                content.len() as u64,
+                PathBuf::from(path),
+                options,
+            )
+            .map(|_| ())
+    }
+}
+
+fn is_outside_root(result: Result<impl Sized, AppError>) -> bool {
+    matches!(result, Err(AppError::Request(message)) if message.ends_with("leads outside of the remote"))
+}
+
+#[test]
+fn parent_folder_of_root_is_unreachable() {
+    let fixture = Fixture::new();
+
+    assert!(is_outside_root(
+        fixture
+            .client
+            .metadata(PathBuf::from("../outside/secret.txt"))
+    ));
+    assert!(is_outside_root(fixture.upload(
+        "/docs/../../x",
+        b"x",
+        UploadOptions::default()
+    )));
+    assert!(!fixture.directory.path().join("x").exists());
+}
+
+#[test]
+fn parent_folder_within_root_is_resolved() {
+    let fixture = Fixture::new();
+    fixture
+        .upload("/docs/../a.txt", b"a", UploadOptions::default())
+        .unwrap();
+
+    assert_eq!(fs::read(fixture.root().join("a.txt")).unwrap(), b"a");
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_link_out_of_root_is_not_followed() {
+    let fixture = Fixture::new();
+    std::os::unix::fs::symlink(
+        fixture.directory.path().join("outside"),
+        fixture.root().join("link"),
+    )
+    .unwrap();
+
+    assert!(is_outside_root(
+        fixture.client.list_entries(PathBuf::from("/link"))
+    ));
+    assert!(is_outside_root(
+        fixture
+            .client
+            .open_download(PathBuf::from("/link/secret.txt"))
+    ));
+    assert!(is_outside_root(fixture.upload(
+        "/link/new.txt",
+        b"new",
+        UploadOptions::default()
+    )));
+    assert!(!fixture.directory.path().join("outside/new.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn symbolic_links_are_not_copied() {
+    let fixture = Fixture::new();
+    fixture
+        .upload("/docs/a.txt", b"a", UploadOptions::default())
+        .unwrap();
+    std::os::unix::fs::symlink(
+        fixture.directory.path().join("outside/secret.txt"),
+        fixture.root().join("docs/secret.txt"),
+    )
+    .unwrap();
+    std::os::unix::fs::symlink(
+        fixture.root().join("docs/a.txt"),
+        fixture.root().join("link.txt"),
+    )
+    .unwrap();
+
+    fixture
+        .client
+        .copy_entry(
+            PathBuf::from("/docs"),
+            PathBuf::from("/copy"),
+            RelocationOptions::default(),
+        )
+        .unwrap();
+    assert_eq!(fs::read(fixture.root().join("copy/a.txt")).unwrap(), b"a");
+    assert!(!fixture.root().join("copy/secret.txt").exists());
+
+    let result = fixture.client.copy_entry(
+        PathBuf::from("/link.txt"),
+        PathBuf::from("/copy.txt"),
+        RelocationOptions::default(),
+    );
+    assert!(matches!(result, Err(AppError::Request(_))));
+    assert!(!fixture.root().join("copy.txt").exists());
+}
+
+#[test]
+fn root_is_not_deleted() {
+    let fixture = Fixture::new();
+
+    for path in ["/", "", "/docs/.."] {
+        let result = fixture.client.delete(PathBuf::from(path));
+        assert!(matches!(result, Err(AppError::Request(_))), "{path}");
+    }
+    assert!(fixture.root().exists());
+}
+
+#[test]
+fn conflicting_entries_are_renamed_with_autorename() {
+    let fixture = Fixture::new();
+    fixture
+        .upload("/a.txt", b"first", UploadOptions::default())
+        .unwrap();
+
+    let result = fixture.upload("/a.txt", b"second", UploadOptions::default());
+    assert!(matches!(result, Err(AppError::Conflict(path)) if path == "/a.txt"));
+
+    let autorename = UploadOptions {
+        autorename: true,
+        ..UploadOptions::default()
+    };
+    fixture
+        .upload("/a.txt", b"second", autorename.clone())
+        .unwrap();
+    fixture.upload("/a.txt", b"third", autorename).unwrap();
+    fixture
+        .client
+        .create_folder(PathBuf::from("/docs"), false)
+        .unwrap();
+    fixture
+        .client
+        .create_folder(PathBuf::from("/docs"), true)
+        .unwrap();
+
+    assert_eq!(fs::read(fixture.root().join("a.txt")).unwrap(), b"first");
+    assert_eq!(
+        fs::read(fixture.root().join("a (1).txt")).unwrap(),
+        b"second"
+    );
+    assert_eq!(
+        fs::read(fixture.root().join("a (2).txt")).unwrap(),
+        b"third"
+    );
+    assert!(fixture.root().join("docs (1)").is_dir());
+}
+
+#[test]
+fn folder_is_not_moved_into_itself() {
+    let fixture = Fixture::new();
+    fixture
+        .upload("/docs/a.txt", b"a", UploadOptions::default())
+        .unwrap();
+
+    let result = fixture.client.move_entry(
+        PathBuf::from("/docs"),
+        PathBuf::from("/docs/nested"),
+        RelocationOptions::default(),
+    );
+    assert!(matches!(result, Err(AppError::Request(_))));
+    assert!(matches!(
+        fixture.client.copy_entry(
+            PathBuf::from("/docs"),
+            PathBuf::from("/docs/copy"),
+            RelocationOptions::default()
+        ),
+        Err(AppError::Request(_))
+    ));
+    assert_eq!(fs::read(fixture.root().join("docs/a.txt")).unwrap(), b"a");
+}
+
+#[test]
+fn short_upload_does_not_replace_overwritten_file() {
+    let fixture = Fixture::new();
+    fixture
+        .upload("/a.txt", b"original", UploadOptions::default())
+        .unwrap();
+
+    let overwrite = UploadOptions {
+        mode: WriteMode::Overwrite,
+        ..UploadOptions::default()
+    };
+    let result =
+        fixture
+            .client
+            .upload_stream(&mut &b"short"[..], 100, PathBuf::from("/a.txt"), overwrite);
+
+    assert!(matches!(result, Err(AppError::Io(_))));
+    assert_eq!(fs::read(fixture.root().join("a.txt")).unwrap(), b"original");
+    assert!(!fixture.root().join("a.txt.part").exists());
+}
@@ -0,0 +1,451 @@
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::cloud_client::upload_state::PendingUpload;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::errors::AppError;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+type ErrorFactory = Box<dyn Fn() -> AppError + Send>;
+
+/// Cloud storage keeping entries in memory, with faults injected on demand.
+/// Clones share the storage, so a test can keep one to inspect what the other one,
+/// given to the code under test, has done.
+#[derive(Clone, Default)]
+pub struct MemoryClient {
+    state: Arc<Mutex<MemoryState>>,
+}
+
+#[derive(Default)]
+struct MemoryState {
+    /// Contents of files, `None` for folders, by paths relative to the root
+    entries: BTreeMap<PathBuf, Option<Vec<u8>>>,
+    /// Files reported without size until they are downloaded, like exported Google Docs
+    exported: BTreeSet<PathBuf>,
+    pending_uploads: Vec<PendingUpload>,
+    /// Operations with their paths in the order they were called
+    calls: Vec<String>,
+    faults: Vec<Fault>,
+    /// Delay before every operation, e.g. to test timeouts
+    latency: Option<Duration>,
+}
+
+/// Error returned instead of performing the `call`-th operation, counting from 1.
+/// Only operations named `operation` are counted if it is set.
+struct Fault {
+    operation: Option<&'static str>,
+    call: usize,
+    error: ErrorFactory,
+}
+
+impl MemoryClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MemoryState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds file together with its parent folders
+    pub fn add_file(&self, path: &str, content: &[u8]) {
+        self.state()
+            .insert(normalize(Path::new(path)), Some(content.to_vec()));
+    }
+
+    /// Adds file which is reported with zero size, as its content is produced on download
+    pub fn add_exported_file(&self, path: &str, content: &[u8]) {
+        let path = normalize(Path::new(path));
+        let mut state = self.state();
+        state.insert(path.clone(), Some(content.to_vec()));
+        state.exported.insert(path);
+    }
+
+    pub fn add_folder(&self, path: &str) {
+        self.state().insert(normalize(Path::new(path)), None);
+    }
+
+    pub fn add_pending_upload(&self, upload: PendingUpload) {
+        self.state().pending_uploads.push(upload);
+    }
+
+    /// Returns content of the file, `None` if there is no such file
+    pub fn content(&self, path: &str) -> Option<Vec<u8>> {
+        self.state()
+            .entries
+            .get(&normalize(Path::new(path)))
+            .cloned()
+            .flatten()
+    }
+
+    pub fn exists(&self, path: &str) -> bool {
+        self.state()
+            .entries
+            .contains_key(&normalize(Path::new(path)))
+    }
+
+    /// Returns performed operations with their paths, e.g. `upload /docs/a.txt`
+    pub fn calls(&self) -> Vec<String> {
+        self.state().calls.clone()
+    }
+
+    /// Fails the `call`-th operation of any kind counting from the creation of the storage
+    pub fn fail_call(&self, call: usize, error: impl Fn() -> AppError + Send + 'static) {
+        self.state().faults.push(Fault {
+            operation: None,
+            call,
+            error: Box::new(error),
+        });
+    }
+
+    /// Fails the `call`-th operation named `operation`, e.g. `upload` or `list_entries`
+    pub fn fail_operation(
+        &self,
+        operation: &'static str,
+        call: usize,
+        error: impl Fn() -> AppError + Send + 'static,
+    ) {
+        self.state().faults.push(Fault {
+            operation: Some(operation),
+            call,
+            error: Box::new(error),
+        });
+    }
+
+    /// Delays every following operation
+    pub fn set_latency(&self, latency: Duration) {
+        self.state().latency = Some(latency);
+    }
+
+    /// Records the operation and returns the error injected into it, if any
+    fn enter(&self, operation: &'static str, path: &Path) -> Result<(), AppError> {
+        let latency = self.state().latency;
+        if let Some(latency) = latency {
+            thread::sleep(latency);
+        }
+
+        let mut state = self.state();
+        let call = format!("{operation} {}", path.display());
+        state.calls.push(call.trim_end().to_string());
+        let call = state.calls.len();
+        let operation_call = state
+            .calls
+            .iter()
+            .filter(|called| called.split(' ').next() == Some(operation))
+            .count();
+        match state.faults.iter().find(|fault| match fault.operation {
+            Some(faulty) => faulty == operation && fault.call == operation_call,
+            None => fault.call == call,
+        }) {
+            Some(fault) => Err((fault.error)()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl MemoryState {
+    fn insert(&mut self, path: PathBuf, content: Option<Vec<u8>>) {
+        for parent in path.ancestors().skip(1) {
+            self.entries.entry(parent.to_path_buf()).or_insert(None);
+        }
+        self.entries.insert(path, content);
+    }
+
+    fn get(&self, path: &Path) -> Result<&Option<Vec<u8>>, AppError> {
+        match self.entries.get(path) {
+            Some(content) => Ok(content),
+            // Root folder exists even in the empty storage
+            None if path.as_os_str().is_empty() => Ok(&None),
+            None => Err(AppError::NotFound(display(path))),
+        }
+    }
+
+    fn file(&self, path: &Path) -> Result<&Vec<u8>, AppError> {
+        self.get(path)?
+            .as_ref()
+            .ok_or_else(|| AppError::Request(format!("{} is a folder", display(path))))
+    }
+
+    /// Returns the destination path, renamed if there is a conflict and `autorename` allows that
+    fn destination_path(&self, path: PathBuf, autorename: bool) -> Result<PathBuf, AppError> {
+        let path = free_path(&Path::new("/").join(path), autorename, |candidate| {
+            Ok(self.entries.contains_key(&normalize(candidate)))
+        })?;
+        Ok(normalize(&path))
+    }
+
+    /// Returns the entry and everything inside it, by paths relative to it
+    fn subtree(&self, path: &Path) -> Vec<(PathBuf, Option<Vec<u8>>)> {
+        self.entries
+            .iter()
+            .filter_map(|(entry_path, content)| {
+                let relative = entry_path.strip_prefix(path).ok()?;
+                Some((relative.to_path_buf(), content.clone()))
+            })
+            .collect()
+    }
+
+    fn entry(&self, path: &Path) -> Result<Entry, AppError> {
+        Ok(self.reported_entry(path, self.get(path)?))
+    }
+
+    fn reported_entry(&self, path: &Path, content: &Option<Vec<u8>>) -> Entry {
+        let mut entry = entry(path, content);
+        if self.exported.contains(path) {
+            entry.size = 0;
+        }
+        entry
+    }
+}
+
+impl CloudClient for MemoryClient {
+    fn backend(&self) -> &'static str {
+        "memory"
+    }
+
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        self.enter("download", &from_path)?;
+        let content = self.state().file(&normalize(&from_path))?.clone();
+        fs::write(to_path, content).map_err(AppError::Io)
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        self.enter("open_download", &path)?;
+        let content = self.state().file(&normalize(&path))?.clone();
+        Ok(Box::new(Cursor::new(content)))
+    }
+
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        self.enter("upload", &to_path)?;
+        let content = fs::read(from_path).map_err(AppError::Io)?;
+        self.write(content, to_path, options)?;
+        Ok(())
+    }
+
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        self.enter("upload_stream", &to_path)?;
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(AppError::Io)?;
+        if content.len() as u64 != size {
+            return Err(AppError::Request(format!(
+                "expected {size} bytes, but {} were sent",
+                content.len()
+            )));
+        }
+        self.write(content, to_path, options)
+    }
+
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        self.enter("delete", &path)?;
+        let path = normalize(&path);
+        let mut state = self.state();
+        state.get(&path)?;
+        if path.as_os_str().is_empty() {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+        state
+            .entries
+            .retain(|entry_path, _| !entry_path.starts_with(&path));
+        Ok(())
+    }
+
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        self.enter("create_folder", &path)?;
+        let mut state = self.state();
+        let path = state.destination_path(normalize(&path), autorename)?;
+        state.insert(path, None);
+        Ok(())
+    }
+
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        self.enter("move_entry", &from_path)?;
+        self.relocate(from_path, to_path, options, true)
+    }
+
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        self.enter("copy_entry", &from_path)?;
+        self.relocate(from_path, to_path, options, false)
+    }
+
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        self.enter("metadata", &path)?;
+        self.state().entry(&normalize(&path))
+    }
+
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        self.enter("list_entries", &path)?;
+        let path = normalize(&path);
+        let state = self.state();
+        if state.get(&path)?.is_some() {
+            return Err(AppError::Request(format!("{} is a file", display(&path))));
+        }
+        Ok(state
+            .entries
+            .iter()
+            .filter(|(entry_path, _)| entry_path.parent() == Some(path.as_path()))
+            .map(|(entry_path, content)| state.reported_entry(entry_path, content))
+            .collect())
+    }
+
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        self.enter("pending_uploads", Path::new(""))?;
+        Ok(self.state().pending_uploads.clone())
+    }
+
+    fn abandon_upload(&self, upload: &PendingUpload) -> Result<(), AppError> {
+        self.enter("abandon_upload", &upload.to_path)?;
+        self.state().pending_uploads.retain(|pending| {
+            pending.from_path != upload.from_path || pending.to_path != upload.to_path
+        });
+        Ok(())
+    }
+
+    fn content_hasher(&self) -> Option<Box<dyn ContentHasher>> {
+        Some(Box::<MemoryContentHasher>::default())
+    }
+}
+
+impl MemoryClient {
+    /// Stores uploaded content according to the write mode, where revision is the content hash
+    fn write(
+        &self,
+        content: Vec<u8>,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let to_path = normalize(&to_path);
+        let mut state = self.state();
+        if let Some(None) = state.entries.get(&to_path) {
+            return Err(AppError::Conflict(display(&to_path)));
+        }
+        let to_path = match options.mode {
+            WriteMode::Add => state.destination_path(to_path, options.autorename)?,
+            WriteMode::Overwrite => to_path,
+            WriteMode::Update(revision) => match state.entries.get(&to_path) {
+                Some(Some(existing)) if hash(existing) != revision => {
+                    return Err(AppError::Conflict(display(&to_path)))
+                }
+                _ => to_path,
+            },
+        };
+        state.insert(to_path.clone(), Some(content));
+        state.entry(&to_path)
+    }
+
+    fn relocate(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+        remove_source: bool,
+    ) -> Result<(), AppError> {
+        let (from_path, to_path) = (normalize(&from_path), normalize(&to_path));
+        let mut state = self.state();
+        state.get(&from_path)?;
+        let to_path = state.destination_path(to_path, options.autorename)?;
+        if to_path.starts_with(&from_path) {
+            return Err(AppError::Request(
+                "folder can't be relocated into itself".to_string(),
+            ));
+        }
+
+        let subtree = state.subtree(&from_path);
+        if remove_source {
+            state
+                .entries
+                .retain(|entry_path, _| !entry_path.starts_with(&from_path));
+        }
+        for (relative, content) in subtree {
+            let path = if relative.as_os_str().is_empty() {
+                to_path.clone()
+            } else {
+                to_path.join(relative)
+            };
+            state.insert(path, content);
+        }
+        Ok(())
+    }
+}
+
+/// SHA-256 of the content in hex, used both as the content hash and the revision
+#[derive(Default)]
+pub struct MemoryContentHasher {
+    hasher: Sha256,
+}
+
+impl ContentHasher for MemoryContentHasher {
+    fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        hex::encode(self.hasher.finalize())
+    }
+}
+
+fn hash(content: &[u8]) -> String {
+    let mut hasher = Box::<MemoryContentHasher>::default();
+    hasher.update(content);
+    hasher.finish()
+}
+
+/// Turns cloud path into path relative to the root, which is the empty path
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+fn display(path: &Path) -> String {
+    format!("/{}", path.display())
+}
+
+fn entry(path: &Path, content: &Option<Vec<u8>>) -> Entry {
+    Entry {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        kind: match content {
+            Some(_) => EntryKind::File,
+            None => EntryKind::Folder,
+        },
+        size: content.as_ref().map_or(0, |content| content.len() as u64),
+        modified: None,
+        hash: content.as_deref().map(hash),
+        id: Some(display(path)),
+    }
+}
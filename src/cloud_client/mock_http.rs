@@ -0,0 +1,240 @@
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+
+/// HTTP server on a local port answering every request with the handler it is started with
+pub struct MockHttp {
+    address: SocketAddr,
+    stopped: Arc<AtomicBool>,
+}
+
+/// Request received by the mock server
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    pub method: String,
+    /// Path together with the query, as it is in the request line
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn path(&self) -> &str {
+        self.target
+            .split_once('?')
+            .map_or(self.target.as_str(), |(path, _)| path)
+    }
+
+    /// Returns decoded value of the query parameter
+    pub fn query(&self, name: &str) -> Option<String> {
+        Url::parse(&format!("http://mock{}", self.target))
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.to_string())
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Response of the mock server, `Content-Length` is added when it is sent
+#[derive(Debug, Clone)]
+pub struct Reply {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Reply {
+    pub fn new(status: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn json(status: u16, body: Value) -> Self {
+        Self::new(status, body.to_string()).header("Content-Type", "application/json")
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+impl MockHttp {
+    pub fn start(handler: impl Fn(HttpRequest) -> Reply + Send + Sync + 'static) -> MockHttp {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("mock server binds to a port");
+        let address = listener.local_addr().expect("mock server has an address");
+        let stopped = Arc::new(AtomicBool::new(false));
+        let handler = Arc::new(handler);
+
+        let accepted_stopped = stopped.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if accepted_stopped.load(Ordering::SeqCst) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let handler = handler.clone();
+                // Responses may be read lazily, so connections are served concurrently
+                thread::spawn(move || serve(stream, handler.as_ref()));
+            }
+        });
+
+        MockHttp { address, stopped }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+}
+
+impl Drop for MockHttp {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wakes the accepting thread up, so it notices it has been stopped
+        let _ = TcpStream::connect(self.address);
+    }
+}
+
+/// Mock server answering requests with replies queued by the test, in the order they are queued.
+/// Requests without a queued reply are answered with `501 Not Implemented`.
+pub struct ScriptedHttp {
+    server: MockHttp,
+    script: Arc<Mutex<Script>>,
+}
+
+#[derive(Default)]
+struct Script {
+    replies: VecDeque<(String, String, Reply)>,
+    requests: Vec<HttpRequest>,
+}
+
+impl ScriptedHttp {
+    pub fn start() -> ScriptedHttp {
+        let script = Arc::new(Mutex::new(Script::default()));
+        let handled_script = script.clone();
+        let server = MockHttp::start(move |request| {
+            let mut script = handled_script
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            script.answer(request)
+        });
+        ScriptedHttp { server, script }
+    }
+
+    pub fn url(&self) -> String {
+        self.server.url()
+    }
+
+    fn script(&self) -> MutexGuard<'_, Script> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Queues reply to the next request with `method` to `path`.
+    /// Query is compared as well if `path` has one.
+    pub fn reply(&self, method: &str, path: &str, reply: Reply) {
+        self.script()
+            .replies
+            .push_back((method.to_string(), path.to_string(), reply));
+    }
+
+    /// Returns all received requests in order
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.script().requests.clone()
+    }
+
+    /// Returns whether every queued reply has been sent
+    pub fn is_done(&self) -> bool {
+        self.script().replies.is_empty()
+    }
+}
+
+impl Script {
+    fn answer(&mut self, request: HttpRequest) -> Reply {
+        let position = self.replies.iter().position(|(method, path, _)| {
+            *method == request.method
+                && (*path == request.target || (!path.contains('?') && path == request.path()))
+        });
+        let reply = match position.and_then(|position| self.replies.remove(position)) {
+            Some((_, _, reply)) => reply,
+            None => Reply::new(
+                501,
+                format!("no reply for {} {}", request.method, request.target),
+            ),
+        };
+        self.requests.push(request);
+        reply
+    }
+}
+
+fn serve(mut stream: TcpStream, handler: &(dyn Fn(HttpRequest) -> Reply + Send + Sync)) {
+    let Some(request) = read_request(&stream) else {
+        return;
+    };
+    let reply = handler(request);
+
+    let reason = StatusCode::from_u16(reply.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("");
+    let mut head = format!("HTTP/1.1 {} {reason}\r\n", reply.status);
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        reply.body.len()
+    ));
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&reply.body);
+}
+
+/// Reads request line, headers and body sized with `Content-Length`
+fn read_request(stream: &TcpStream) -> Option<HttpRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?.to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        reader.read_line(&mut line).ok()?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        let (name, value) = header.split_once(':')?;
+        headers.push((name.trim().to_string(), value.trim().to_string()));
+    }
+
+    let length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(HttpRequest {
+        method,
+        target,
+        headers,
+        body,
+    })
+}
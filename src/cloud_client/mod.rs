@@ -1,11 +1,158 @@
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::upload_state::PendingUpload;
 use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
 use std::path::PathBuf;
+use std::str::FromStr;
 
+pub mod authenticator;
+pub mod autorename;
+pub mod content_hash;
 pub mod dropbox;
+pub mod entry;
+pub mod folder_transfer;
+pub mod google_drive;
+pub mod http;
+pub mod local;
+#[cfg(test)]
+pub mod memory;
+#[cfg(test)]
+pub mod mock_http;
+pub mod onedrive;
+pub mod remote_copy;
+pub mod remotes;
+pub mod retry;
+pub mod s3;
+pub mod sftp;
+pub mod upload_state;
+pub mod webdav;
+
+/// How to resolve a conflict with an existing file at the upload destination
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub enum WriteMode {
+    /// Never overwrite the existing file
+    #[default]
+    Add,
+    /// Always overwrite the existing file
+    Overwrite,
+    /// Overwrite the existing file only if it has the specified revision
+    Update(String),
+}
+
+impl FromStr for WriteMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "add" => Ok(WriteMode::Add),
+            "overwrite" => Ok(WriteMode::Overwrite),
+            _ => match value.strip_prefix("update:") {
+                Some(revision) if !revision.is_empty() => {
+                    Ok(WriteMode::Update(revision.to_string()))
+                }
+                _ => Err("expected add, overwrite or update:<rev>".to_string()),
+            },
+        }
+    }
+}
+
+/// Options of uploading files to cloud storage
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct UploadOptions {
+    pub mode: WriteMode,
+    /// Rename the file if there is a conflict at the destination
+    pub autorename: bool,
+    /// Don't notify users about the modification
+    pub mute: bool,
+    /// Treat uploading identical content as a conflict as well
+    pub strict_conflict: bool,
+}
+
+/// Options of moving and copying entries within cloud storage
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RelocationOptions {
+    /// Rename entry if there is a conflict at the destination
+    pub autorename: bool,
+    /// Allow moves which result in ownership transfer of the content
+    pub allow_ownership_transfer: bool,
+}
 
 pub trait CloudClient {
+    /// Name of the storage type, clients of the same backend can copy entries between each other
+    fn backend(&self) -> &'static str;
     fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError>;
-    fn upload(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError>;
+    /// Opens content of the cloud file for reading, without saving it anywhere
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError>;
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError>;
+    /// Uploads `size` bytes read from `reader` and returns the uploaded file
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError>;
     fn delete(&self, path: PathBuf) -> Result<(), AppError>;
-    fn list_entries(&self, path: PathBuf) -> Result<Vec<String>, AppError>;
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError>;
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError>;
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError>;
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError>;
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError>;
+    /// Lists all entries under `path` together with their paths relative to it
+    fn list_entries_recursive(&self, path: PathBuf) -> Result<Vec<(PathBuf, Entry)>, AppError> {
+        let mut entries = Vec::new();
+        let mut folders = vec![PathBuf::new()];
+        while let Some(folder) = folders.pop() {
+            let folder_path = if folder.as_os_str().is_empty() {
+                path.clone()
+            } else {
+                path.join(&folder)
+            };
+            for entry in self.list_entries(folder_path)? {
+                let relative_path = folder.join(&entry.name);
+                if entry.is_folder() {
+                    folders.push(relative_path.clone());
+                }
+                entries.push((relative_path, entry));
+            }
+        }
+        Ok(entries)
+    }
+    /// Returns uploads which were interrupted and can be resumed by uploading the same file again
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError>;
+    /// Forgets interrupted upload, so the next upload of the same file starts from scratch
+    fn abandon_upload(&self, upload: &PendingUpload) -> Result<(), AppError>;
+    /// Returns hasher computing the content hash reported in `Entry::hash` of this storage
+    fn content_hasher(&self) -> Option<Box<dyn ContentHasher>> {
+        None
+    }
+    /// Returns reference to the entry which another client of the same backend can copy
+    /// on the server side, if the backend supports that
+    fn copy_reference(&self, _path: PathBuf) -> Result<Option<String>, AppError> {
+        Ok(None)
+    }
+    /// Copies entry referenced by `copy_reference` of another client of the same backend
+    fn save_copy_reference(&self, _reference: &str, _to_path: PathBuf) -> Result<(), AppError> {
+        Err(AppError::Remote(format!(
+            "{} doesn't support copying by reference",
+            self.backend()
+        )))
+    }
 }
@@ -0,0 +1,88 @@
+use crate::config::OneDriveConfig;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use std::path::{Component, Path};
+
+static DEFAULT_API_URL: &str = "https://graph.microsoft.com/v1.0";
+static DEFAULT_AUTH_URL: &str = "https://login.microsoftonline.com";
+static DEFAULT_TENANT: &str = "common";
+
+/// Characters encoded in path segments of path-based item addresses
+const SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b':')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// Endpoints of Microsoft Graph and of the identity platform.
+/// Base URLs can be overridden in the remote configuration, e.g. to use a mock server.
+#[derive(Debug, Clone)]
+pub struct ApiUrls {
+    api: String,
+    /// OAuth endpoints of the tenant
+    authority: String,
+}
+
+impl ApiUrls {
+    pub fn new(config: &OneDriveConfig) -> Self {
+        let base = |url: &Option<String>, default: &str| {
+            url.as_deref()
+                .unwrap_or(default)
+                .trim_end_matches('/')
+                .to_string()
+        };
+        let tenant = config.tenant.as_deref().unwrap_or(DEFAULT_TENANT);
+        Self {
+            api: base(&config.api_url, DEFAULT_API_URL),
+            authority: format!(
+                "{}/{tenant}/oauth2/v2.0",
+                base(&config.auth_url, DEFAULT_AUTH_URL)
+            ),
+        }
+    }
+
+    pub fn token(&self) -> String {
+        format!("{}/token", self.authority)
+    }
+
+    pub fn device_code(&self) -> String {
+        format!("{}/devicecode", self.authority)
+    }
+
+    /// Addresses the item by its path relative to the drive root, e.g. `/me/drive/root:/a/b.txt:`
+    pub fn item(&self, drive_path: &Path) -> String {
+        match encoded_path(drive_path) {
+            Some(path) => format!("{}/me/drive/root:/{path}:", self.api),
+            None => format!("{}/me/drive/root", self.api),
+        }
+    }
+
+    /// Addresses `action` of the item, e.g. `children` or `content`
+    pub fn item_action(&self, drive_path: &Path, action: &str) -> String {
+        format!("{}/{action}", self.item(drive_path))
+    }
+}
+
+/// Percent-encodes segments of the path, returns `None` for the root
+fn encoded_path(path: &Path) -> Option<String> {
+    let segments: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => {
+                Some(utf8_percent_encode(&name.to_string_lossy(), SEGMENT_ENCODE_SET).to_string())
+            }
+            _ => None,
+        })
+        .collect();
+    (!segments.is_empty()).then(|| segments.join("/"))
+}
@@ -0,0 +1,770 @@
+use crate::cloud_client::authenticator::Authenticator;
+use crate::cloud_client::content_hash::ContentHasher;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::http::{check_response, send_checked, ApiResponses, ErrorResponse};
+use crate::cloud_client::onedrive::api_url::ApiUrls;
+use crate::cloud_client::onedrive::content_hash::QuickXorHasher;
+use crate::cloud_client::onedrive::oauth::StoredCredentials;
+use crate::cloud_client::onedrive::parameters::item::{
+    ConflictBehavior, FolderFacet, ItemParametersBuilder, ParentReference, UploadSessionItem,
+    UploadSessionParameters,
+};
+use crate::cloud_client::onedrive::parameters::token::TokenParameters;
+use crate::cloud_client::onedrive::responses::error::ApiErrorResponse;
+use crate::cloud_client::onedrive::responses::item::{DriveItem, ItemList, ITEM_SELECT};
+use crate::cloud_client::onedrive::responses::operation::{
+    AsyncOperationStatus, UploadSession, UploadSessionResult,
+};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::upload_state::{FileFingerprint, PendingUpload, UploadState};
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::{OneDriveConfig, RemoteConfig};
+use crate::credentials::CredentialStore;
+use crate::errors::{AppError, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR};
+use crate::utilities::files::write_atomically;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::{CONTENT_RANGE, IF_MATCH, LOCATION};
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use tracing::{debug, info, instrument, warn};
+
+static CONFLICT_BEHAVIOR_PARAMETER: &str = "@microsoft.graph.conflictBehavior";
+static PAGE_SIZE: &str = "200";
+const MAX_REDIRECTS: usize = 10;
+
+/// Chunks of upload sessions, except the last one, have to be multiples of 320 KiB
+const UPLOAD_CHUNK_ALIGNMENT: u64 = 320 * 1024;
+const DEFAULT_UPLOAD_CHUNK_SIZE: u64 = 32 * UPLOAD_CHUNK_ALIGNMENT;
+/// Upload sessions don't accept chunks larger than 60 MiB
+const MAX_UPLOAD_CHUNK_SIZE: u64 = 192 * UPLOAD_CHUNK_ALIGNMENT;
+/// Simple upload is documented to accept files up to 4 MiB
+const DEFAULT_UPLOAD_SESSION_THRESHOLD: u64 = 4 * 1024 * 1024;
+const COPY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Client of the OneDrive of the signed-in user, accessed with Microsoft Graph.
+/// Items are addressed by their paths, so no ids have to be resolved.
+#[derive(Debug)]
+pub struct OneDriveClient {
+    client: Client,
+    urls: ApiUrls,
+    authenticator: Authenticator<TokenParameters>,
+    /// Files larger than this are uploaded with upload sessions
+    upload_session_threshold: u64,
+    /// Size of a single upload session request
+    upload_chunk_size: u64,
+    /// Keeps track of upload sessions, so they can be resumed after restart
+    upload_state: UploadState,
+    /// Decides which failed requests are repeated and how
+    retry_policy: RetryPolicy,
+    /// Drive folder all paths are relative to, the whole drive if absent
+    root: Option<PathBuf>,
+}
+
+impl OneDriveClient {
+    /// Builds client of the remote named `name`
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &OneDriveConfig,
+        credential_store: &dyn CredentialStore,
+    ) -> Result<OneDriveClient, AppError> {
+        let client = ClientBuilder::new()
+            .timeout(None)
+            .redirect(Policy::custom(|attempt| {
+                // Monitor of completed copy redirects to the copy, which requires access token
+                if attempt.status() == StatusCode::SEE_OTHER {
+                    attempt.stop()
+                } else if attempt.previous().len() >= MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .map_err(|_| AppError::PrepareClient(BUILD_REQUEST_CLIENT_ERROR.to_string()))?;
+        let urls = ApiUrls::new(config);
+        let refresh_parameters =
+            StoredCredentials::load(credential_store, remote.credential_key(name))?
+                .map(|credentials| credentials.refresh_parameters())
+                .transpose()?;
+        let authenticator = Authenticator::build(
+            client.clone(),
+            urls.token(),
+            refresh_parameters,
+            "ONEDRIVE_ACCESS_TOKEN",
+        )?;
+
+        let upload_chunk_size = config
+            .upload_chunk_size
+            .map_or(DEFAULT_UPLOAD_CHUNK_SIZE, |size| size.get())
+            .min(MAX_UPLOAD_CHUNK_SIZE)
+            / UPLOAD_CHUNK_ALIGNMENT
+            * UPLOAD_CHUNK_ALIGNMENT;
+
+        Ok(Self {
+            client,
+            urls,
+            authenticator,
+            upload_session_threshold: config
+                .upload_session_threshold
+                .map_or(DEFAULT_UPLOAD_SESSION_THRESHOLD, |threshold| {
+                    threshold.get()
+                }),
+            upload_chunk_size: upload_chunk_size.max(UPLOAD_CHUNK_ALIGNMENT),
+            upload_state: UploadState::from_env(name),
+            retry_policy: remote.retry.policy()?,
+            root: remote.root.clone(),
+        })
+    }
+
+    /// Resolves path of the remote into path relative to the drive root
+    fn drive_path(&self, path: &Path) -> PathBuf {
+        self.root
+            .iter()
+            .flat_map(|root| root.components())
+            .chain(path.components())
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .collect()
+    }
+
+    /// Sends request built by `request` with access token, following the retry policy
+    fn send_authorized(
+        &self,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, AppError> {
+        self.authenticator
+            .send::<GraphApi>(&self.retry_policy, idempotent, request)
+    }
+
+    /// Sends request to pre-authenticated URL, e.g. of upload session, which must not
+    /// receive the access token
+    fn send_anonymous(
+        &self,
+        idempotent: bool,
+        request: impl Fn() -> RequestBuilder,
+    ) -> Result<Response, AppError> {
+        self.retry_policy
+            .run(idempotent, || send_checked::<GraphApi>(request()))
+    }
+
+    fn get_item(&self, drive_path: &Path) -> Result<DriveItem, AppError> {
+        self.send_authorized(true, || {
+            self.client
+                .get(self.urls.item(drive_path))
+                .query(&[("$select", ITEM_SELECT)])
+        })?
+        .json::<DriveItem>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Lists children of the folder, following `@odata.nextLink` page by page
+    fn list_children(&self, drive_path: &Path) -> Result<Vec<DriveItem>, AppError> {
+        let mut items = Vec::new();
+        let mut next_link: Option<String> = None;
+        loop {
+            let page = self
+                .send_authorized(true, || match &next_link {
+                    // Next link already has all the query parameters
+                    Some(link) => self.client.get(link),
+                    None => self
+                        .client
+                        .get(self.urls.item_action(drive_path, "children"))
+                        .query(&[("$select", ITEM_SELECT), ("$top", PAGE_SIZE)]),
+                })?
+                .json::<ItemList>()
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+            items.extend(page.value);
+            match page.next_link {
+                Some(link) => next_link = Some(link),
+                None => return Ok(items),
+            }
+        }
+    }
+
+    /// Creates folder called `name` in the folder at `parent_path`
+    fn create_child_folder(
+        &self,
+        parent_path: &Path,
+        name: &str,
+        conflict_behavior: ConflictBehavior,
+    ) -> Result<DriveItem, AppError> {
+        let parameters = ItemParametersBuilder::default()
+            .name(Some(name.to_string()))
+            .folder(Some(FolderFacet::default()))
+            .conflict_behavior(Some(conflict_behavior))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        self.send_authorized(false, || {
+            self.client
+                .post(self.urls.item_action(parent_path, "children"))
+                .query(&[("$select", ITEM_SELECT)])
+                .json(&parameters)
+        })?
+        .json::<DriveItem>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Returns the folder at path relative to the drive root, creating absent folders
+    fn ensure_folder(&self, drive_path: &Path) -> Result<DriveItem, AppError> {
+        let error = match self.get_item(drive_path) {
+            Ok(item) if item.is_folder() => return Ok(item),
+            Ok(_) => {
+                return Err(AppError::Conflict(format!(
+                    "{} is not a folder",
+                    drive_path.display()
+                )))
+            }
+            Err(error @ AppError::NotFound(_)) => error,
+            Err(error) => return Err(error),
+        };
+        let (Some(parent), Some(name)) = (drive_path.parent(), drive_path.file_name()) else {
+            return Err(error);
+        };
+
+        self.ensure_folder(parent)?;
+        match self.create_child_folder(parent, &name.to_string_lossy(), ConflictBehavior::Fail) {
+            // Folder has been created in the meantime
+            Err(AppError::Conflict(_)) => self.get_item(drive_path),
+            result => result,
+        }
+    }
+
+    /// Returns the folder the entry at `drive_path` is placed into
+    fn ensure_parent(&self, drive_path: &Path) -> Result<DriveItem, AppError> {
+        self.ensure_folder(drive_path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// Decides how the upload treats the existing file according to the write mode.
+    /// Returns the conflict behavior and `eTag` the existing file must still have.
+    fn write_conditions(
+        &self,
+        drive_path: &Path,
+        options: &UploadOptions,
+    ) -> Result<(ConflictBehavior, Option<String>), AppError> {
+        match &options.mode {
+            WriteMode::Add => Ok((ConflictBehavior::from_autorename(options.autorename), None)),
+            WriteMode::Overwrite => Ok((ConflictBehavior::Replace, None)),
+            // Revision is `quickXorHash` shown in listings, while `eTag` guards against
+            // changes made after it has been compared
+            WriteMode::Update(revision) => {
+                let item = self.get_item(drive_path)?;
+                if item.quick_xor_hash() != Some(revision.as_str()) {
+                    return Err(AppError::Conflict(format!(
+                        "{} has changed since revision {revision}",
+                        drive_path.display()
+                    )));
+                }
+                Ok((ConflictBehavior::Replace, item.e_tag))
+            }
+        }
+    }
+
+    /// Uploads content in a single request, suitable for small files only
+    fn simple_upload(
+        &self,
+        content: Vec<u8>,
+        drive_path: &Path,
+        options: &UploadOptions,
+    ) -> Result<DriveItem, AppError> {
+        let (conflict_behavior, e_tag) = self.write_conditions(drive_path, options)?;
+        self.ensure_parent(drive_path)?;
+
+        // Repeated upload replaces the same file, while others may conflict with the first one
+        let idempotent = conflict_behavior == ConflictBehavior::Replace;
+        let item = self
+            .send_authorized(idempotent, || {
+                let request = self
+                    .client
+                    .put(self.urls.item_action(drive_path, "content"))
+                    .query(&[
+                        (CONFLICT_BEHAVIOR_PARAMETER, conflict_behavior.as_str()),
+                        ("$select", ITEM_SELECT),
+                    ])
+                    .body(content.clone());
+                match &e_tag {
+                    Some(e_tag) => request.header(IF_MATCH, e_tag),
+                    None => request,
+                }
+            })?
+            .json::<DriveItem>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+        info!("File has been uploaded");
+        Ok(item)
+    }
+
+    /// Creates upload session writing to `drive_path` and returns its URL
+    fn create_upload_session(
+        &self,
+        drive_path: &Path,
+        options: &UploadOptions,
+    ) -> Result<String, AppError> {
+        let (conflict_behavior, e_tag) = self.write_conditions(drive_path, options)?;
+        self.ensure_parent(drive_path)?;
+
+        info!("Starting upload session...");
+        let parameters = UploadSessionParameters {
+            item: UploadSessionItem { conflict_behavior },
+        };
+        let session = self
+            .send_authorized(true, || {
+                let request = self
+                    .client
+                    .post(self.urls.item_action(drive_path, "createUploadSession"))
+                    .json(&parameters);
+                match &e_tag {
+                    Some(e_tag) => request.header(IF_MATCH, e_tag),
+                    None => request,
+                }
+            })?
+            .json::<UploadSession>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+        session
+            .upload_url
+            .ok_or_else(|| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Sends `bytes` starting at `offset` of `size` bytes to the upload session
+    fn send_chunk(
+        &self,
+        session_url: &str,
+        bytes: &[u8],
+        offset: u64,
+        size: u64,
+    ) -> Result<UploadSessionResult, AppError> {
+        let content_range = format!("bytes {offset}-{}/{size}", offset + bytes.len() as u64 - 1);
+        self.send_anonymous(true, || {
+            self.client
+                .put(session_url)
+                .header(CONTENT_RANGE, content_range.as_str())
+                .body(bytes.to_vec())
+        })?
+        .json::<UploadSessionResult>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))
+    }
+
+    /// Sends content read from `reader` to the upload session starting from `offset`,
+    /// until all `size` bytes are received. Bytes the server didn't keep are sent again.
+    /// `on_chunk` is called with the new offset after every accepted chunk.
+    fn send_chunks(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        session_url: &str,
+        mut offset: u64,
+        mut on_chunk: impl FnMut(u64) -> Result<(), AppError>,
+    ) -> Result<DriveItem, AppError> {
+        let mut chunk = Vec::with_capacity(self.upload_chunk_size as usize);
+        loop {
+            let missing = self.upload_chunk_size - chunk.len() as u64;
+            reader
+                .take(missing)
+                .read_to_end(&mut chunk)
+                .map_err(AppError::Io)?;
+            if chunk.is_empty() {
+                return Err(if offset < size {
+                    AppError::Io(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        format!("expected {size} bytes, but {offset} were read"),
+                    ))
+                } else {
+                    AppError::Response("upload session expects more bytes".to_string())
+                });
+            }
+
+            match self.send_chunk(session_url, &chunk, offset, size)? {
+                UploadSessionResult::Complete(item) => {
+                    info!("File has been uploaded");
+                    return Ok(item);
+                }
+                UploadSessionResult::Incomplete(session) => {
+                    let received = session.next_offset().unwrap_or(offset + chunk.len() as u64);
+                    let kept = received.saturating_sub(offset).min(chunk.len() as u64);
+                    chunk.drain(..kept as usize);
+                    offset += kept;
+                    on_chunk(offset)?;
+                    info!("Uploaded {offset} of {size} bytes");
+                }
+            }
+        }
+    }
+
+    /// Uploads local file with upload session, recording its progress in the upload state,
+    /// so an interrupted upload of unchanged file continues from the next expected byte
+    #[instrument(name = "OneDrive upload session", skip(self))]
+    fn upload_in_session(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let from_path = fs::canonicalize(from_path).map_err(AppError::Io)?;
+        let fingerprint = FileFingerprint::of(&from_path)?;
+
+        if let Some(upload) = self.upload_state.find(&from_path, &to_path)? {
+            if upload.fingerprint == fingerprint {
+                info!("Resuming upload...");
+                match self.resume_upload(upload) {
+                    // Session has expired
+                    Err(error @ AppError::NotFound(_)) => {
+                        info!("Unable to resume upload ({error}), restarting upload")
+                    }
+                    result => return result,
+                }
+            } else {
+                info!("Local file has changed since the last attempt, restarting upload");
+            }
+        }
+
+        let session_url = self.create_upload_session(&self.drive_path(&to_path), &options)?;
+        let upload = PendingUpload {
+            remote: self.upload_state.remote().to_string(),
+            from_path,
+            to_path,
+            session_id: session_url,
+            offset: 0,
+            fingerprint,
+            options,
+        };
+        self.upload_state.save(&upload)?;
+        self.continue_upload(upload)
+    }
+
+    /// Asks the session which bytes it expects and continues from there
+    fn resume_upload(&self, mut upload: PendingUpload) -> Result<(), AppError> {
+        let session = self
+            .send_anonymous(true, || self.client.get(&upload.session_id))?
+            .json::<UploadSession>()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+        let offset = session.next_offset().unwrap_or(upload.offset);
+        info!("Resuming upload from {offset} bytes...");
+        upload.offset = offset;
+        self.continue_upload(upload)
+    }
+
+    fn continue_upload(&self, mut upload: PendingUpload) -> Result<(), AppError> {
+        let mut file = File::open(&upload.from_path).map_err(AppError::Io)?;
+        file.seek(SeekFrom::Start(upload.offset))
+            .map_err(AppError::Io)?;
+
+        let session_url = upload.session_id.clone();
+        self.send_chunks(
+            &mut file,
+            upload.fingerprint.size(),
+            &session_url,
+            upload.offset,
+            |offset| {
+                upload.offset = offset;
+                self.upload_state.save(&upload)
+            },
+        )?;
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    /// Requests content of the file, which is redirected to pre-authenticated download URL
+    fn send_download_request(&self, drive_path: &Path) -> Result<Response, AppError> {
+        self.send_authorized(true, || {
+            self.client
+                .get(self.urls.item_action(drive_path, "content"))
+        })
+    }
+
+    /// Polls the monitor of long-running action until it completes
+    fn wait_for_operation(&self, monitor_url: &str) -> Result<(), AppError> {
+        loop {
+            let response = self.retry_policy.run(true, || {
+                let response = self
+                    .client
+                    .get(monitor_url)
+                    .send()
+                    .map_err(|error| AppError::SendRequest(error.to_string()))?;
+                if response.status() == StatusCode::SEE_OTHER {
+                    Ok(response)
+                } else {
+                    check_response::<GraphApi>(response)
+                }
+            })?;
+            // Completed action redirects to the resulting item
+            if response.status() == StatusCode::SEE_OTHER {
+                return Ok(());
+            }
+
+            let status = response
+                .json::<AsyncOperationStatus>()
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+            match status.status.as_str() {
+                "completed" => return Ok(()),
+                "failed" | "cancelled" => {
+                    let message = status
+                        .error
+                        .map(|error| format!("{}: {}", error.code, error.message))
+                        .unwrap_or(status.status);
+                    return Err(AppError::Api(message));
+                }
+                _ => {
+                    if let Some(percentage) = status.percentage_complete {
+                        info!("Completed {percentage:.0}%");
+                    }
+                    thread::sleep(COPY_POLL_INTERVAL);
+                }
+            }
+        }
+    }
+}
+
+impl CloudClient for OneDriveClient {
+    fn backend(&self) -> &'static str {
+        "onedrive"
+    }
+
+    #[instrument(name = "OneDrive download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        info!("Downloading...");
+        let mut response = self.send_download_request(&self.drive_path(&from_path))?;
+        let written = write_atomically(&mut response, &to_path).map_err(AppError::Io)?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        Ok(Box::new(
+            self.send_download_request(&self.drive_path(&path))?,
+        ))
+    }
+
+    /// Files up to the upload session threshold are uploaded in a single request
+    #[instrument(name = "OneDrive upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let size = fs::metadata(&from_path).map_err(AppError::Io)?.len();
+        if size > self.upload_session_threshold {
+            return self.upload_in_session(from_path, to_path, options);
+        }
+
+        info!("Uploading...");
+        let content = fs::read(&from_path).map_err(AppError::Io)?;
+        self.simple_upload(content, &self.drive_path(&to_path), &options)?;
+        Ok(())
+    }
+
+    /// Large content is sent with upload session as well, but unlike uploads of local files
+    /// such uploads can't be resumed after restart
+    #[instrument(name = "OneDrive upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let drive_path = self.drive_path(&to_path);
+        if size > self.upload_session_threshold {
+            let session_url = self.create_upload_session(&drive_path, &options)?;
+            let item = self.send_chunks(reader, size, &session_url, 0, |_| Ok(()))?;
+            return Ok(item.into());
+        }
+
+        let mut content = Vec::with_capacity(size as usize);
+        reader
+            .take(size)
+            .read_to_end(&mut content)
+            .map_err(AppError::Io)?;
+        if content.len() as u64 != size {
+            return Err(AppError::Io(io::Error::new(
+                ErrorKind::UnexpectedEof,
+                format!("expected {size} bytes, but {} were read", content.len()),
+            )));
+        }
+        Ok(self.simple_upload(content, &drive_path, &options)?.into())
+    }
+
+    /// Items are moved to the recycle bin, so they can be restored
+    #[instrument(name = "OneDrive delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        let drive_path = self.drive_path(&path);
+        if drive_path.as_os_str().is_empty() {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+
+        info!("Deleting...");
+        self.send_authorized(true, || self.client.delete(self.urls.item(&drive_path)))?;
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "OneDrive create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        info!("Creating folder...");
+        let drive_path = self.drive_path(&path);
+        let Some(name) = drive_path.file_name() else {
+            return Err(AppError::Conflict("root folder already exists".to_string()));
+        };
+        let parent = drive_path.parent().unwrap_or(Path::new(""));
+        self.ensure_folder(parent)?;
+        self.create_child_folder(
+            parent,
+            &name.to_string_lossy(),
+            ConflictBehavior::from_autorename(autorename),
+        )?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "OneDrive move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Moving...");
+        let destination_path = self.drive_path(&to_path);
+        let parent = self.ensure_parent(&destination_path)?;
+        let parameters = ItemParametersBuilder::default()
+            .name(Some(file_name(&destination_path)))
+            .parent_reference(Some(ParentReference {
+                drive_id: None,
+                id: parent.id,
+            }))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        let conflict_behavior = ConflictBehavior::from_autorename(options.autorename);
+
+        self.send_authorized(false, || {
+            self.client
+                .patch(self.urls.item(&self.drive_path(&from_path)))
+                .query(&[(CONFLICT_BEHAVIOR_PARAMETER, conflict_behavior.as_str())])
+                .json(&parameters)
+        })?;
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    /// Copying runs on the server in the background, its monitor is polled until it completes
+    #[instrument(name = "OneDrive copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Copying...");
+        let destination_path = self.drive_path(&to_path);
+        let parent = self.ensure_parent(&destination_path)?;
+        let parameters = ItemParametersBuilder::default()
+            .name(Some(file_name(&destination_path)))
+            .parent_reference(Some(ParentReference {
+                drive_id: parent
+                    .parent_reference
+                    .and_then(|reference| reference.drive_id),
+                id: parent.id,
+            }))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)?;
+        let conflict_behavior = ConflictBehavior::from_autorename(options.autorename);
+
+        let response = self.send_authorized(false, || {
+            self.client
+                .post(self.urls.item_action(&self.drive_path(&from_path), "copy"))
+                .query(&[(CONFLICT_BEHAVIOR_PARAMETER, conflict_behavior.as_str())])
+                .json(&parameters)
+        })?;
+        let monitor_url = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        self.wait_for_operation(monitor_url)?;
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        Ok(self.get_item(&self.drive_path(&path))?.into())
+    }
+
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        info!("Listing entries...");
+        let entries: Vec<Entry> = self
+            .list_children(&self.drive_path(&path))?
+            .into_iter()
+            .map(Entry::from)
+            .collect();
+
+        debug!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        self.upload_state.load()
+    }
+
+    /// Upload session is cancelled, so the uploaded chunks don't stay on the server
+    fn abandon_upload(&self, upload: &PendingUpload) -> Result<(), AppError> {
+        if let Err(error) = self.send_anonymous(true, || self.client.delete(&upload.session_id)) {
+            warn!("Unable to cancel upload session: {error}");
+        }
+        self.upload_state.remove(&upload.from_path, &upload.to_path)
+    }
+
+    fn content_hasher(&self) -> Option<Box<dyn ContentHasher>> {
+        Some(Box::<QuickXorHasher>::default())
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Responses of Microsoft Graph API
+struct GraphApi;
+
+impl ApiResponses for GraphApi {
+    fn decode_error(response: ErrorResponse) -> AppError {
+        let ErrorResponse {
+            status,
+            retry_after,
+            body,
+            ..
+        } = response;
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited { retry_after },
+            StatusCode::INSUFFICIENT_STORAGE => AppError::InsufficientSpace,
+            // Graph asks to slow down with 503 and `Retry-After` as well
+            StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {
+                AppError::RateLimited { retry_after }
+            }
+            status if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message: body,
+            },
+            status => match serde_json::from_str::<ApiErrorResponse>(&body) {
+                Ok(error) => error.into_app_error(status, retry_after),
+                Err(_) if status == StatusCode::NOT_FOUND => AppError::NotFound(body),
+                Err(_) => AppError::Request(format!("{status}: {body}")),
+            },
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::cloud_client::content_hash::ContentHasher;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+/// Width of the hash in bits, kept in two 64-bit cells and one 32-bit cell
+const WIDTH_IN_BITS: usize = 160;
+/// Every next byte is XORed into the hash this many bits further, wrapping around
+const SHIFT: usize = 11;
+
+/// Computes `quickXorHash` of OneDrive files
+/// More details [here](https://learn.microsoft.com/onedrive/developer/code-snippets/quickxorhash)
+#[derive(Default)]
+pub struct QuickXorHasher {
+    cells: [u64; 3],
+    length: u64,
+    /// Bit position the next byte is XORed at
+    shift: usize,
+}
+
+impl QuickXorHasher {
+    fn xor_byte(&mut self, byte: u8, position: usize) {
+        let cell = position / 64;
+        let offset = position % 64;
+        let cell_bits = if cell == self.cells.len() - 1 {
+            WIDTH_IN_BITS - 128
+        } else {
+            64
+        };
+        self.cells[cell] ^= u64::from(byte) << offset;
+        // Bits which don't fit into the cell continue in the next one
+        if offset + 8 > cell_bits {
+            let next = (cell + 1) % self.cells.len();
+            self.cells[next] ^= u64::from(byte) >> (cell_bits - offset);
+        }
+    }
+}
+
+impl ContentHasher for QuickXorHasher {
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.xor_byte(byte, self.shift);
+            self.shift = (self.shift + SHIFT) % WIDTH_IN_BITS;
+        }
+        self.length += data.len() as u64;
+    }
+
+    fn finish(self: Box<Self>) -> String {
+        let mut hash = [0u8; WIDTH_IN_BITS / 8];
+        hash[..8].copy_from_slice(&self.cells[0].to_le_bytes());
+        hash[8..16].copy_from_slice(&self.cells[1].to_le_bytes());
+        hash[16..].copy_from_slice(&self.cells[2].to_le_bytes()[..4]);
+        // Length is XORed into the last 8 bytes
+        for (byte, length_byte) in hash[12..].iter_mut().zip(self.length.to_le_bytes()) {
+            *byte ^= length_byte;
+        }
+        STANDARD.encode(hash)
+    }
+}
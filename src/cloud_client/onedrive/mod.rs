@@ -0,0 +1,8 @@
+pub mod api_url;
+pub mod client;
+pub mod content_hash;
+pub mod oauth;
+pub mod parameters;
+pub mod responses;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,162 @@
+use crate::cloud_client::authenticator::{request_token, TokenRequestError, TokenResult};
+use crate::cloud_client::onedrive::api_url::ApiUrls;
+use crate::cloud_client::onedrive::parameters::token::{
+    DeviceCodeParameters, GrantType, TokenParameters, TokenParametersBuilder,
+};
+use crate::cloud_client::onedrive::responses::token::DeviceCodeResult;
+use crate::config::OneDriveConfig;
+use crate::credentials::CredentialStore;
+use crate::errors::{AppError, RESPONSE_BODY_ERROR};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Access to files of the user, with refresh token to keep it
+static SCOPE: &str = "offline_access Files.ReadWrite.All";
+/// Seconds between token requests if the server doesn't tell
+const DEFAULT_POLL_INTERVAL: u64 = 5;
+/// Seconds added to the interval when the server asks to slow down
+const SLOW_DOWN_INCREMENT: u64 = 5;
+
+/// Long-lived credentials received after login
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StoredCredentials {
+    pub client_id: String,
+    pub refresh_token: String,
+}
+
+impl StoredCredentials {
+    pub fn load(
+        store: &dyn CredentialStore,
+        key: &str,
+    ) -> Result<Option<StoredCredentials>, AppError> {
+        store
+            .load(key)?
+            .map(|secret| {
+                serde_json::from_str(&secret)
+                    .map_err(|error| AppError::Credentials(error.to_string()))
+            })
+            .transpose()
+    }
+
+    pub fn save(&self, store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+        let secret = serde_json::to_string(self)
+            .map_err(|error| AppError::Credentials(error.to_string()))?;
+        store.save(key, &secret)
+    }
+
+    /// Parameters of the request receiving new access token with the refresh token
+    pub fn refresh_parameters(&self) -> Result<TokenParameters, AppError> {
+        TokenParametersBuilder::default()
+            .grant_type(GrantType::RefreshToken)
+            .client_id(self.client_id.clone())
+            .scope(Some(SCOPE.to_string()))
+            .refresh_token(Some(self.refresh_token.clone()))
+            .build()
+            .map_err(|_| AppError::PrepareRequestParameters)
+    }
+}
+
+/// Runs OAuth2 device authorization flow: prints the code to be entered on the verification
+/// page, which works without browser on this machine, and waits for the user to allow access.
+/// The refresh token is stored under `key`.
+pub fn login(
+    store: &dyn CredentialStore,
+    key: &str,
+    config: &OneDriveConfig,
+) -> Result<(), AppError> {
+    let client_id = match &config.client_id {
+        Some(client_id) => client_id.clone(),
+        None => std::env::var("ONEDRIVE_CLIENT_ID")
+            .map_err(|_| AppError::Config("OneDrive client id is absent".to_string()))?,
+    };
+    let client = Client::new();
+    let urls = ApiUrls::new(config);
+
+    let response = client
+        .post(urls.device_code())
+        .form(&DeviceCodeParameters {
+            client_id: client_id.clone(),
+            scope: SCOPE.to_string(),
+        })
+        .send()
+        .map_err(|error| AppError::SendRequest(error.to_string()))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        return Err(AppError::Authorization(format!("{status}: {body}")));
+    }
+    let device_code = response
+        .json::<DeviceCodeResult>()
+        .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+    match &device_code.message {
+        Some(message) => println!("{message}\n"),
+        None => println!(
+            "Open {} in your browser and enter the code {}\n",
+            device_code.verification_uri, device_code.user_code
+        ),
+    }
+
+    let token = poll_token(&client, &urls, &client_id, &device_code)?;
+    let refresh_token = token
+        .refresh_token
+        .ok_or_else(|| AppError::Authorization("refresh token is absent".to_string()))?;
+    StoredCredentials {
+        client_id,
+        refresh_token,
+    }
+    .save(store, key)?;
+
+    println!("Logged in successfully");
+    Ok(())
+}
+
+/// Requests token with the device code until the user allows or denies access,
+/// or the code expires
+fn poll_token(
+    client: &Client,
+    urls: &ApiUrls,
+    client_id: &str,
+    device_code: &DeviceCodeResult,
+) -> Result<TokenResult, AppError> {
+    let parameters = TokenParametersBuilder::default()
+        .grant_type(GrantType::DeviceCode)
+        .client_id(client_id.to_string())
+        .device_code(Some(device_code.device_code.clone()))
+        .build()
+        .map_err(|_| AppError::PrepareRequestParameters)?;
+    let expires_at = Instant::now() + Duration::from_secs(device_code.expires_in);
+    let mut interval = device_code.interval.unwrap_or(DEFAULT_POLL_INTERVAL);
+
+    println!("Waiting for access to be allowed...");
+    loop {
+        thread::sleep(Duration::from_secs(interval));
+        match request_token(client, &urls.token(), &parameters) {
+            Err(TokenRequestError::Rejected(error)) if error.error == "authorization_pending" => {}
+            Err(TokenRequestError::Rejected(error)) if error.error == "slow_down" => {
+                interval += SLOW_DOWN_INCREMENT
+            }
+            Err(error) => return Err(error.into()),
+            Ok(token) => return Ok(token),
+        }
+        if Instant::now() >= expires_at {
+            return Err(AppError::Authorization(
+                "device code has expired".to_string(),
+            ));
+        }
+    }
+}
+
+/// Microsoft doesn't allow public clients to revoke refresh tokens,
+/// so they are just deleted from the store
+pub fn logout(store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+    if StoredCredentials::load(store, key)?.is_none() {
+        println!("There are no stored credentials");
+        return Ok(());
+    }
+    store.delete(key)?;
+    println!("Logged out successfully");
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+/// What happens when an item with the same name already exists at the destination
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictBehavior {
+    Fail,
+    Replace,
+    Rename,
+}
+
+impl ConflictBehavior {
+    pub fn from_autorename(autorename: bool) -> Self {
+        if autorename {
+            ConflictBehavior::Rename
+        } else {
+            ConflictBehavior::Fail
+        }
+    }
+
+    /// Value of `@microsoft.graph.conflictBehavior` query parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictBehavior::Fail => "fail",
+            ConflictBehavior::Replace => "replace",
+            ConflictBehavior::Rename => "rename",
+        }
+    }
+}
+
+/// Reference to the parent folder of moved and copied items
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParentReference {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drive_id: Option<String>,
+    pub id: String,
+}
+
+/// Empty facet marking the created item as a folder
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct FolderFacet {}
+
+/// Body of requests creating folders, moving and copying items
+#[derive(Serialize, Deserialize, Builder, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemParameters {
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    folder: Option<FolderFacet>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent_reference: Option<ParentReference>,
+    #[builder(default)]
+    #[serde(
+        rename = "@microsoft.graph.conflictBehavior",
+        skip_serializing_if = "Option::is_none"
+    )]
+    conflict_behavior: Option<ConflictBehavior>,
+}
+
+/// Body of request creating upload session
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadSessionParameters {
+    pub item: UploadSessionItem,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UploadSessionItem {
+    #[serde(rename = "@microsoft.graph.conflictBehavior")]
+    pub conflict_behavior: ConflictBehavior,
+}
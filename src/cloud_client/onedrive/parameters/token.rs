@@ -0,0 +1,33 @@
+use derive_builder::Builder;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub enum GrantType {
+    #[serde(rename = "urn:ietf:params:oauth:grant-type:device_code")]
+    DeviceCode,
+    #[serde(rename = "refresh_token")]
+    RefreshToken,
+}
+
+/// Form parameters of OAuth token request
+#[derive(Serialize, Deserialize, Builder)]
+pub struct TokenParameters {
+    grant_type: GrantType,
+    client_id: String,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    device_code: Option<String>,
+    #[builder(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    refresh_token: Option<String>,
+}
+
+/// Form parameters of device authorization request
+#[derive(Serialize, Deserialize)]
+pub struct DeviceCodeParameters {
+    pub client_id: String,
+    pub scope: String,
+}
@@ -0,0 +1,36 @@
+use crate::errors::AppError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Body of error response
+/// More details [here](https://learn.microsoft.com/graph/errors)
+#[derive(Deserialize, Debug)]
+pub struct ApiErrorResponse {
+    pub error: ApiError,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApiError {
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl ApiErrorResponse {
+    pub fn into_app_error(self, status: StatusCode, retry_after: Option<u64>) -> AppError {
+        let code = self.error.code.as_str();
+        let message = self.error.message.trim_end_matches('.').to_string();
+
+        match (status, code) {
+            (StatusCode::UNAUTHORIZED, _) => AppError::ExpiredAccessToken,
+            (_, "activityLimitReached") => AppError::RateLimited { retry_after },
+            (StatusCode::NOT_FOUND, _) | (_, "itemNotFound") => AppError::NotFound(message),
+            (_, "quotaLimitReached") => AppError::InsufficientSpace,
+            (StatusCode::FORBIDDEN, _) | (_, "accessDenied") => AppError::Unauthorized(message),
+            (StatusCode::CONFLICT | StatusCode::PRECONDITION_FAILED, _)
+            | (_, "nameAlreadyExists" | "resourceModified") => AppError::Conflict(message),
+            _ => AppError::Api(message),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::cloud_client::entry::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+/// Properties of items requested from the API, the rest of them are not returned
+pub static ITEM_SELECT: &str = "id,name,size,lastModifiedDateTime,eTag,file,folder,parentReference";
+
+/// File or folder of the drive
+/// More details [here](https://learn.microsoft.com/graph/api/resources/driveitem)
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveItem {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    pub last_modified_date_time: Option<DateTime<Utc>>,
+    pub e_tag: Option<String>,
+    pub file: Option<FileFacet>,
+    pub folder: Option<FolderFacet>,
+    pub parent_reference: Option<ItemReference>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileFacet {
+    pub hashes: Option<Hashes>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Hashes {
+    /// Hash available on both personal and business drives
+    pub quick_xor_hash: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderFacet {
+    #[serde(default)]
+    pub child_count: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemReference {
+    pub id: Option<String>,
+    pub drive_id: Option<String>,
+}
+
+impl DriveItem {
+    pub fn is_folder(&self) -> bool {
+        self.folder.is_some()
+    }
+
+    pub fn quick_xor_hash(&self) -> Option<&str> {
+        self.file
+            .as_ref()?
+            .hashes
+            .as_ref()?
+            .quick_xor_hash
+            .as_deref()
+    }
+}
+
+impl From<DriveItem> for Entry {
+    fn from(item: DriveItem) -> Self {
+        let is_folder = item.is_folder();
+        Entry {
+            hash: item.quick_xor_hash().map(str::to_string),
+            name: item.name,
+            kind: if is_folder {
+                EntryKind::Folder
+            } else {
+                EntryKind::File
+            },
+            size: if is_folder { 0 } else { item.size },
+            modified: item.last_modified_date_time,
+            id: Some(item.id),
+        }
+    }
+}
+
+/// Page of `children` response, the next page is requested with `@odata.nextLink` as is
+#[derive(Deserialize, Debug)]
+pub struct ItemList {
+    #[serde(default)]
+    pub value: Vec<DriveItem>,
+    #[serde(rename = "@odata.nextLink")]
+    pub next_link: Option<String>,
+}
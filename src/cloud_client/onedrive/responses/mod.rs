@@ -0,0 +1,4 @@
+pub mod error;
+pub mod item;
+pub mod operation;
+pub mod token;
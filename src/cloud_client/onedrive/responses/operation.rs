@@ -0,0 +1,51 @@
+use crate::cloud_client::onedrive::responses::item::DriveItem;
+use serde::Deserialize;
+
+/// Upload session state, the session is finished when the item is returned instead
+/// More details [here](https://learn.microsoft.com/graph/api/driveitem-createuploadsession)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadSession {
+    /// Returned only when the session is created
+    pub upload_url: Option<String>,
+    /// Ranges like `26-` or `26-31`, the first one starts with the next expected byte
+    #[serde(default)]
+    pub next_expected_ranges: Vec<String>,
+}
+
+impl UploadSession {
+    /// Returns offset of the first byte the session hasn't received yet
+    pub fn next_offset(&self) -> Option<u64> {
+        self.next_expected_ranges
+            .iter()
+            .filter_map(|range| range.split('-').next()?.parse().ok())
+            .min()
+    }
+}
+
+/// Response of requests sending content to the upload session
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum UploadSessionResult {
+    Complete(DriveItem),
+    Incomplete(UploadSession),
+}
+
+/// State of long-running action like copying, reported by the monitor URL
+/// More details [here](https://learn.microsoft.com/graph/long-running-actions-overview)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AsyncOperationStatus {
+    /// `notStarted`, `inProgress`, `completed`, `failed` and others
+    pub status: String,
+    pub percentage_complete: Option<f64>,
+    pub error: Option<OperationError>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OperationError {
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+/// Response of device authorization request
+/// More details [here](https://learn.microsoft.com/entra/identity-platform/v2-oauth2-device-code)
+#[derive(Deserialize, Debug)]
+pub struct DeviceCodeResult {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Lifetime of the device code in seconds
+    pub expires_in: u64,
+    /// Seconds to wait between token requests
+    pub interval: Option<u64>,
+    /// Instructions for the user
+    pub message: Option<String>,
+}
@@ -0,0 +1,242 @@
+use crate::cloud_client::mock_http::{Reply, ScriptedHttp};
+use crate::cloud_client::onedrive::client::OneDriveClient;
+use crate::cloud_client::onedrive::oauth::StoredCredentials;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions};
+use crate::config::{BackendConfig, OneDriveConfig, RemoteConfig, RetryConfig};
+use crate::credentials::file::FileCredentialStore;
+use serde_json::{json, Value};
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const REMOTE: &str = "mock";
+
+/// Client of a mock server logged in with stored credentials, content over 4 bytes
+/// is uploaded with upload sessions in 320 KiB chunks.
+/// The first request of every test receives access token.
+struct Fixture {
+    server: ScriptedHttp,
+    client: OneDriveClient,
+    _directory: TempDir,
+}
+
+impl Fixture {
+    fn new() -> Fixture {
+        let server = ScriptedHttp::start();
+        let directory = tempfile::tempdir().expect("temporary directory is created");
+
+        let store = FileCredentialStore::new(directory.path().join("credentials"), None);
+        StoredCredentials {
+            client_id: "mock-client".to_string(),
+            refresh_token: "refresh".to_string(),
+        }
+        .save(&store, REMOTE)
+        .expect("credentials are stored");
+
+        // Chunk size is rounded up to the smallest one allowed
+        let config = OneDriveConfig {
+            upload_session_threshold: NonZeroU64::new(4),
+            upload_chunk_size: NonZeroU64::new(1),
+            api_url: Some(server.url()),
+            auth_url: Some(server.url()),
+            ..OneDriveConfig::default()
+        };
+        let remote = RemoteConfig {
+            backend: BackendConfig::OneDrive(OneDriveConfig::default()),
+            credentials: None,
+            root: None,
+            retry: RetryConfig {
+                max_attempts: NonZeroU32::new(3),
+                max_elapsed_secs: NonZeroU64::new(10),
+                initial_backoff_ms: NonZeroU64::new(1),
+                max_backoff_ms: NonZeroU64::new(1),
+            },
+        };
+        let client =
+            OneDriveClient::build(REMOTE, &remote, &config, &store).expect("client is built");
+
+        server.reply(
+            "POST",
+            "/common/oauth2/v2.0/token",
+            Reply::json(200, json!({"access_token": "access", "expires_in": 3600})),
+        );
+        Fixture {
+            server,
+            client,
+            _directory: directory,
+        }
+    }
+
+    fn targets(&self) -> Vec<String> {
+        self.server
+            .requests()
+            .into_iter()
+            .map(|request| format!("{} {}", request.method, request.path()))
+            .collect()
+    }
+}
+
+fn file(id: &str, name: &str) -> Value {
+    json!({"id": id, "name": name, "size": 1, "file": {}})
+}
+
+fn folder(id: &str, name: &str) -> Value {
+    json!({"id": id, "name": name, "folder": {"childCount": 0}})
+}
+
+#[test]
+fn children_are_listed_following_next_link() {
+    let fixture = Fixture::new();
+    let next_link = format!(
+        "{}/me/drive/items/docs-id/children?$skiptoken=page2",
+        fixture.server.url()
+    );
+    fixture.server.reply(
+        "GET",
+        "/me/drive/root:/docs:/children",
+        Reply::json(
+            200,
+            json!({
+                "value": [folder("nested-id", "nested"), file("a-id", "a.txt")],
+                "@odata.nextLink": next_link,
+            }),
+        ),
+    );
+    fixture.server.reply(
+        "GET",
+        "/me/drive/items/docs-id/children?$skiptoken=page2",
+        Reply::json(200, json!({"value": [file("b-id", "b.txt")]})),
+    );
+
+    let entries = fixture
+        .client
+        .list_entries(PathBuf::from("/docs"))
+        .expect("folder is listed");
+
+    let names: Vec<String> = entries.iter().map(|entry| entry.display_name()).collect();
+    assert_eq!(names, ["nested/", "a.txt", "b.txt"]);
+    let requests = fixture.server.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(requests[1].query("$top").as_deref(), Some("200"));
+    // Next link is requested as is, without adding the query parameters again
+    assert_eq!(
+        requests[2].target,
+        "/me/drive/items/docs-id/children?$skiptoken=page2"
+    );
+    assert_eq!(requests[2].header("Authorization"), Some("Bearer access"));
+}
+
+#[test]
+fn bytes_session_did_not_keep_are_sent_again() {
+    let fixture = Fixture::new();
+    let session_url = format!("{}/upload/session", fixture.server.url());
+    fixture.server.reply(
+        "GET",
+        "/me/drive/root",
+        Reply::json(200, folder("root-id", "root")),
+    );
+    fixture.server.reply(
+        "POST",
+        "/me/drive/root:/big.bin:/createUploadSession",
+        Reply::json(200, json!({"uploadUrl": session_url})),
+    );
+    // Only a part of the first chunk has been received
+    fixture.server.reply(
+        "PUT",
+        "/upload/session",
+        Reply::json(202, json!({"nextExpectedRanges": ["200000-"]})),
+    );
+    fixture.server.reply(
+        "PUT",
+        "/upload/session",
+        Reply::json(201, file("big-id", "big.bin")),
+    );
+
+    let content: Vec<u8> = (0..400 * 1024).map(|index| index as u8).collect();
+    let entry = fixture
+        .client
+        .upload_stream(
+            &mut &content[..],
+            content.len() as u64,
+            PathBuf::from("/big.bin"),
+            UploadOptions::default(),
+        )
+        .expect("content is uploaded");
+
+    assert_eq!(entry.id.as_deref(), Some("big-id"));
+    let requests = fixture.server.requests();
+    let chunks = &requests[3..];
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+        chunks[0].header("Content-Range"),
+        Some("bytes 0-327679/409600")
+    );
+    assert_eq!(chunks[0].body, &content[..327680]);
+    assert_eq!(
+        chunks[1].header("Content-Range"),
+        Some("bytes 200000-409599/409600")
+    );
+    assert_eq!(chunks[1].body, &content[200000..]);
+    // Upload session URL is pre-authenticated
+    assert!(chunks
+        .iter()
+        .all(|chunk| chunk.header("Authorization").is_none()));
+}
+
+#[test]
+fn copy_is_completed_when_monitor_redirects_to_copy() {
+    let fixture = Fixture::new();
+    let monitor_url = format!("{}/monitor/operation", fixture.server.url());
+    let copy_url = format!("{}/me/drive/items/copy-id", fixture.server.url());
+    fixture.server.reply(
+        "GET",
+        "/me/drive/root",
+        Reply::json(200, folder("root-id", "root")),
+    );
+    fixture.server.reply(
+        "POST",
+        "/me/drive/root:/a.txt:/copy",
+        Reply::new(202, "").header("Location", &monitor_url),
+    );
+    fixture.server.reply(
+        "GET",
+        "/monitor/operation",
+        Reply::json(
+            202,
+            json!({"status": "inProgress", "percentageComplete": 50.0}),
+        ),
+    );
+    fixture.server.reply(
+        "GET",
+        "/monitor/operation",
+        Reply::new(303, "").header("Location", &copy_url),
+    );
+
+    fixture
+        .client
+        .copy_entry(
+            PathBuf::from("/a.txt"),
+            PathBuf::from("/b.txt"),
+            RelocationOptions::default(),
+        )
+        .expect("entry is copied");
+
+    // The copy isn't requested without access token
+    assert_eq!(
+        fixture.targets(),
+        [
+            "POST /common/oauth2/v2.0/token",
+            "GET /me/drive/root",
+            "POST /me/drive/root:/a.txt:/copy",
+            "GET /monitor/operation",
+            "GET /monitor/operation"
+        ]
+    );
+    let requests = fixture.server.requests();
+    assert_eq!(
+        requests[2]
+            .query("@microsoft.graph.conflictBehavior")
+            .as_deref(),
+        Some("fail")
+    );
+}
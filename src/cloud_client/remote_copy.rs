@@ -0,0 +1,152 @@
+use crate::cloud_client::content_hash::HashingReader;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::folder_transfer::TransferSummary;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions};
+use crate::errors::AppError;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Copies file or folder from one remote to another.
+/// Remotes of the same backend copy entries on the server side if the backend allows that,
+/// otherwise every file is streamed from the source download into the destination upload
+/// without being saved locally. Content of every copied file is verified afterwards.
+pub fn copy_between_remotes(
+    source: &dyn CloudClient,
+    from_path: &Path,
+    destination: &dyn CloudClient,
+    to_path: &Path,
+    options: RelocationOptions,
+) -> Result<TransferSummary, AppError> {
+    let entry = source.metadata(from_path.to_path_buf())?;
+    let mut summary = TransferSummary::default();
+
+    // Saving copy reference can't rename the entry on conflict, so such copies are streamed
+    if source.backend() == destination.backend() && !options.autorename {
+        if let Some(reference) = source.copy_reference(from_path.to_path_buf())? {
+            info!("Copying on the server side...");
+            let result = destination
+                .save_copy_reference(&reference, to_path.to_path_buf())
+                .and_then(|_| verify_server_copy(&entry, destination, to_path));
+            summary.record(from_path.to_path_buf(), result);
+            return Ok(summary);
+        }
+    }
+
+    let upload_options = UploadOptions {
+        autorename: options.autorename,
+        ..UploadOptions::default()
+    };
+    if !entry.is_folder() {
+        let result = stream_file(
+            source,
+            from_path,
+            &entry,
+            destination,
+            to_path,
+            &upload_options,
+        );
+        summary.record(from_path.to_path_buf(), result);
+        return Ok(summary);
+    }
+
+    create_folder(destination, to_path.to_path_buf())?;
+    for (relative_path, entry) in source.list_entries_recursive(from_path.to_path_buf())? {
+        let destination_path = to_path.join(&relative_path);
+        if entry.is_folder() {
+            create_folder(destination, destination_path)?;
+        } else {
+            let result = stream_file(
+                source,
+                &from_path.join(&relative_path),
+                &entry,
+                destination,
+                &destination_path,
+                &upload_options,
+            );
+            summary.record(relative_path, result);
+        }
+    }
+    Ok(summary)
+}
+
+/// Creates the folder unless it already exists, e.g. when the copy is repeated
+fn create_folder(destination: &dyn CloudClient, path: PathBuf) -> Result<(), AppError> {
+    match destination.create_folder(path.clone(), false) {
+        Err(AppError::Conflict(message)) => match destination.metadata(path) {
+            Ok(entry) if entry.is_folder() => Ok(()),
+            _ => Err(AppError::Conflict(message)),
+        },
+        result => result,
+    }
+}
+
+/// Pipes download of the source file into upload of the destination one,
+/// hashing the content on the way the same way the destination does.
+/// Files reported without size are read in full before the upload,
+/// as their size is known only once they are downloaded.
+fn stream_file(
+    source: &dyn CloudClient,
+    from_path: &Path,
+    entry: &Entry,
+    destination: &dyn CloudClient,
+    to_path: &Path,
+    options: &UploadOptions,
+) -> Result<(), AppError> {
+    debug!("Streaming {:?} to {:?}", from_path, to_path);
+    let download = source.open_download(from_path.to_path_buf())?;
+    let mut reader = HashingReader::new(download, destination.content_hasher());
+    let (size, uploaded, (read, hash)) = if entry.size == 0 {
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).map_err(AppError::Io)?;
+        let size = content.len() as u64;
+        let uploaded = destination.upload_stream(
+            &mut content.as_slice(),
+            size,
+            to_path.to_path_buf(),
+            options.clone(),
+        )?;
+        (size, uploaded, reader.finish())
+    } else {
+        let uploaded = destination.upload_stream(
+            &mut reader,
+            entry.size,
+            to_path.to_path_buf(),
+            options.clone(),
+        )?;
+        (entry.size, uploaded, reader.finish())
+    };
+
+    if read != size || uploaded.size != size {
+        return Err(AppError::Verification(format!(
+            "{} has {size} bytes, but {read} were read and {} were uploaded",
+            from_path.display(),
+            uploaded.size
+        )));
+    }
+    if let (Some(expected), Some(actual)) = (hash, &uploaded.hash) {
+        if &expected != actual {
+            return Err(AppError::Verification(format!(
+                "hash of {} is {actual} instead of {expected}",
+                to_path.display()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Compares the copy with the original, both are hashed the same way as they share the backend
+fn verify_server_copy(
+    entry: &Entry,
+    destination: &dyn CloudClient,
+    to_path: &Path,
+) -> Result<(), AppError> {
+    let copy = destination.metadata(to_path.to_path_buf())?;
+    if copy.kind != entry.kind || copy.size != entry.size || copy.hash != entry.hash {
+        return Err(AppError::Verification(format!(
+            "{} differs from the original",
+            to_path.display()
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::cloud_client::dropbox::client::DropboxClient;
+use crate::cloud_client::google_drive::client::GoogleDriveClient;
+use crate::cloud_client::local::client::LocalFsClient;
+use crate::cloud_client::onedrive::client::OneDriveClient;
+use crate::cloud_client::s3::client::S3Client;
+use crate::cloud_client::sftp::client::SftpClient;
+use crate::cloud_client::webdav::client::WebDavClient;
+use crate::cloud_client::CloudClient;
+use crate::config::{BackendConfig, Config, RemoteConfig};
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Separates remote name from the path in it, e.g. `work:/reports/q3.pdf`
+const REMOTE_SEPARATOR: char = ':';
+
+/// Clients of all configured remotes, one of which is shown in the cloud pane.
+/// Cloud paths without remote prefix belong to the current remote.
+pub struct Remotes {
+    clients: BTreeMap<String, Box<dyn CloudClient>>,
+    /// Remotes which couldn't be prepared, e.g. because they are not logged in yet
+    unavailable: BTreeMap<String, String>,
+    current: String,
+}
+
+impl Remotes {
+    pub fn new(current: String, client: Box<dyn CloudClient>) -> Self {
+        Self {
+            clients: BTreeMap::from([(current.clone(), client)]),
+            unavailable: BTreeMap::new(),
+            current,
+        }
+    }
+
+    /// Prepares clients of all configured remotes.
+    /// Only failure to prepare the `current` one is an error, other remotes are marked unavailable.
+    pub fn build(
+        config: &Config,
+        current: &str,
+        credential_store: &dyn CredentialStore,
+    ) -> Result<Remotes, AppError> {
+        let client = build_client(current, &config.remotes[current], credential_store)?;
+        let mut remotes = Self::new(current.to_string(), client);
+
+        for (name, remote) in config.remotes.iter().filter(|(name, _)| *name != current) {
+            match build_client(name, remote, credential_store) {
+                Ok(client) => remotes.insert(name.clone(), client),
+                Err(error) => {
+                    warn!("Remote {name} is unavailable: {error}");
+                    remotes.unavailable.insert(name.clone(), error.to_string());
+                }
+            }
+        }
+        Ok(remotes)
+    }
+
+    pub fn insert(&mut self, name: String, client: Box<dyn CloudClient>) {
+        self.unavailable.remove(&name);
+        self.clients.insert(name, client);
+    }
+
+    pub fn current_name(&self) -> &str {
+        &self.current
+    }
+
+    pub fn current(&self) -> &dyn CloudClient {
+        self.clients[&self.current].as_ref()
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn CloudClient, AppError> {
+        self.clients
+            .get(name)
+            .map(Box::as_ref)
+            .ok_or_else(|| self.missing(name))
+    }
+
+    fn missing(&self, name: &str) -> AppError {
+        AppError::Remote(match self.unavailable.get(name) {
+            Some(reason) => format!("remote {name} is unavailable: {reason}"),
+            None => format!("there is no remote named {name}"),
+        })
+    }
+
+    /// Returns names of available remotes in alphabetical order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.clients.keys().map(String::as_str)
+    }
+
+    /// Returns unavailable remotes with the reasons
+    pub fn unavailable(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.unavailable
+            .iter()
+            .map(|(name, reason)| (name.as_str(), reason.as_str()))
+    }
+
+    /// Makes remote named `name` the current one
+    pub fn switch(&mut self, name: &str) -> Result<(), AppError> {
+        self.get(name)?;
+        self.current = name.to_string();
+        Ok(())
+    }
+
+    /// Makes the next available remote in alphabetical order the current one
+    pub fn switch_next(&mut self) {
+        let next = self
+            .clients
+            .range::<str, _>((
+                std::ops::Bound::Excluded(self.current.as_str()),
+                std::ops::Bound::Unbounded,
+            ))
+            .next()
+            .or_else(|| self.clients.iter().next())
+            .map(|(name, _)| name.clone());
+        if let Some(next) = next {
+            self.current = next;
+        }
+    }
+
+    /// Splits remote prefix off the cloud path, returning remote name and the path within it.
+    /// Prefixes which don't name a configured remote are a part of the path, e.g. `notes:v2.txt`.
+    pub fn resolve(&self, path: &Path) -> Result<(&str, PathBuf), AppError> {
+        let configured =
+            |name: &str| self.clients.contains_key(name) || self.unavailable.contains_key(name);
+        let Some((name, path)) = split_remote(path).filter(|(name, _)| configured(name)) else {
+            return Ok((self.current.as_str(), path.to_path_buf()));
+        };
+        let (name, _) = self
+            .clients
+            .get_key_value(name)
+            .ok_or_else(|| self.missing(name))?;
+        Ok((name.as_str(), path))
+    }
+
+    /// Returns client of the remote the cloud path belongs to and the path within that remote
+    pub fn client_for(&self, path: &Path) -> Result<(&dyn CloudClient, PathBuf), AppError> {
+        let (name, path) = self.resolve(path)?;
+        Ok((self.get(name)?, path))
+    }
+}
+
+/// Prepares client of the backend the remote is configured with
+fn build_client(
+    name: &str,
+    remote: &RemoteConfig,
+    credential_store: &dyn CredentialStore,
+) -> Result<Box<dyn CloudClient>, AppError> {
+    match &remote.backend {
+        BackendConfig::Dropbox(config) => Ok(Box::new(DropboxClient::build(
+            name,
+            remote,
+            config,
+            credential_store,
+        )?)),
+        BackendConfig::GoogleDrive(config) => Ok(Box::new(GoogleDriveClient::build(
+            name,
+            remote,
+            config,
+            credential_store,
+        )?)),
+        BackendConfig::Local => Ok(Box::new(LocalFsClient::build(name, remote)?)),
+        BackendConfig::OneDrive(config) => Ok(Box::new(OneDriveClient::build(
+            name,
+            remote,
+            config,
+            credential_store,
+        )?)),
+        BackendConfig::S3(config) => Ok(Box::new(S3Client::build(name, remote, config)?)),
+        BackendConfig::Sftp(config) => Ok(Box::new(SftpClient::build(name, remote, config)?)),
+        BackendConfig::WebDav(config) => Ok(Box::new(WebDavClient::build(
+            name,
+            remote,
+            config,
+            credential_store,
+        )?)),
+    }
+}
+
+/// Returns remote name and the rest of the path if the path starts with `<remote>:`
+fn split_remote(path: &Path) -> Option<(&str, PathBuf)> {
+    let (name, path) = path.to_str()?.split_once(REMOTE_SEPARATOR)?;
+    if name.is_empty() || name.contains('/') {
+        return None;
+    }
+    // Cloud root is addressed the same way as the initial cloud path of the workspace
+    let path = if path.is_empty() { "//" } else { path };
+    Some((name, PathBuf::from(path)))
+}
@@ -0,0 +1,103 @@
+use crate::errors::AppError;
+use crate::utilities::environment::read_positive_variable;
+use rand::Rng;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Decides how failed requests to cloud storage are repeated
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// No new attempt is made once this much time has passed since the first one
+    pub max_elapsed: Duration,
+    /// Upper bound of the delay before the second attempt, doubled for every next one
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            max_elapsed: Duration::from_secs(120),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Overrides defaults with `RETRY_MAX_ATTEMPTS`, `RETRY_MAX_ELAPSED_SECS`,
+    /// `RETRY_INITIAL_BACKOFF_MS` and `RETRY_MAX_BACKOFF_MS` environment variables
+    pub fn from_env() -> Result<RetryPolicy, AppError> {
+        let default = Self::default();
+        Ok(Self {
+            max_attempts: read_positive_variable(
+                "RETRY_MAX_ATTEMPTS",
+                default.max_attempts.into(),
+            )?
+            .try_into()
+            .unwrap_or(u32::MAX),
+            max_elapsed: Duration::from_secs(read_positive_variable(
+                "RETRY_MAX_ELAPSED_SECS",
+                default.max_elapsed.as_secs(),
+            )?),
+            initial_backoff: Duration::from_millis(read_positive_variable(
+                "RETRY_INITIAL_BACKOFF_MS",
+                default.initial_backoff.as_millis() as u64,
+            )?),
+            max_backoff: Duration::from_millis(read_positive_variable(
+                "RETRY_MAX_BACKOFF_MS",
+                default.max_backoff.as_millis() as u64,
+            )?),
+        })
+    }
+
+    /// Runs `operation` until it succeeds, fails permanently or the policy limits are reached.
+    /// Rate limited requests were not processed, so they are always repeated,
+    /// while server and network failures are repeated only for `idempotent` operations.
+    pub fn run<T>(
+        &self,
+        idempotent: bool,
+        mut operation: impl FnMut() -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        let started = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let error = match operation() {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+
+            let delay = match &error {
+                AppError::RateLimited {
+                    retry_after: Some(seconds),
+                } => Duration::from_secs(*seconds),
+                AppError::RateLimited { retry_after: None } => self.backoff(attempt),
+                AppError::Server { .. } | AppError::SendRequest(_) if idempotent => {
+                    self.backoff(attempt)
+                }
+                _ => return Err(error),
+            };
+
+            if attempt >= self.max_attempts || started.elapsed() + delay > self.max_elapsed {
+                return Err(error);
+            }
+
+            warn!("Attempt {attempt} failed ({error}), retrying in {delay:?}");
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Exponential backoff with full jitter: random delay up to the doubled previous bound
+    fn backoff(&self, attempt: u32) -> Duration {
+        let bound = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_backoff);
+        rand::thread_rng().gen_range(Duration::ZERO..=bound)
+    }
+}
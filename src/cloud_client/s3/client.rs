@@ -0,0 +1,712 @@
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::cloud_client::http::{send_checked, ApiResponses, ErrorResponse};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::s3::responses::{
+    last_segment, InitiateMultipartUploadResult, ListBucketResult, Object, S3ErrorResponse,
+};
+use crate::cloud_client::s3::signing::{canonical_query, encode_key, payload_hash, Signer};
+use crate::cloud_client::upload_state::PendingUpload;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::{RemoteConfig, S3Config};
+use crate::errors::{AppError, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR};
+use crate::utilities::files::write_atomically;
+use chrono::{DateTime, Utc};
+use reqwest::blocking::{Client, ClientBuilder, Response};
+use reqwest::header::{
+    HeaderMap, HeaderValue, CONTENT_LENGTH, ETAG, IF_MATCH, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Method, StatusCode, Url};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, instrument, warn};
+
+static DEFAULT_REGION: &str = "us-east-1";
+static COPY_SOURCE_HEADER: &str = "x-amz-copy-source";
+
+const DEFAULT_MULTIPART_THRESHOLD: u64 = 16 * 1024 * 1024;
+const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
+/// S3 rejects smaller parts, except for the last one
+const MIN_PART_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Client of Amazon S3 or compatible storage like MinIO.
+/// Folders are emulated with `/` separated key prefixes.
+#[derive(Debug)]
+pub struct S3Client {
+    client: Client,
+    signer: Signer,
+    /// Base URL the bucket and keys are appended to
+    bucket_url: Url,
+    bucket: String,
+    /// Prefix of all keys of the remote, empty or ending with `/`
+    prefix: String,
+    /// Files larger than this are uploaded in parts
+    multipart_threshold: u64,
+    part_size: u64,
+    retry_policy: RetryPolicy,
+}
+
+impl S3Client {
+    /// Builds client of the remote named `name`.
+    /// Credentials absent in the configuration are read from environment variables.
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &S3Config,
+    ) -> Result<S3Client, AppError> {
+        let client = ClientBuilder::new()
+            .timeout(None)
+            .build()
+            .map_err(|_| AppError::PrepareClient(BUILD_REQUEST_CLIENT_ERROR.to_string()))?;
+
+        let region = config.region.as_deref().unwrap_or(DEFAULT_REGION);
+        let credential = |value: &Option<String>, variable: &str| {
+            value
+                .clone()
+                .or_else(|| std::env::var(variable).ok())
+                .ok_or_else(|| {
+                    AppError::Config(format!("{variable} is absent for S3 remote {name}"))
+                })
+        };
+        let signer = Signer {
+            access_key_id: credential(&config.access_key_id, "AWS_ACCESS_KEY_ID")?,
+            secret_access_key: credential(&config.secret_access_key, "AWS_SECRET_ACCESS_KEY")?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+            region: region.to_string(),
+        };
+
+        let endpoint = match &config.endpoint {
+            Some(endpoint) => endpoint.clone(),
+            None => format!("https://s3.{region}.amazonaws.com"),
+        };
+        let mut bucket_url = Url::parse(&endpoint)
+            .map_err(|error| AppError::Config(format!("invalid S3 endpoint: {error}")))?;
+        if config.path_style {
+            bucket_url.set_path(&format!("/{}/", encode_key(&config.bucket)));
+        } else {
+            let host = format!(
+                "{}.{}",
+                config.bucket,
+                bucket_url.host_str().unwrap_or_default()
+            );
+            bucket_url
+                .set_host(Some(&host))
+                .map_err(|error| AppError::Config(format!("invalid S3 endpoint: {error}")))?;
+            bucket_url.set_path("/");
+        }
+
+        let part_size = config
+            .part_size
+            .map_or(DEFAULT_PART_SIZE, |size| size.get());
+        if part_size < MIN_PART_SIZE {
+            return Err(AppError::Config(format!(
+                "part size of S3 remote {name} must be at least {MIN_PART_SIZE} bytes"
+            )));
+        }
+
+        let prefix = remote
+            .root
+            .as_deref()
+            .map(relative_key)
+            .filter(|root| !root.is_empty())
+            .map(|root| root + "/")
+            .unwrap_or_default();
+
+        Ok(Self {
+            client,
+            signer,
+            bucket_url,
+            bucket: config.bucket.clone(),
+            prefix,
+            multipart_threshold: config
+                .multipart_threshold
+                .map_or(DEFAULT_MULTIPART_THRESHOLD, |threshold| threshold.get()),
+            part_size,
+            retry_policy: remote.retry.policy()?,
+        })
+    }
+
+    /// Returns key of the object at `path` of the remote, empty for the remote root
+    fn key(&self, path: &Path) -> String {
+        let relative = relative_key(path);
+        if relative.is_empty() {
+            relative
+        } else {
+            self.prefix.clone() + &relative
+        }
+    }
+
+    /// Returns prefix of all keys inside the folder at `path`
+    fn folder_prefix(&self, path: &Path) -> String {
+        match self.key(path) {
+            key if key.is_empty() => self.prefix.clone(),
+            key => key + "/",
+        }
+    }
+
+    /// Sends signed request to the object with `key`, following the retry policy
+    fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: &[(&str, String)],
+        headers: HeaderMap,
+        body: Vec<u8>,
+        idempotent: bool,
+    ) -> Result<Response, AppError> {
+        let mut url = self
+            .bucket_url
+            .join(&encode_key(key))
+            .map_err(|_| AppError::PrepareRequest)?;
+        let query = canonical_query(query);
+        url.set_query((!query.is_empty()).then_some(query.as_str()));
+        let payload_hash = payload_hash(&body);
+
+        self.retry_policy.run(idempotent, || {
+            let mut headers = headers.clone();
+            self.signer
+                .sign(&method, &url, &mut headers, &payload_hash, Utc::now());
+            send_checked::<S3Api>(
+                self.client
+                    .request(method.clone(), url.clone())
+                    .headers(headers)
+                    .body(body.clone()),
+            )
+        })
+    }
+
+    fn head_object(&self, key: &str) -> Result<Entry, AppError> {
+        let response = self.send(Method::HEAD, key, &[], HeaderMap::new(), Vec::new(), true)?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        Ok(Entry {
+            name: last_segment(key).to_string(),
+            kind: EntryKind::File,
+            size: header(CONTENT_LENGTH)
+                .and_then(|length| length.parse().ok())
+                .unwrap_or_default(),
+            modified: header(LAST_MODIFIED)
+                .and_then(|modified| DateTime::parse_from_rfc2822(modified).ok())
+                .map(|modified| modified.with_timezone(&Utc)),
+            hash: header(ETAG).map(|e_tag| e_tag.trim_matches('"').to_string()),
+            id: None,
+        })
+    }
+
+    /// Lists all objects with `prefix`, grouped into common prefixes if `delimited`
+    fn list_objects(
+        &self,
+        prefix: &str,
+        delimited: bool,
+        max_keys: Option<u32>,
+    ) -> Result<(Vec<Object>, Vec<String>), AppError> {
+        let mut objects = Vec::new();
+        let mut prefixes = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if delimited {
+                query.push(("delimiter", "/".to_string()));
+            }
+            if let Some(max_keys) = max_keys {
+                query.push(("max-keys", max_keys.to_string()));
+            }
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+
+            let body = self
+                .send(Method::GET, "", &query, HeaderMap::new(), Vec::new(), true)?
+                .text()
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+            let page: ListBucketResult = quick_xml::de::from_str(&body)
+                .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+
+            objects.extend(page.contents);
+            prefixes.extend(page.common_prefixes.into_iter().map(|prefix| prefix.prefix));
+            match page.next_continuation_token {
+                Some(token) if page.is_truncated && max_keys.is_none() => {
+                    continuation_token = Some(token)
+                }
+                _ => return Ok((objects, prefixes)),
+            }
+        }
+    }
+
+    /// Returns key at `path` that isn't taken yet, renaming it with a number if needed
+    fn destination_key(&self, path: &Path, autorename: bool) -> Result<String, AppError> {
+        let path = free_path(path, autorename, |candidate| self.exists(candidate))?;
+        Ok(self.key(&path))
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, AppError> {
+        match self.metadata(path.to_path_buf()) {
+            Ok(_) => Ok(true),
+            Err(AppError::NotFound(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Uploads content in parts of `part_size` bytes, aborting the upload on failure
+    /// so the uploaded parts don't occupy the storage
+    fn upload_in_parts(
+        &self,
+        reader: &mut dyn Read,
+        key: &str,
+        headers: HeaderMap,
+    ) -> Result<(), AppError> {
+        info!("Starting multipart upload...");
+        let body = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploads", String::new())],
+                HeaderMap::new(),
+                Vec::new(),
+                false,
+            )?
+            .text()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        let upload_id = quick_xml::de::from_str::<InitiateMultipartUploadResult>(&body)
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?
+            .upload_id;
+
+        let result = self.send_parts(reader, key, &upload_id, headers);
+        if result.is_err() {
+            let aborted = self.send(
+                Method::DELETE,
+                key,
+                &[("uploadId", upload_id)],
+                HeaderMap::new(),
+                Vec::new(),
+                true,
+            );
+            if let Err(error) = aborted {
+                warn!("Unable to abort multipart upload: {error}");
+            }
+        }
+        result
+    }
+
+    fn send_parts(
+        &self,
+        reader: &mut dyn Read,
+        key: &str,
+        upload_id: &str,
+        headers: HeaderMap,
+    ) -> Result<(), AppError> {
+        let mut completed = String::from("<CompleteMultipartUpload>");
+        let mut uploaded = 0;
+        for part_number in 1.. {
+            let mut part = Vec::with_capacity(self.part_size as usize);
+            reader
+                .take(self.part_size)
+                .read_to_end(&mut part)
+                .map_err(AppError::Io)?;
+            if part.is_empty() && part_number > 1 {
+                break;
+            }
+
+            uploaded += part.len();
+            let response = self.send(
+                Method::PUT,
+                key,
+                &[
+                    ("partNumber", part_number.to_string()),
+                    ("uploadId", upload_id.to_string()),
+                ],
+                HeaderMap::new(),
+                part,
+                true,
+            )?;
+            let e_tag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+            completed.push_str(&format!(
+                "<Part><PartNumber>{part_number}</PartNumber><ETag>{}</ETag></Part>",
+                quick_xml::escape::escape(e_tag)
+            ));
+            info!("Uploaded {uploaded} bytes");
+        }
+        completed.push_str("</CompleteMultipartUpload>");
+
+        // Failure of the completion may be reported in the body of successful response
+        let body = self
+            .send(
+                Method::POST,
+                key,
+                &[("uploadId", upload_id.to_string())],
+                headers,
+                completed.into_bytes(),
+                false,
+            )?
+            .text()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        match quick_xml::de::from_str::<S3ErrorResponse>(&body) {
+            Ok(error) => Err(error.into_app_error(StatusCode::INTERNAL_SERVER_ERROR)),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn copy_object(&self, from_key: &str, to_key: &str) -> Result<(), AppError> {
+        let mut headers = HeaderMap::new();
+        let source = format!("/{}/{}", encode_key(&self.bucket), encode_key(from_key));
+        headers.insert(
+            COPY_SOURCE_HEADER,
+            HeaderValue::from_str(&source).map_err(|_| AppError::PrepareRequest)?,
+        );
+
+        // Failure of the copy may be reported in the body of successful response
+        let body = self
+            .send(Method::PUT, to_key, &[], headers, Vec::new(), true)?
+            .text()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        match quick_xml::de::from_str::<S3ErrorResponse>(&body) {
+            Ok(error) => Err(error.into_app_error(StatusCode::INTERNAL_SERVER_ERROR)),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn delete_object(&self, key: &str) -> Result<(), AppError> {
+        self.send(Method::DELETE, key, &[], HeaderMap::new(), Vec::new(), true)?;
+        Ok(())
+    }
+
+    /// Returns keys of the object at `path`, or of all objects in the folder at `path`,
+    /// paired with their keys under `to_path`
+    fn relocated_keys(
+        &self,
+        from_path: &Path,
+        to_path: &Path,
+        options: RelocationOptions,
+    ) -> Result<Vec<(String, String)>, AppError> {
+        let entry = self.metadata(from_path.to_path_buf())?;
+        let to_key = self.destination_key(to_path, options.autorename)?;
+        if !entry.is_folder() {
+            return Ok(vec![(self.key(from_path), to_key)]);
+        }
+
+        let from_prefix = self.folder_prefix(from_path);
+        let to_prefix = to_key + "/";
+        if to_prefix.starts_with(&from_prefix) {
+            return Err(AppError::Request(
+                "folder can't be relocated into itself".to_string(),
+            ));
+        }
+        let (objects, _) = self.list_objects(&from_prefix, false, None)?;
+        Ok(objects
+            .into_iter()
+            .map(|object| {
+                let relocated = to_prefix.clone() + &object.key[from_prefix.len()..];
+                (object.key, relocated)
+            })
+            .collect())
+    }
+}
+
+impl CloudClient for S3Client {
+    fn backend(&self) -> &'static str {
+        "s3"
+    }
+
+    #[instrument(name = "S3 download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        info!("Downloading...");
+        let mut response = self.open_download(from_path)?;
+        let written = write_atomically(&mut response, &to_path).map_err(AppError::Io)?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let key = self.key(&path);
+        let response = self.send(Method::GET, &key, &[], HeaderMap::new(), Vec::new(), true)?;
+        Ok(Box::new(response))
+    }
+
+    #[instrument(name = "S3 upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let mut file = File::open(&from_path).map_err(AppError::Io)?;
+        let size = file.metadata().map_err(AppError::Io)?.len();
+        self.upload_stream(&mut file, size, to_path, options)?;
+        Ok(())
+    }
+
+    /// Conflicts are detected with conditional writes, `update` mode expects ETag of the object
+    #[instrument(name = "S3 upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let mut headers = HeaderMap::new();
+        let key = match &options.mode {
+            WriteMode::Add if options.autorename => self.destination_key(&to_path, true)?,
+            WriteMode::Add => {
+                headers.insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+                self.key(&to_path)
+            }
+            WriteMode::Overwrite => self.key(&to_path),
+            WriteMode::Update(e_tag) => {
+                let e_tag = format!("\"{}\"", e_tag.trim_matches('"'));
+                headers.insert(
+                    IF_MATCH,
+                    HeaderValue::from_str(&e_tag).map_err(|_| AppError::PrepareRequest)?,
+                );
+                self.key(&to_path)
+            }
+        };
+
+        if size > self.multipart_threshold {
+            self.upload_in_parts(reader, &key, headers)?;
+        } else {
+            info!("Uploading...");
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).map_err(AppError::Io)?;
+            let idempotent = options.mode == WriteMode::Overwrite;
+            self.send(Method::PUT, &key, &[], headers, bytes, idempotent)?;
+        }
+
+        info!("File has been uploaded");
+        self.head_object(&key)
+    }
+
+    /// Deletes the object, or every object inside the folder
+    #[instrument(name = "S3 delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        info!("Deleting...");
+        let key = self.key(&path);
+        if key.is_empty() {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+
+        match self.head_object(&key) {
+            Ok(_) => self.delete_object(&key)?,
+            Err(AppError::NotFound(_)) => {
+                let (objects, _) = self.list_objects(&(key + "/"), false, None)?;
+                if objects.is_empty() {
+                    return Err(AppError::NotFound(path.display().to_string()));
+                }
+                for object in objects {
+                    self.delete_object(&object.key)?;
+                }
+            }
+            Err(error) => return Err(error),
+        }
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    /// Creates empty `<key>/` object, which keeps the folder even without any files inside
+    #[instrument(name = "S3 create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        info!("Creating folder...");
+        let key = self.destination_key(&path, autorename)? + "/";
+        self.send(Method::PUT, &key, &[], HeaderMap::new(), Vec::new(), true)?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    /// S3 can't rename objects, so they are copied and deleted afterwards
+    #[instrument(name = "S3 move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Moving...");
+        for (from_key, to_key) in self.relocated_keys(&from_path, &to_path, options)? {
+            self.copy_object(&from_key, &to_key)?;
+            self.delete_object(&from_key)?;
+        }
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    #[instrument(name = "S3 copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Copying...");
+        for (from_key, to_key) in self.relocated_keys(&from_path, &to_path, options)? {
+            self.copy_object(&from_key, &to_key)?;
+        }
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    /// Path is a folder if there is no object with its key, but there are objects inside it
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let key = self.key(&path);
+        let folder = Entry {
+            name: last_segment(&key).to_string(),
+            kind: EntryKind::Folder,
+            size: 0,
+            modified: None,
+            hash: None,
+            id: None,
+        };
+        if key.is_empty() {
+            return Ok(folder);
+        }
+
+        match self.head_object(&key) {
+            Err(AppError::NotFound(_)) => {}
+            result => return result,
+        }
+        let (objects, prefixes) = self.list_objects(&(key + "/"), true, Some(1))?;
+        if objects.is_empty() && prefixes.is_empty() {
+            return Err(AppError::NotFound(path.display().to_string()));
+        }
+        Ok(folder)
+    }
+
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        info!("Listing entries...");
+        let prefix = self.folder_prefix(&path);
+        let (objects, prefixes) = self.list_objects(&prefix, true, None)?;
+
+        let mut entries: Vec<Entry> = prefixes
+            .iter()
+            .map(|prefix| Entry {
+                name: last_segment(prefix).to_string(),
+                kind: EntryKind::Folder,
+                size: 0,
+                modified: None,
+                hash: None,
+                id: None,
+            })
+            .collect();
+        entries.extend(
+            objects
+                .into_iter()
+                .filter(|object| object.key != prefix)
+                .map(Object::into_entry),
+        );
+
+        debug!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    /// Uses a single listing without delimiter, folders are derived from the keys
+    fn list_entries_recursive(&self, path: PathBuf) -> Result<Vec<(PathBuf, Entry)>, AppError> {
+        info!("Listing entries recursively...");
+        let prefix = self.folder_prefix(&path);
+        let (objects, _) = self.list_objects(&prefix, false, None)?;
+
+        let mut folders = BTreeMap::new();
+        let mut files = Vec::new();
+        for object in objects {
+            let relative_path = PathBuf::from(&object.key[prefix.len()..]);
+            for folder in relative_path.ancestors().skip(1) {
+                if !folder.as_os_str().is_empty() {
+                    folders
+                        .entry(folder.to_path_buf())
+                        .or_insert_with(|| Entry {
+                            name: last_segment(&folder.to_string_lossy()).to_string(),
+                            kind: EntryKind::Folder,
+                            size: 0,
+                            modified: None,
+                            hash: None,
+                            id: None,
+                        });
+                }
+            }
+            // Folder markers only make sure their folders are listed
+            if !object.key.ends_with('/') {
+                files.push((relative_path, object.into_entry()));
+            }
+        }
+
+        let mut entries: Vec<(PathBuf, Entry)> = folders.into_iter().collect();
+        entries.extend(files);
+        info!("Received {} entries", entries.len());
+        Ok(entries)
+    }
+
+    /// Multipart uploads are aborted on failure, so there is nothing to resume
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn abandon_upload(&self, _upload: &PendingUpload) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+/// Joins normal components of the path with `/`
+fn relative_key(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Responses of S3 API
+struct S3Api;
+
+impl ApiResponses for S3Api {
+    /// Decodes the XML error body, falling back to the status for responses without one,
+    /// e.g. to HEAD requests
+    fn decode_error(response: ErrorResponse) -> AppError {
+        let ErrorResponse {
+            status,
+            retry_after,
+            body,
+            ..
+        } = response;
+        if let Ok(error) = quick_xml::de::from_str::<S3ErrorResponse>(&body) {
+            return match error.into_app_error(status) {
+                AppError::RateLimited { .. } => AppError::RateLimited { retry_after },
+                error => error,
+            };
+        }
+        match status {
+            StatusCode::NOT_FOUND => AppError::NotFound(status.to_string()),
+            StatusCode::PRECONDITION_FAILED | StatusCode::CONFLICT => {
+                AppError::Conflict(status.to_string())
+            }
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                AppError::Unauthorized(status.to_string())
+            }
+            StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited { retry_after },
+            status if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message: body,
+            },
+            status => AppError::Request(format!("{status}: {body}")),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod client;
+pub mod responses;
+pub mod signing;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,93 @@
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::errors::AppError;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+/// Page of `ListObjectsV2` response
+/// More details [here](https://docs.aws.amazon.com/AmazonS3/latest/API/API_ListObjectsV2.html)
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListBucketResult {
+    #[serde(default)]
+    pub contents: Vec<Object>,
+    #[serde(default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+    #[serde(default)]
+    pub is_truncated: bool,
+    pub next_continuation_token: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct Object {
+    pub key: String,
+    pub size: u64,
+    pub last_modified: Option<DateTime<Utc>>,
+    #[serde(rename = "ETag")]
+    pub e_tag: Option<String>,
+}
+
+/// "Folder" grouping keys which share the prefix up to the delimiter
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CommonPrefix {
+    pub prefix: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct InitiateMultipartUploadResult {
+    pub upload_id: String,
+}
+
+/// Error body, also returned with successful status by some requests which fail midway
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct S3ErrorResponse {
+    pub code: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+impl Object {
+    /// Turns object into file entry named after the last segment of its key
+    pub fn into_entry(self) -> Entry {
+        Entry {
+            name: last_segment(&self.key).to_string(),
+            kind: EntryKind::File,
+            size: self.size,
+            modified: self.last_modified,
+            hash: self.e_tag.map(|e_tag| e_tag.trim_matches('"').to_string()),
+            id: None,
+        }
+    }
+}
+
+impl S3ErrorResponse {
+    pub fn into_app_error(self, status: StatusCode) -> AppError {
+        let message = format!("{}: {}", self.code, self.message);
+        match self.code.as_str() {
+            "NoSuchKey" | "NoSuchUpload" | "NoSuchBucket" => AppError::NotFound(message),
+            "PreconditionFailed" | "ConditionalRequestConflict" => AppError::Conflict(message),
+            "SlowDown" => AppError::RateLimited { retry_after: None },
+            "AccessDenied" | "InvalidAccessKeyId" | "SignatureDoesNotMatch" | "ExpiredToken" => {
+                AppError::Unauthorized(message)
+            }
+            _ if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message,
+            },
+            _ if status == StatusCode::BAD_REQUEST => AppError::Request(message),
+            _ => AppError::Api(message),
+        }
+    }
+}
+
+/// Returns the last non-empty segment of the key, e.g. `b` for both `a/b` and `a/b/`
+pub fn last_segment(key: &str) -> &str {
+    key.trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
@@ -0,0 +1,166 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use std::fmt::{Debug, Formatter};
+
+/// Characters left as they are by URI encoding of SigV4, `/` is kept in object keys only
+const QUERY_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+const KEY_ENCODE_SET: &AsciiSet = &QUERY_ENCODE_SET.remove(b'/');
+
+static ALGORITHM: &str = "AWS4-HMAC-SHA256";
+static SERVICE: &str = "s3";
+static DATE_HEADER: &str = "x-amz-date";
+static CONTENT_HASH_HEADER: &str = "x-amz-content-sha256";
+static SECURITY_TOKEN_HEADER: &str = "x-amz-security-token";
+
+pub fn encode_key(key: &str) -> String {
+    utf8_percent_encode(key, KEY_ENCODE_SET).to_string()
+}
+
+/// Returns query string with parameters sorted and encoded the way SigV4 expects
+pub fn canonical_query(parameters: &[(&str, String)]) -> String {
+    let mut parameters: Vec<String> = parameters
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "{}={}",
+                utf8_percent_encode(name, QUERY_ENCODE_SET),
+                utf8_percent_encode(value, QUERY_ENCODE_SET)
+            )
+        })
+        .collect();
+    parameters.sort();
+    parameters.join("&")
+}
+
+pub fn payload_hash(payload: &[u8]) -> String {
+    hex::encode(Sha256::digest(payload))
+}
+
+/// Signs requests with AWS Signature Version 4
+/// More details [here](https://docs.aws.amazon.com/IAM/latest/UserGuide/create-signed-request.html)
+pub struct Signer {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub region: String,
+}
+
+impl Debug for Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Signer")
+            .field("access_key_id", &self.access_key_id)
+            .field("region", &self.region)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer {
+    /// Adds date, payload hash and authorization headers to `headers` of the request.
+    /// `url` must already contain the encoded path and the canonical query.
+    pub fn sign(
+        &self,
+        method: &Method,
+        url: &Url,
+        headers: &mut HeaderMap,
+        payload_hash: &str,
+        now: DateTime<Utc>,
+    ) {
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        insert_header(headers, DATE_HEADER, &timestamp);
+        insert_header(headers, CONTENT_HASH_HEADER, payload_hash);
+        if let Some(token) = &self.session_token {
+            insert_header(headers, SECURITY_TOKEN_HEADER, token);
+        }
+
+        let (canonical_request, signed_headers) =
+            canonical_request(method, url, headers, payload_hash);
+
+        let scope = format!("{date}/{}/{SERVICE}/aws4_request", self.region);
+        let string_to_sign = format!(
+            "{ALGORITHM}\n{timestamp}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = [date.as_str(), &self.region, SERVICE, "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac(&key, part.as_bytes()),
+            );
+        let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+        insert_header(
+            headers,
+            AUTHORIZATION.as_str(),
+            &format!(
+                "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.access_key_id
+            ),
+        );
+    }
+}
+
+/// Returns canonical request together with the list of its signed headers,
+/// which are `host` and all `x-amz-` headers
+pub fn canonical_request(
+    method: &Method,
+    url: &Url,
+    headers: &HeaderMap,
+    payload_hash: &str,
+) -> (String, String) {
+    let host = match url.port() {
+        Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let mut signed: Vec<(String, String)> = headers
+        .iter()
+        .filter(|(name, _)| name.as_str().starts_with("x-amz-"))
+        .map(|(name, value)| {
+            let value = value.to_str().unwrap_or_default().trim().to_string();
+            (name.as_str().to_string(), value)
+        })
+        .collect();
+    signed.push(("host".to_string(), host));
+    signed.sort();
+
+    let canonical_headers: String = signed
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect();
+    let signed_headers = signed
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    let canonical_request = format!(
+        "{method}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        url.path(),
+        url.query().unwrap_or_default()
+    );
+    (canonical_request, signed_headers)
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn insert_header(headers: &mut HeaderMap, name: &str, value: &str) {
+    if let (Ok(name), Ok(value)) = (
+        HeaderName::from_bytes(name.as_bytes()),
+        HeaderValue::from_str(value),
+    ) {
+        headers.insert(name, value);
+    }
+}
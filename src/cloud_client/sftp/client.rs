@@ -0,0 +1,683 @@
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::upload_state::PendingUpload;
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::{RemoteConfig, SftpConfig};
+use crate::errors::AppError;
+use crate::utilities::files::write_atomically;
+use chrono::DateTime;
+use ssh2::{
+    CheckResult, ErrorCode, FileStat, KnownHostFileKind, OpenFlags, OpenType, RenameFlags, Session,
+    Sftp,
+};
+use std::cell::RefCell;
+use std::env;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Component, Path, PathBuf};
+use std::rc::Rc;
+use std::time::Duration;
+use tracing::{debug, info, instrument};
+
+static DEFAULT_PORT: u16 = 22;
+/// Size of buffer collecting written data, so that it is sent in pipelined packets
+static WRITE_BUFFER_SIZE: usize = 1024 * 1024;
+static FILE_MODE: i32 = 0o644;
+static FOLDER_MODE: i32 = 0o755;
+static CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Time to wait for the server during each operation of the session, so a stalled connection
+/// fails and is reopened instead of blocking forever
+static SESSION_TIMEOUT_MILLIS: u32 = 60 * 1000;
+
+/// Status codes of SFTP protocol.
+/// More details [here](https://datatracker.ietf.org/doc/html/draft-ietf-secsh-filexfer-13#section-9.1)
+const SFTP_NO_SUCH_FILE: i32 = 2;
+const SFTP_PERMISSION_DENIED: i32 = 3;
+const SFTP_NO_SUCH_PATH: i32 = 10;
+const SFTP_FILE_ALREADY_EXISTS: i32 = 11;
+const SFTP_NO_SPACE_ON_FILESYSTEM: i32 = 14;
+const SFTP_QUOTA_EXCEEDED: i32 = 15;
+
+/// How the client proves its identity to the server
+#[derive(Debug)]
+enum SftpAuth {
+    /// Keys offered by the running SSH agent
+    Agent,
+    PrivateKey {
+        path: PathBuf,
+        passphrase: Option<String>,
+    },
+}
+
+/// Client of SSH server accessed over SFTP.
+/// The connection is opened with the first request and reopened after it breaks.
+pub struct SftpClient {
+    host: String,
+    port: u16,
+    username: String,
+    auth: SftpAuth,
+    known_hosts: PathBuf,
+    /// Folder all paths are relative to, the home folder by default
+    root: PathBuf,
+    connection: RefCell<Option<Rc<Sftp>>>,
+    retry_policy: RetryPolicy,
+}
+
+impl std::fmt::Debug for SftpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SftpClient")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("root", &self.root)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SftpClient {
+    /// Builds client of the remote named `name` without connecting to the server
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &SftpConfig,
+    ) -> Result<SftpClient, AppError> {
+        let username = match &config.username {
+            Some(username) => username.clone(),
+            None => env::var("USER").map_err(|_| {
+                AppError::Config(format!("username is absent for SFTP remote {name}"))
+            })?,
+        };
+        let known_hosts = match &config.known_hosts {
+            Some(known_hosts) => known_hosts.clone(),
+            None => dirs::home_dir()
+                .ok_or_else(|| AppError::Config("home folder is unknown".to_string()))?
+                .join(".ssh")
+                .join("known_hosts"),
+        };
+        let auth = match &config.private_key {
+            Some(path) => SftpAuth::PrivateKey {
+                path: path.clone(),
+                passphrase: config.passphrase.clone(),
+            },
+            None => SftpAuth::Agent,
+        };
+
+        Ok(Self {
+            host: config.host.clone(),
+            port: config.port.unwrap_or(DEFAULT_PORT),
+            username,
+            auth,
+            known_hosts,
+            root: remote.root.clone().unwrap_or_else(|| PathBuf::from(".")),
+            connection: RefCell::new(None),
+            retry_policy: remote.retry.policy()?,
+        })
+    }
+
+    /// Connects to the server, verifies its host key and authenticates
+    #[instrument(name = "SFTP connect", skip(self))]
+    fn connect(&self) -> Result<Sftp, AppError> {
+        info!("Connecting to {}:{}...", self.host, self.port);
+        let stream = self.connect_stream()?;
+        let mut session = Session::new().map_err(session_error)?;
+        session.set_timeout(SESSION_TIMEOUT_MILLIS);
+        session.set_tcp_stream(stream);
+        session.handshake().map_err(session_error)?;
+        self.verify_host_key(&session)?;
+
+        match &self.auth {
+            SftpAuth::Agent => session.userauth_agent(&self.username),
+            SftpAuth::PrivateKey { path, passphrase } => {
+                session.userauth_pubkey_file(&self.username, None, path, passphrase.as_deref())
+            }
+        }
+        .map_err(|error| AppError::Authorization(error.message().to_string()))?;
+        if !session.authenticated() {
+            return Err(AppError::Authorization(format!(
+                "{} was not accepted by {}",
+                self.username, self.host
+            )));
+        }
+
+        info!("Connected");
+        session.sftp().map_err(session_error)
+    }
+
+    /// Connects to the first of the server addresses which accepts the connection in time
+    fn connect_stream(&self) -> Result<TcpStream, AppError> {
+        let addresses = (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|error| AppError::SendRequest(error.to_string()))?;
+        let mut last_error = None;
+        for address in addresses {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(AppError::SendRequest(match last_error {
+            Some(error) => error.to_string(),
+            None => format!("{} has no addresses", self.host),
+        }))
+    }
+
+    /// Accepts only servers whose host key is listed in known_hosts
+    fn verify_host_key(&self, session: &Session) -> Result<(), AppError> {
+        let (key, _) = session
+            .host_key()
+            .ok_or_else(|| AppError::Remote(format!("{} sent no host key", self.host)))?;
+        let mut known_hosts = session.known_hosts().map_err(session_error)?;
+        known_hosts
+            .read_file(&self.known_hosts, KnownHostFileKind::OpenSSH)
+            .map_err(|error| {
+                AppError::Config(format!(
+                    "unable to read {}: {}",
+                    self.known_hosts.display(),
+                    error.message()
+                ))
+            })?;
+
+        match known_hosts.check_port(&self.host, self.port, key) {
+            CheckResult::Match => Ok(()),
+            CheckResult::NotFound => Err(AppError::Remote(format!(
+                "host key of {} is not in {}, connect with ssh once to add it",
+                self.host,
+                self.known_hosts.display()
+            ))),
+            CheckResult::Mismatch => Err(AppError::Remote(format!(
+                "host key of {} doesn't match the one in {}",
+                self.host,
+                self.known_hosts.display()
+            ))),
+            CheckResult::Failure => Err(AppError::Remote(format!(
+                "unable to verify host key of {}",
+                self.host
+            ))),
+        }
+    }
+
+    /// Runs `operation` with the open connection, following the retry policy.
+    /// Broken connection is dropped, so the next attempt reconnects.
+    fn run<T>(
+        &self,
+        idempotent: bool,
+        mut operation: impl FnMut(&Sftp) -> Result<T, AppError>,
+    ) -> Result<T, AppError> {
+        self.retry_policy.run(idempotent, || {
+            let sftp = self.sftp()?;
+            let result = operation(&sftp);
+            let broken = match &result {
+                Err(AppError::SendRequest(_)) => true,
+                // Transfers report failures of both the session and local files as I/O errors
+                Err(AppError::Io(error)) => {
+                    error.kind() == ErrorKind::TimedOut || !is_connected(&sftp)
+                }
+                _ => false,
+            };
+            if broken {
+                self.connection.replace(None);
+            }
+            result
+        })
+    }
+
+    fn sftp(&self) -> Result<Rc<Sftp>, AppError> {
+        if let Some(sftp) = self.connection.borrow().as_ref() {
+            return Ok(Rc::clone(sftp));
+        }
+        let sftp = Rc::new(self.connect()?);
+        self.connection.replace(Some(Rc::clone(&sftp)));
+        Ok(sftp)
+    }
+
+    /// Resolves path of the remote into path on the server, `..` can't lead out of the root
+    pub fn server_path(&self, path: &Path) -> Result<PathBuf, AppError> {
+        let mut relative = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => relative.push(name),
+                Component::ParentDir if !relative.pop() => {
+                    return Err(AppError::Request(format!(
+                        "{} leads outside of the remote",
+                        path.display()
+                    )))
+                }
+                _ => {}
+            }
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+impl CloudClient for SftpClient {
+    fn backend(&self) -> &'static str {
+        "sftp"
+    }
+
+    #[instrument(name = "SFTP download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        info!("Downloading...");
+        let server_path = self.server_path(&from_path)?;
+        let written = self.run(true, |sftp| {
+            let mut file = open_file(sftp, &server_path)?;
+            write_atomically(&mut file, &to_path).map_err(AppError::Io)
+        })?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let server_path = self.server_path(&path)?;
+        let file = self.run(true, |sftp| open_file(sftp, &server_path))?;
+        Ok(Box::new(file))
+    }
+
+    #[instrument(name = "SFTP upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let mut file = File::open(&from_path).map_err(AppError::Io)?;
+        let size = file.metadata().map_err(AppError::Io)?.len();
+        self.upload_stream(&mut file, size, to_path, options)?;
+        Ok(())
+    }
+
+    /// Revisions are not kept on SSH servers, so `update` mode is not supported
+    #[instrument(name = "SFTP upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let server_path = self.server_path(&to_path)?;
+        if let WriteMode::Update(_) = options.mode {
+            return Err(AppError::Request(
+                "SFTP remote doesn't support update mode".to_string(),
+            ));
+        }
+
+        info!("Uploading...");
+        // Content can't be read again, so the upload isn't repeated
+        let server_path = self.run(false, |sftp| {
+            let server_path = match options.mode {
+                WriteMode::Add => destination_path(sftp, &server_path, options.autorename)?,
+                _ => server_path.clone(),
+            };
+            if let Some(parent) = server_path.parent() {
+                create_folders(sftp, parent)?;
+            }
+
+            // The file at `server_path` is replaced only once the whole content has been written
+            let partial_path = partial_path(&server_path)?;
+            let result = write_file(sftp, reader, &partial_path, size).and_then(|_| {
+                replace_file(
+                    sftp,
+                    &partial_path,
+                    &server_path,
+                    options.mode == WriteMode::Overwrite,
+                )
+            });
+            if let Err(error) = result {
+                // Partial file is useless, as SFTP uploads can't be resumed
+                let _ = sftp.unlink(&partial_path);
+                return Err(error);
+            }
+            Ok(server_path)
+        })?;
+
+        info!("File has been uploaded");
+        self.run(true, |sftp| server_entry(sftp, &server_path))
+    }
+
+    #[instrument(name = "SFTP delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        let server_path = self.server_path(&path)?;
+        if server_path == self.root {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+
+        info!("Deleting...");
+        self.run(true, |sftp| {
+            let stat = sftp
+                .lstat(&server_path)
+                .map_err(|error| sftp_error(error, &server_path))?;
+            if stat.is_dir() {
+                delete_folder(sftp, &server_path)
+            } else {
+                sftp.unlink(&server_path)
+                    .map_err(|error| sftp_error(error, &server_path))
+            }
+        })?;
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "SFTP create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        let server_path = self.server_path(&path)?;
+        self.run(false, |sftp| {
+            let server_path = destination_path(sftp, &server_path, autorename)?;
+            create_folders(sftp, &server_path)
+        })?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "SFTP move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let source = self.server_path(&from_path)?;
+        let destination = self.server_path(&to_path)?;
+        if destination.starts_with(&source) {
+            return Err(AppError::Request(
+                "folder can't be moved into itself".to_string(),
+            ));
+        }
+
+        self.run(false, |sftp| {
+            sftp.lstat(&source)
+                .map_err(|error| sftp_error(error, &source))?;
+            let destination = destination_path(sftp, &destination, options.autorename)?;
+            sftp.rename(&source, &destination, None)
+                .map_err(|error| sftp_error(error, &source))
+        })?;
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    /// SFTP can't copy on the server side, so the content passes through this machine
+    #[instrument(name = "SFTP copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let source = self.server_path(&from_path)?;
+        let destination = self.server_path(&to_path)?;
+        if destination.starts_with(&source) {
+            return Err(AppError::Request(
+                "folder can't be copied into itself".to_string(),
+            ));
+        }
+
+        self.run(false, |sftp| {
+            let stat = sftp
+                .stat(&source)
+                .map_err(|error| sftp_error(error, &source))?;
+            let destination = destination_path(sftp, &destination, options.autorename)?;
+            if stat.is_dir() {
+                copy_folder(sftp, &source, &destination)
+            } else {
+                copy_file(sftp, &source, &destination)
+            }
+        })?;
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let server_path = self.server_path(&path)?;
+        self.run(true, |sftp| server_entry(sftp, &server_path))
+    }
+
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        info!("Listing entries...");
+        let server_path = self.server_path(&path)?;
+        let mut entries: Vec<Entry> = self
+            .run(true, |sftp| {
+                sftp.readdir(&server_path)
+                    .map_err(|error| sftp_error(error, &server_path))
+            })?
+            .iter()
+            .map(|(path, stat)| entry(path, stat))
+            .collect();
+        entries.sort_by(|first, second| first.name.cmp(&second.name));
+
+        debug!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    /// Files are written in one go, so there is nothing to resume
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn abandon_upload(&self, _upload: &PendingUpload) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+/// Failures of the session itself mean the connection is broken, so they can be retried
+fn session_error(error: ssh2::Error) -> AppError {
+    AppError::SendRequest(error.message().to_string())
+}
+
+/// Turns SFTP status into the error matching it
+pub fn sftp_error(error: ssh2::Error, path: &Path) -> AppError {
+    let path = path.display().to_string();
+    match error.code() {
+        ErrorCode::SFTP(SFTP_NO_SUCH_FILE | SFTP_NO_SUCH_PATH) => AppError::NotFound(path),
+        ErrorCode::SFTP(SFTP_PERMISSION_DENIED) => AppError::Unauthorized(path),
+        ErrorCode::SFTP(SFTP_FILE_ALREADY_EXISTS) => AppError::Conflict(path),
+        ErrorCode::SFTP(SFTP_NO_SPACE_ON_FILESYSTEM | SFTP_QUOTA_EXCEEDED) => {
+            AppError::InsufficientSpace
+        }
+        ErrorCode::SFTP(code) => AppError::Request(format!("{path}: SFTP status {code}")),
+        ErrorCode::Session(_) => session_error(error),
+    }
+}
+
+/// Tells whether the session still answers requests
+fn is_connected(sftp: &Sftp) -> bool {
+    match sftp.stat(Path::new(".")) {
+        Err(error) => !matches!(error.code(), ErrorCode::Session(_)),
+        Ok(_) => true,
+    }
+}
+
+fn exists(sftp: &Sftp, path: &Path) -> Result<bool, AppError> {
+    is_found(sftp.lstat(path), path)
+}
+
+/// Tells whether `lstat` of the entry at `path` has found it
+fn is_found(stat: Result<FileStat, ssh2::Error>, path: &Path) -> Result<bool, AppError> {
+    match stat.map_err(|error| sftp_error(error, path)) {
+        Ok(_) => Ok(true),
+        Err(AppError::NotFound(_)) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Returns the destination path, renamed to the first free `name (1).ext`, `name (2).ext`
+/// and so on if there is a conflict and `autorename` allows that
+fn destination_path(sftp: &Sftp, path: &Path, autorename: bool) -> Result<PathBuf, AppError> {
+    free_server_path(path, autorename, |candidate| sftp.lstat(candidate))
+}
+
+/// Same as `destination_path`, with entries looked up by `lstat`
+pub fn free_server_path(
+    path: &Path,
+    autorename: bool,
+    lstat: impl Fn(&Path) -> Result<FileStat, ssh2::Error>,
+) -> Result<PathBuf, AppError> {
+    free_path(path, autorename, |candidate| {
+        is_found(lstat(candidate), candidate)
+    })
+}
+
+/// Returns path of the hidden temporary file next to `path`, which the upload is written into
+fn partial_path(path: &Path) -> Result<PathBuf, AppError> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| AppError::Request(format!("{} is not a file path", path.display())))?;
+    let mut partial_name = OsString::from(".");
+    partial_name.push(file_name);
+    partial_name.push(".part");
+    Ok(path.with_file_name(partial_name))
+}
+
+/// Writes `reader` into the file at `path`, failing unless exactly `size` bytes were written
+fn write_file(sftp: &Sftp, reader: &mut dyn Read, path: &Path, size: u64) -> Result<(), AppError> {
+    let file = sftp
+        .open_mode(
+            path,
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE,
+            FILE_MODE,
+            OpenType::File,
+        )
+        .map_err(|error| sftp_error(error, path))?;
+    let mut writer = BufWriter::with_capacity(WRITE_BUFFER_SIZE, file);
+    let written = io::copy(reader, &mut writer).map_err(AppError::Io)?;
+    writer.flush().map_err(AppError::Io)?;
+    if written != size {
+        return Err(AppError::Io(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            format!("expected {size} bytes, but {written} were written"),
+        )));
+    }
+    Ok(())
+}
+
+/// Renames the uploaded file into place, replacing the existing one only with `overwrite` set
+fn replace_file(
+    sftp: &Sftp,
+    partial_path: &Path,
+    path: &Path,
+    overwrite: bool,
+) -> Result<(), AppError> {
+    let flags = if overwrite {
+        RenameFlags::OVERWRITE | RenameFlags::ATOMIC
+    } else {
+        RenameFlags::ATOMIC
+    };
+    match sftp.rename(partial_path, path, Some(flags)) {
+        Ok(()) => Ok(()),
+        // Servers speaking SFTP version 3, e.g. OpenSSH, ignore the flags
+        // and never rename over the existing file
+        Err(_) if overwrite && exists(sftp, path)? => {
+            sftp.unlink(path).map_err(|error| sftp_error(error, path))?;
+            sftp.rename(partial_path, path, Some(flags))
+                .map_err(|error| sftp_error(error, path))
+        }
+        Err(_) if exists(sftp, path)? => Err(AppError::Conflict(path.display().to_string())),
+        Err(error) => Err(sftp_error(error, path)),
+    }
+}
+
+/// Creates the folder together with its absent parents
+fn create_folders(sftp: &Sftp, path: &Path) -> Result<(), AppError> {
+    let mut folder = PathBuf::new();
+    for component in path.components() {
+        folder.push(component);
+        if matches!(component, Component::Normal(_)) && !exists(sftp, &folder)? {
+            sftp.mkdir(&folder, FOLDER_MODE)
+                .map_err(|error| sftp_error(error, &folder))?;
+        }
+    }
+    Ok(())
+}
+
+fn open_file(sftp: &Sftp, path: &Path) -> Result<ssh2::File, AppError> {
+    let stat = sftp.stat(path).map_err(|error| sftp_error(error, path))?;
+    if stat.is_dir() {
+        return Err(AppError::Request(format!("{} is a folder", path.display())));
+    }
+    sftp.open(path).map_err(|error| sftp_error(error, path))
+}
+
+fn delete_folder(sftp: &Sftp, path: &Path) -> Result<(), AppError> {
+    for (child, stat) in sftp
+        .readdir(path)
+        .map_err(|error| sftp_error(error, path))?
+    {
+        if stat.is_dir() {
+            delete_folder(sftp, &child)?;
+        } else {
+            sftp.unlink(&child)
+                .map_err(|error| sftp_error(error, &child))?;
+        }
+    }
+    sftp.rmdir(path).map_err(|error| sftp_error(error, path))
+}
+
+fn copy_file(sftp: &Sftp, source: &Path, destination: &Path) -> Result<(), AppError> {
+    let mut reader = open_file(sftp, source)?;
+    let file = sftp
+        .open_mode(
+            destination,
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::EXCLUSIVE,
+            FILE_MODE,
+            OpenType::File,
+        )
+        .map_err(|error| sftp_error(error, destination))?;
+    let mut writer = BufWriter::with_capacity(WRITE_BUFFER_SIZE, file);
+    io::copy(&mut reader, &mut writer).map_err(AppError::Io)?;
+    writer.flush().map_err(AppError::Io)
+}
+
+fn copy_folder(sftp: &Sftp, source: &Path, destination: &Path) -> Result<(), AppError> {
+    sftp.mkdir(destination, FOLDER_MODE)
+        .map_err(|error| sftp_error(error, destination))?;
+    for (child, stat) in sftp
+        .readdir(source)
+        .map_err(|error| sftp_error(error, source))?
+    {
+        let target = destination.join(child.file_name().unwrap_or_default());
+        if stat.is_dir() {
+            copy_folder(sftp, &child, &target)?;
+        } else {
+            copy_file(sftp, &child, &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn server_entry(sftp: &Sftp, path: &Path) -> Result<Entry, AppError> {
+    let stat = sftp.stat(path).map_err(|error| sftp_error(error, path))?;
+    Ok(entry(path, &stat))
+}
+
+fn entry(path: &Path, stat: &FileStat) -> Entry {
+    let is_dir = stat.is_dir();
+    Entry {
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        kind: if is_dir {
+            EntryKind::Folder
+        } else {
+            EntryKind::File
+        },
+        size: if is_dir {
+            0
+        } else {
+            stat.size.unwrap_or_default()
+        },
+        modified: stat
+            .mtime
+            .and_then(|mtime| DateTime::from_timestamp(i64::try_from(mtime).ok()?, 0)),
+        hash: None,
+        id: None,
+    }
+}
@@ -0,0 +1,300 @@
+use crate::cloud_client::sftp::client::{free_server_path, sftp_error, SftpClient};
+use crate::cloud_client::{CloudClient, UploadOptions, WriteMode};
+use crate::config::{RemoteConfig, SftpConfig};
+use crate::errors::AppError;
+use ssh2::{ErrorCode, FileStat};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn config(host: &str) -> SftpConfig {
+    SftpConfig {
+        host: host.to_string(),
+        port: None,
+        username: Some("user".to_string()),
+        private_key: None,
+        passphrase: None,
+        known_hosts: Some(PathBuf::from("known_hosts")),
+    }
+}
+
+/// Client of the remote with the `root` folder, which isn't connected until it is used
+fn client(config: SftpConfig, root: Option<PathBuf>) -> SftpClient {
+    let remote = RemoteConfig {
+        root,
+        ..RemoteConfig::default()
+    };
+    SftpClient::build("test", &remote, &config).expect("client is built")
+}
+
+fn status(code: i32) -> ssh2::Error {
+    ssh2::Error::new(ErrorCode::SFTP(code), "status")
+}
+
+fn file_stat() -> FileStat {
+    FileStat {
+        size: Some(1),
+        uid: None,
+        gid: None,
+        perm: None,
+        atime: None,
+        mtime: None,
+    }
+}
+
+#[test]
+fn server_path_is_resolved_within_root() {
+    let client = client(config("example.com"), Some(PathBuf::from("/srv/files")));
+
+    assert_eq!(
+        client.server_path(Path::new("/docs/a.txt")).unwrap(),
+        PathBuf::from("/srv/files/docs/a.txt")
+    );
+    assert_eq!(
+        client.server_path(Path::new("/docs/../a.txt")).unwrap(),
+        PathBuf::from("/srv/files/a.txt")
+    );
+    // Absolute paths are relative to the root
+    assert_eq!(
+        client.server_path(Path::new("/etc/passwd")).unwrap(),
+        PathBuf::from("/srv/files/etc/passwd")
+    );
+}
+
+#[test]
+fn server_path_outside_root_is_rejected() {
+    let client = client(config("example.com"), Some(PathBuf::from("/srv/files")));
+
+    for path in ["/..", "/../etc/passwd", "/docs/../../files2", "../../etc"] {
+        assert!(
+            matches!(
+                client.server_path(Path::new(path)),
+                Err(AppError::Request(message)) if message.ends_with("leads outside of the remote")
+            ),
+            "{path} is rejected"
+        );
+    }
+}
+
+#[test]
+fn sftp_statuses_are_mapped_to_errors() {
+    let path = Path::new("/a.txt");
+
+    assert!(matches!(sftp_error(status(2), path), AppError::NotFound(_)));
+    assert!(matches!(
+        sftp_error(status(10), path),
+        AppError::NotFound(_)
+    ));
+    assert!(matches!(
+        sftp_error(status(3), path),
+        AppError::Unauthorized(_)
+    ));
+    assert!(matches!(
+        sftp_error(status(11), path),
+        AppError::Conflict(_)
+    ));
+    assert!(matches!(
+        sftp_error(status(14), path),
+        AppError::InsufficientSpace
+    ));
+    assert!(matches!(
+        sftp_error(status(15), path),
+        AppError::InsufficientSpace
+    ));
+    assert!(matches!(
+        sftp_error(status(4), path),
+        AppError::Request(message) if message == "/a.txt: SFTP status 4"
+    ));
+    // Failures of the session itself are retried after reconnecting
+    assert!(matches!(
+        sftp_error(
+            ssh2::Error::new(ErrorCode::Session(-7), "socket send"),
+            path
+        ),
+        AppError::SendRequest(_)
+    ));
+}
+
+#[test]
+fn taken_destination_path_is_renamed_with_autorename() {
+    let taken = ["/docs/a.txt", "/docs/a (1).txt"];
+    let lstat = |path: &Path| {
+        if taken.iter().any(|taken| Path::new(taken) == path) {
+            Ok(file_stat())
+        } else {
+            Err(status(2))
+        }
+    };
+
+    assert_eq!(
+        free_server_path(Path::new("/docs/b.txt"), false, lstat).unwrap(),
+        PathBuf::from("/docs/b.txt")
+    );
+    assert_eq!(
+        free_server_path(Path::new("/docs/a.txt"), true, lstat).unwrap(),
+        PathBuf::from("/docs/a (2).txt")
+    );
+    assert!(matches!(
+        free_server_path(Path::new("/docs/a.txt"), false, lstat),
+        Err(AppError::Conflict(_))
+    ));
+}
+
+#[test]
+fn failed_lookup_of_destination_path_is_reported() {
+    let result = free_server_path(Path::new("/docs/a.txt"), true, |_| Err(status(3)));
+
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+}
+
+/// Configuration of the SSH server the integration tests run against, e.g.
+/// `CSU_TEST_SFTP_HOST=localhost CSU_TEST_SFTP_PORT=2222 cargo test -- --ignored sftp`.
+/// `CSU_TEST_SFTP_USERNAME`, `CSU_TEST_SFTP_PRIVATE_KEY` and `CSU_TEST_SFTP_KNOWN_HOSTS`
+/// are optional and default to the settings of the SFTP remote.
+fn server_config() -> SftpConfig {
+    let variable = |name: &str| env::var(format!("CSU_TEST_SFTP_{name}")).ok();
+    SftpConfig {
+        host: variable("HOST").expect("CSU_TEST_SFTP_HOST is set to the SSH server"),
+        port: variable("PORT").map(|port| port.parse().expect("CSU_TEST_SFTP_PORT is a port")),
+        username: variable("USERNAME"),
+        private_key: variable("PRIVATE_KEY").map(PathBuf::from),
+        passphrase: None,
+        known_hosts: variable("KNOWN_HOSTS").map(PathBuf::from),
+    }
+}
+
+/// Folder in the home folder of the test server, removed when the test finishes
+struct ServerFolder {
+    name: String,
+    /// Client of the home folder
+    home: SftpClient,
+}
+
+impl ServerFolder {
+    fn new() -> ServerFolder {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        ServerFolder {
+            name: format!("csu-test-{nanos}"),
+            home: client(server_config(), None),
+        }
+    }
+
+    /// Client of the remote rooted at this folder
+    fn client(&self) -> SftpClient {
+        client(server_config(), Some(PathBuf::from(&self.name)))
+    }
+}
+
+impl Drop for ServerFolder {
+    fn drop(&mut self) {
+        let _ = self.home.delete(PathBuf::from(&self.name));
+    }
+}
+
+fn upload(
+    client: &SftpClient,
+    path: &str,
+    content: &[u8],
+    options: UploadOptions,
+) -> Result<(), AppError> {
+    client
+        .upload_stream(
+            &mut &content[..],
+            content.len() as u64,
+            PathBuf::from(path),
+            options,
+        )
+        .map(drop)
+}
+
+#[test]
+#[ignore = "needs an SSH server set with CSU_TEST_SFTP_HOST"]
+fn sftp_server_uploads_and_downloads_files() {
+    let folder = ServerFolder::new();
+    let client = folder.client();
+    upload(&client, "/docs/a.txt", b"content", UploadOptions::default()).unwrap();
+
+    let local = tempfile::tempdir().unwrap();
+    client
+        .download(PathBuf::from("/docs/a.txt"), local.path().join("a.txt"))
+        .unwrap();
+    assert_eq!(
+        std::fs::read(local.path().join("a.txt")).unwrap(),
+        b"content"
+    );
+    assert_eq!(
+        client.metadata(PathBuf::from("/docs/a.txt")).unwrap().size,
+        7
+    );
+}
+
+#[test]
+#[ignore = "needs an SSH server set with CSU_TEST_SFTP_HOST"]
+fn sftp_server_lists_entries() {
+    let folder = ServerFolder::new();
+    let client = folder.client();
+    upload(&client, "/b.txt", b"b", UploadOptions::default()).unwrap();
+    upload(&client, "/a/c.txt", b"c", UploadOptions::default()).unwrap();
+
+    let entries = client.list_entries(PathBuf::from("/")).unwrap();
+    let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+    assert_eq!(names, ["a", "b.txt"]);
+    assert!(entries[0].is_folder());
+}
+
+#[test]
+#[ignore = "needs an SSH server set with CSU_TEST_SFTP_HOST"]
+fn sftp_server_deletes_files_and_folders() {
+    let folder = ServerFolder::new();
+    let client = folder.client();
+    upload(&client, "/a.txt", b"a", UploadOptions::default()).unwrap();
+    upload(
+        &client,
+        "/docs/nested/b.txt",
+        b"b",
+        UploadOptions::default(),
+    )
+    .unwrap();
+
+    client.delete(PathBuf::from("/a.txt")).unwrap();
+    client.delete(PathBuf::from("/docs")).unwrap();
+    assert!(client.list_entries(PathBuf::from("/")).unwrap().is_empty());
+    assert!(matches!(
+        client.metadata(PathBuf::from("/docs")),
+        Err(AppError::NotFound(_))
+    ));
+}
+
+#[test]
+#[ignore = "needs an SSH server set with CSU_TEST_SFTP_HOST"]
+fn sftp_server_keeps_file_when_overwriting_upload_fails() {
+    let folder = ServerFolder::new();
+    let client = folder.client();
+    upload(&client, "/a.txt", b"original", UploadOptions::default()).unwrap();
+
+    let overwrite = UploadOptions {
+        mode: WriteMode::Overwrite,
+        ..UploadOptions::default()
+    };
+    let result = client.upload_stream(
+        &mut &b"short"[..],
+        100,
+        PathBuf::from("/a.txt"),
+        overwrite.clone(),
+    );
+    assert!(matches!(result, Err(AppError::Io(_))));
+    let names: Vec<_> = client
+        .list_entries(PathBuf::from("/"))
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.name)
+        .collect();
+    assert_eq!(names, ["a.txt"]);
+    assert_eq!(client.metadata(PathBuf::from("/a.txt")).unwrap().size, 8);
+
+    upload(&client, "/a.txt", b"new", overwrite).unwrap();
+    assert_eq!(client.metadata(PathBuf::from("/a.txt")).unwrap().size, 3);
+}
@@ -0,0 +1,134 @@
+use crate::cloud_client::UploadOptions;
+use crate::errors::AppError;
+use crate::utilities::files::write_atomically;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Size and modification time of the source file, used to detect it was changed
+/// between the interrupted upload and its resumption
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileFingerprint {
+    size: u64,
+    modified: SystemTime,
+}
+
+impl FileFingerprint {
+    pub fn of(path: &Path) -> Result<FileFingerprint, AppError> {
+        let metadata = fs::metadata(path).map_err(AppError::Io)?;
+        Ok(FileFingerprint {
+            size: metadata.len(),
+            modified: metadata.modified().map_err(AppError::Io)?,
+        })
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Upload which was started but not finished yet
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingUpload {
+    /// Name of the remote the file is uploaded to
+    pub remote: String,
+    pub from_path: PathBuf,
+    pub to_path: PathBuf,
+    /// Backend specific identifier of the upload session
+    pub session_id: String,
+    /// Number of bytes already committed to the upload session
+    pub offset: u64,
+    pub fingerprint: FileFingerprint,
+    /// Options to commit the upload with
+    pub options: UploadOptions,
+}
+
+impl PendingUpload {
+    fn is_same(&self, remote: &str, from_path: &Path, to_path: &Path) -> bool {
+        self.remote == remote && self.from_path == from_path && self.to_path == to_path
+    }
+}
+
+/// Persists pending uploads of a remote in a JSON file, so they survive application restarts.
+/// The file is shared by all remotes.
+#[derive(Debug)]
+pub struct UploadState {
+    path: PathBuf,
+    remote: String,
+}
+
+impl UploadState {
+    pub fn new(path: PathBuf, remote: String) -> Self {
+        Self { path, remote }
+    }
+
+    /// Uses file from `UPLOAD_STATE_FILE` environment variable or `uploads.json` in the user's
+    /// data directory, so uploads can be resumed regardless of the current directory
+    pub fn from_env(remote: &str) -> Self {
+        let path = match std::env::var("UPLOAD_STATE_FILE") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => dirs::data_dir()
+                .map(|directory| directory.join(crate::APPLICATION_NAME))
+                .unwrap_or_default()
+                .join("uploads.json"),
+        };
+        Self::new(path, remote.to_string())
+    }
+
+    pub fn remote(&self) -> &str {
+        &self.remote
+    }
+
+    pub fn load(&self) -> Result<Vec<PendingUpload>, AppError> {
+        let mut uploads = self.load_all()?;
+        uploads.retain(|upload| upload.remote == self.remote);
+        Ok(uploads)
+    }
+
+    fn load_all(&self) -> Result<Vec<PendingUpload>, AppError> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|error| AppError::UploadState(error.to_string())),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(error) => Err(AppError::Io(error)),
+        }
+    }
+
+    pub fn find(
+        &self,
+        from_path: &Path,
+        to_path: &Path,
+    ) -> Result<Option<PendingUpload>, AppError> {
+        Ok(self
+            .load()?
+            .into_iter()
+            .find(|upload| upload.is_same(&self.remote, from_path, to_path)))
+    }
+
+    /// Adds upload to the state or replaces the one with the same source and destination
+    pub fn save(&self, upload: &PendingUpload) -> Result<(), AppError> {
+        let mut uploads = self.load_all()?;
+        uploads.retain(|saved| !saved.is_same(&upload.remote, &upload.from_path, &upload.to_path));
+        uploads.push(upload.clone());
+        self.store(&uploads)
+    }
+
+    pub fn remove(&self, from_path: &Path, to_path: &Path) -> Result<(), AppError> {
+        let mut uploads = self.load_all()?;
+        uploads.retain(|saved| !saved.is_same(&self.remote, from_path, to_path));
+        self.store(&uploads)
+    }
+
+    fn store(&self, uploads: &[PendingUpload]) -> Result<(), AppError> {
+        let content = serde_json::to_string_pretty(uploads)
+            .map_err(|error| AppError::UploadState(error.to_string()))?;
+        if let Some(directory) = self.path.parent() {
+            fs::create_dir_all(directory).map_err(AppError::Io)?;
+        }
+        // Crash in the middle of writing must not lose every pending upload
+        write_atomically(&mut content.as_bytes(), &self.path).map_err(AppError::Io)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,81 @@
+use crate::config::WebDavConfig;
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use reqwest::blocking::RequestBuilder;
+use std::fmt::{Debug, Formatter};
+
+/// Credentials sent with every request
+pub enum WebDavAuth {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+impl Debug for WebDavAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WebDavAuth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .finish_non_exhaustive(),
+            WebDavAuth::Bearer(_) => f.debug_struct("Bearer").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl WebDavAuth {
+    /// Uses basic authentication if the username is configured, bearer token otherwise.
+    /// Password or token from the configuration takes precedence over the one stored by `csu login`.
+    pub fn build(
+        config: &WebDavConfig,
+        credential_store: &dyn CredentialStore,
+        credential_key: &str,
+    ) -> Result<WebDavAuth, AppError> {
+        let secret = match &config.password {
+            Some(password) => password.clone(),
+            None => credential_store.load(credential_key)?.ok_or_else(|| {
+                AppError::Credentials(format!(
+                    "password of {credential_key} is absent, run `csu login`"
+                ))
+            })?,
+        };
+        Ok(match &config.username {
+            Some(username) => WebDavAuth::Basic {
+                username: username.clone(),
+                password: secret,
+            },
+            None => WebDavAuth::Bearer(secret),
+        })
+    }
+
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            WebDavAuth::Basic { username, password } => {
+                request.basic_auth(username, Some(password))
+            }
+            WebDavAuth::Bearer(token) => request.bearer_auth(token),
+        }
+    }
+}
+
+/// Asks for the password, or the token without configured username, and stores it under `key`
+pub fn login(
+    store: &dyn CredentialStore,
+    key: &str,
+    config: &WebDavConfig,
+) -> Result<(), AppError> {
+    let prompt = match &config.username {
+        Some(username) => format!("Password of {username} at {}: ", config.url),
+        None => format!("Token for {}: ", config.url),
+    };
+    let secret = rpassword::prompt_password(prompt).map_err(AppError::Io)?;
+    store.save(key, secret.trim_end())?;
+
+    println!("Logged in successfully");
+    Ok(())
+}
+
+pub fn logout(store: &dyn CredentialStore, key: &str) -> Result<(), AppError> {
+    store.delete(key)?;
+    println!("Logged out successfully");
+    Ok(())
+}
@@ -0,0 +1,440 @@
+use crate::cloud_client::autorename::free_path;
+use crate::cloud_client::entry::Entry;
+use crate::cloud_client::http::{check_response, ApiResponses, ErrorResponse};
+use crate::cloud_client::retry::RetryPolicy;
+use crate::cloud_client::upload_state::PendingUpload;
+use crate::cloud_client::webdav::auth::WebDavAuth;
+use crate::cloud_client::webdav::multistatus::{
+    href_path, parse_multistatus, Resource, PROPFIND_BODY,
+};
+use crate::cloud_client::{CloudClient, RelocationOptions, UploadOptions, WriteMode};
+use crate::config::{RemoteConfig, WebDavConfig};
+use crate::credentials::CredentialStore;
+use crate::errors::{AppError, BUILD_REQUEST_CLIENT_ERROR, RESPONSE_BODY_ERROR};
+use crate::utilities::files::write_atomically;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use reqwest::blocking::{Body, Client, ClientBuilder, RequestBuilder, Response};
+use reqwest::header::{CONTENT_TYPE, IF_MATCH, IF_NONE_MATCH};
+use reqwest::redirect::Policy;
+use reqwest::{Method, StatusCode, Url};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, instrument, warn};
+
+/// Characters encoded in path segments of resource URLs
+const SEGMENT_ENCODE_SET: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'\\')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+static DEPTH_HEADER: &str = "Depth";
+static DESTINATION_HEADER: &str = "Destination";
+static OVERWRITE_HEADER: &str = "Overwrite";
+
+/// Client of WebDAV server, e.g. Nextcloud or ownCloud
+#[derive(Debug)]
+pub struct WebDavClient {
+    client: Client,
+    auth: WebDavAuth,
+    /// URL of the collection all paths are relative to, ending with `/`
+    base_url: Url,
+    retry_policy: RetryPolicy,
+}
+
+impl WebDavClient {
+    /// Builds client of the remote named `name`, `root` is resolved relative to the configured URL
+    pub fn build(
+        name: &str,
+        remote: &RemoteConfig,
+        config: &WebDavConfig,
+        credential_store: &dyn CredentialStore,
+    ) -> Result<WebDavClient, AppError> {
+        // Redirected requests are repeated as GET, which breaks the rest of WebDAV methods
+        let client = ClientBuilder::new()
+            .timeout(None)
+            .redirect(Policy::none())
+            .build()
+            .map_err(|_| AppError::PrepareClient(BUILD_REQUEST_CLIENT_ERROR.to_string()))?;
+        let auth = WebDavAuth::build(config, credential_store, remote.credential_key(name))?;
+
+        let mut base_url = Url::parse(&config.url)
+            .map_err(|error| AppError::Config(format!("invalid WebDAV URL: {error}")))?;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        if let Some(root) = &remote.root {
+            let root = encoded_path(root);
+            if !root.is_empty() {
+                base_url = base_url
+                    .join(&(root + "/"))
+                    .map_err(|error| AppError::Config(format!("invalid root: {error}")))?;
+            }
+        }
+
+        Ok(Self {
+            client,
+            auth,
+            base_url,
+            retry_policy: remote.retry.policy()?,
+        })
+    }
+
+    /// Returns URL of the resource at `path`, collections are addressed with trailing `/`
+    fn url(&self, path: &Path, is_collection: bool) -> Result<Url, AppError> {
+        let mut relative = encoded_path(path);
+        if is_collection && !relative.is_empty() {
+            relative.push('/');
+        }
+        self.base_url
+            .join(&relative)
+            .map_err(|_| AppError::PrepareRequest)
+    }
+
+    /// Sends request built by `request` with credentials, following the retry policy
+    fn send(
+        &self,
+        idempotent: bool,
+        request: impl Fn() -> Result<RequestBuilder, AppError>,
+    ) -> Result<Response, AppError> {
+        self.retry_policy.run(idempotent, || {
+            self.send_once(request()?)
+                .and_then(check_response::<WebDavApi>)
+        })
+    }
+
+    fn send_once(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        self.auth
+            .apply(request)
+            .send()
+            .map_err(|error| AppError::SendRequest(error.to_string()))
+    }
+
+    /// Returns properties of the resources, `None` if the server redirects the request,
+    /// e.g. to the URL of the collection with trailing `/`
+    fn propfind(&self, url: &Url, depth: &str) -> Result<Option<Vec<Resource>>, AppError> {
+        let method = Method::from_bytes(b"PROPFIND").map_err(|_| AppError::PrepareRequest)?;
+        let response = self.retry_policy.run(true, || {
+            let response = self.send_once(
+                self.client
+                    .request(method.clone(), url.clone())
+                    .header(DEPTH_HEADER, depth)
+                    .header(CONTENT_TYPE, "application/xml")
+                    .body(PROPFIND_BODY),
+            )?;
+            if response.status().is_redirection() {
+                debug!("PROPFIND redirected: {:?}", response);
+                return Ok(None);
+            }
+            check_response::<WebDavApi>(response).map(Some)
+        })?;
+        let Some(response) = response else {
+            return Ok(None);
+        };
+
+        let body = response
+            .text()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+        parse_multistatus(&body).map(Some)
+    }
+
+    /// Returns path at `path` that isn't taken yet, renaming it with a number if needed
+    fn destination_path(&self, path: &Path, autorename: bool) -> Result<PathBuf, AppError> {
+        free_path(path, autorename, |candidate| self.exists(candidate))
+    }
+
+    fn exists(&self, path: &Path) -> Result<bool, AppError> {
+        match self.metadata(path.to_path_buf()) {
+            Ok(_) => Ok(true),
+            Err(AppError::NotFound(_)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Sends MOVE or COPY request, both refuse to replace existing destination
+    fn relocate(
+        &self,
+        method: &[u8],
+        from_path: &Path,
+        to_path: &Path,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        let method = Method::from_bytes(method).map_err(|_| AppError::PrepareRequest)?;
+        let is_collection = self.metadata(from_path.to_path_buf())?.is_folder();
+        let to_path = self.destination_path(to_path, options.autorename)?;
+        let from_url = self.url(from_path, is_collection)?;
+        let to_url = self.url(&to_path, is_collection)?;
+
+        self.send(false, || {
+            Ok(self
+                .client
+                .request(method.clone(), from_url.clone())
+                .header(DESTINATION_HEADER, to_url.as_str())
+                .header(OVERWRITE_HEADER, "F"))
+        })?;
+        Ok(())
+    }
+}
+
+impl CloudClient for WebDavClient {
+    fn backend(&self) -> &'static str {
+        "webdav"
+    }
+
+    #[instrument(name = "WebDAV download", skip(self))]
+    fn download(&self, from_path: PathBuf, to_path: PathBuf) -> Result<(), AppError> {
+        info!("Downloading...");
+        let mut response = self.open_download(from_path)?;
+        let written = write_atomically(&mut response, &to_path).map_err(AppError::Io)?;
+
+        info!("File has been saved ({written} bytes)");
+        Ok(())
+    }
+
+    fn open_download(&self, path: PathBuf) -> Result<Box<dyn Read>, AppError> {
+        let url = self.url(&path, false)?;
+        let response = self.send(true, || Ok(self.client.get(url.clone())))?;
+        Ok(Box::new(response))
+    }
+
+    /// Streams the local file, reopening it for every attempt
+    #[instrument(name = "WebDAV upload", skip(self))]
+    fn upload(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<(), AppError> {
+        let to_path = match options.mode {
+            WriteMode::Add if options.autorename => self.destination_path(&to_path, true)?,
+            _ => to_path,
+        };
+        let url = self.url(&to_path, false)?;
+
+        info!("Uploading...");
+        self.send(options.mode == WriteMode::Overwrite, || {
+            let file = File::open(&from_path).map_err(AppError::Io)?;
+            let size = file.metadata().map_err(AppError::Io)?.len();
+            let request = self.client.put(url.clone()).body(Body::sized(file, size));
+            Ok(conditional(request, &options.mode))
+        })?;
+
+        info!("File has been uploaded");
+        Ok(())
+    }
+
+    /// Content is buffered in memory, as the request body has to be repeatable
+    #[instrument(name = "WebDAV upload stream", skip(self, reader))]
+    fn upload_stream(
+        &self,
+        reader: &mut dyn Read,
+        size: u64,
+        to_path: PathBuf,
+        options: UploadOptions,
+    ) -> Result<Entry, AppError> {
+        let to_path = match options.mode {
+            WriteMode::Add if options.autorename => self.destination_path(&to_path, true)?,
+            _ => to_path,
+        };
+        let url = self.url(&to_path, false)?;
+
+        let mut bytes = Vec::with_capacity(size as usize);
+        reader.read_to_end(&mut bytes).map_err(AppError::Io)?;
+
+        info!("Uploading...");
+        self.send(options.mode == WriteMode::Overwrite, || {
+            let request = self.client.put(url.clone()).body(bytes.clone());
+            Ok(conditional(request, &options.mode))
+        })?;
+
+        info!("File has been uploaded");
+        self.metadata(to_path)
+    }
+
+    #[instrument(name = "WebDAV delete", skip(self))]
+    fn delete(&self, path: PathBuf) -> Result<(), AppError> {
+        info!("Deleting...");
+        if encoded_path(&path).is_empty() {
+            return Err(AppError::Request(
+                "root folder can't be deleted".to_string(),
+            ));
+        }
+        let is_collection = self.metadata(path.clone())?.is_folder();
+        let url = self.url(&path, is_collection)?;
+        let mut attempts = 0;
+        self.retry_policy.run(true, || {
+            attempts += 1;
+            match self
+                .send_once(self.client.delete(url.clone()))
+                .and_then(check_response::<WebDavApi>)
+            {
+                // Response to the previous attempt may have been lost after the deletion
+                Err(AppError::NotFound(_)) if attempts > 1 => Ok(()),
+                result => result.map(drop),
+            }
+        })?;
+
+        info!("File has been deleted");
+        Ok(())
+    }
+
+    #[instrument(name = "WebDAV create folder", skip(self))]
+    fn create_folder(&self, path: PathBuf, autorename: bool) -> Result<(), AppError> {
+        info!("Creating folder...");
+        let path = self.destination_path(&path, autorename)?;
+        let url = self.url(&path, true)?;
+        let method = Method::from_bytes(b"MKCOL").map_err(|_| AppError::PrepareRequest)?;
+        self.send(false, || {
+            Ok(self.client.request(method.clone(), url.clone()))
+        })?;
+
+        info!("Folder has been created");
+        Ok(())
+    }
+
+    #[instrument(name = "WebDAV move", skip(self))]
+    fn move_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Moving...");
+        self.relocate(b"MOVE", &from_path, &to_path, options)?;
+
+        info!("Entry has been moved");
+        Ok(())
+    }
+
+    #[instrument(name = "WebDAV copy", skip(self))]
+    fn copy_entry(
+        &self,
+        from_path: PathBuf,
+        to_path: PathBuf,
+        options: RelocationOptions,
+    ) -> Result<(), AppError> {
+        info!("Copying...");
+        self.relocate(b"COPY", &from_path, &to_path, options)?;
+
+        info!("Entry has been copied");
+        Ok(())
+    }
+
+    /// Path doesn't tell whether it is a collection, so the request is repeated
+    /// with trailing `/` if the server redirects it
+    fn metadata(&self, path: PathBuf) -> Result<Entry, AppError> {
+        let resources = match self.propfind(&self.url(&path, false)?, "0")? {
+            Some(resources) => resources,
+            None => self
+                .propfind(&self.url(&path, true)?, "0")?
+                .ok_or_else(|| redirected(&path))?,
+        };
+        resources
+            .into_iter()
+            .next()
+            .map(Resource::into_entry)
+            .ok_or_else(|| AppError::NotFound(path.display().to_string()))
+    }
+
+    /// The listed collection itself is a part of the response and is skipped
+    fn list_entries(&self, path: PathBuf) -> Result<Vec<Entry>, AppError> {
+        info!("Listing entries...");
+        let url = self.url(&path, true)?;
+        let collection_path = href_path(url.path());
+
+        let entries: Vec<Entry> = self
+            .propfind(&url, "1")?
+            .ok_or_else(|| redirected(&path))?
+            .into_iter()
+            .filter(|resource| resource.path != collection_path)
+            .map(Resource::into_entry)
+            .collect();
+
+        debug!("List: {:?}", entries);
+        Ok(entries)
+    }
+
+    /// Files are uploaded with a single request, so there is nothing to resume
+    fn pending_uploads(&self) -> Result<Vec<PendingUpload>, AppError> {
+        Ok(Vec::new())
+    }
+
+    fn abandon_upload(&self, _upload: &PendingUpload) -> Result<(), AppError> {
+        Ok(())
+    }
+}
+
+/// Makes the upload fail on conflict instead of replacing the existing file,
+/// `update` mode expects ETag of the file as revision
+fn conditional(request: RequestBuilder, mode: &WriteMode) -> RequestBuilder {
+    match mode {
+        WriteMode::Add => request.header(IF_NONE_MATCH, "*"),
+        WriteMode::Overwrite => request,
+        WriteMode::Update(etag) => {
+            request.header(IF_MATCH, format!("\"{}\"", etag.trim_matches('"')))
+        }
+    }
+}
+
+/// Joins encoded normal components of the path with `/`
+fn encoded_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => {
+                Some(utf8_percent_encode(&name.to_string_lossy(), SEGMENT_ENCODE_SET).to_string())
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn redirected(path: &Path) -> AppError {
+    AppError::Request(format!("request to {} is redirected", path.display()))
+}
+
+/// Responses of WebDAV server, which describe errors only with their status
+struct WebDavApi;
+
+impl ApiResponses for WebDavApi {
+    fn decode_error(response: ErrorResponse) -> AppError {
+        let ErrorResponse {
+            status,
+            url,
+            retry_after,
+            body,
+        } = response;
+        match status {
+            StatusCode::NOT_FOUND => AppError::NotFound(href_path(url.path())),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                AppError::Unauthorized(status.to_string())
+            }
+            // MKCOL of existing collection is not allowed
+            StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::CONFLICT
+            | StatusCode::PRECONDITION_FAILED => {
+                AppError::Conflict(format!("{status}: {}", href_path(url.path())))
+            }
+            StatusCode::INSUFFICIENT_STORAGE => AppError::InsufficientSpace,
+            StatusCode::TOO_MANY_REQUESTS => AppError::RateLimited { retry_after },
+            status if status.is_server_error() => AppError::Server {
+                status: status.as_u16(),
+                message: body,
+            },
+            status => AppError::Request(format!("{status}: {body}")),
+        }
+    }
+}
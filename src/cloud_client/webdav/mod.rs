@@ -0,0 +1,5 @@
+pub mod auth;
+pub mod client;
+pub mod multistatus;
+#[cfg(test)]
+mod tests;
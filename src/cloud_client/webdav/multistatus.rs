@@ -0,0 +1,123 @@
+use crate::cloud_client::entry::{Entry, EntryKind};
+use crate::errors::{AppError, RESPONSE_BODY_ERROR};
+use chrono::{DateTime, Utc};
+use percent_encoding::percent_decode_str;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// Body of PROPFIND request asking only for the properties entries are built from
+pub static PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:">
+  <d:prop>
+    <d:resourcetype/>
+    <d:getcontentlength/>
+    <d:getlastmodified/>
+    <d:getetag/>
+  </d:prop>
+</d:propfind>"#;
+
+/// Properties of a single resource of `207 Multi-Status` response
+#[derive(Debug, Default)]
+pub struct Resource {
+    /// Decoded path of the resource, without trailing `/`
+    pub path: String,
+    pub is_collection: bool,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    pub etag: Option<String>,
+}
+
+impl Resource {
+    pub fn into_entry(self) -> Entry {
+        Entry {
+            name: self.path.rsplit('/').next().unwrap_or_default().to_string(),
+            kind: if self.is_collection {
+                EntryKind::Folder
+            } else {
+                EntryKind::File
+            },
+            size: if self.is_collection { 0 } else { self.size },
+            modified: self.modified,
+            hash: self.etag,
+            id: None,
+        }
+    }
+}
+
+/// Parses PROPFIND response. Servers use different namespace prefixes (`d:`, `D:` or none),
+/// so elements are matched by their local names.
+/// More details [here](http://www.webdav.org/specs/rfc4918.html#rfc.section.9.1)
+pub fn parse_multistatus(body: &str) -> Result<Vec<Resource>, AppError> {
+    let mut reader = Reader::from_str(body);
+    let mut resources = Vec::new();
+    let mut resource: Option<Resource> = None;
+    let mut element = Vec::new();
+
+    loop {
+        match reader
+            .read_event()
+            .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?
+        {
+            Event::Start(start) => {
+                let name = start.local_name().as_ref().to_vec();
+                match name.as_slice() {
+                    b"response" => resource = Some(Resource::default()),
+                    b"collection" => set_collection(&mut resource),
+                    _ => {}
+                }
+                element = name;
+            }
+            Event::Empty(empty) if empty.local_name().as_ref() == b"collection" => {
+                set_collection(&mut resource)
+            }
+            Event::Text(text) => {
+                let text = text
+                    .unescape()
+                    .map_err(|_| AppError::Response(RESPONSE_BODY_ERROR.to_string()))?;
+                let text = text.trim();
+                if let Some(resource) = &mut resource {
+                    match element.as_slice() {
+                        b"href" => resource.path = href_path(text),
+                        b"getcontentlength" => resource.size = text.parse().unwrap_or_default(),
+                        b"getlastmodified" => {
+                            resource.modified = DateTime::parse_from_rfc2822(text)
+                                .ok()
+                                .map(|modified| modified.with_timezone(&Utc))
+                        }
+                        b"getetag" => resource.etag = Some(text.trim_matches('"').to_string()),
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(end) => {
+                if end.local_name().as_ref() == b"response" {
+                    resources.extend(resource.take());
+                }
+                element.clear();
+            }
+            Event::Eof => return Ok(resources),
+            _ => {}
+        }
+    }
+}
+
+fn set_collection(resource: &mut Option<Resource>) {
+    if let Some(resource) = resource {
+        resource.is_collection = true;
+    }
+}
+
+/// Extracts decoded path from `href`, which may be either absolute URL or absolute path
+pub fn href_path(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(scheme_end) => {
+            let rest = &href[scheme_end + 3..];
+            rest.find('/').map_or("", |path_start| &rest[path_start..])
+        }
+        None => href,
+    };
+    percent_decode_str(path)
+        .decode_utf8_lossy()
+        .trim_end_matches('/')
+        .to_string()
+}
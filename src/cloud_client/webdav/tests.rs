@@ -0,0 +1,185 @@
+use crate::cloud_client::mock_http::{Reply, ScriptedHttp};
+use crate::cloud_client::webdav::client::WebDavClient;
+use crate::cloud_client::webdav::multistatus::{href_path, parse_multistatus};
+use crate::cloud_client::CloudClient;
+use crate::config::{BackendConfig, RemoteConfig, RetryConfig, WebDavConfig};
+use crate::credentials::file::FileCredentialStore;
+use crate::errors::AppError;
+use chrono::{TimeZone, Utc};
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+
+static COLLECTION: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/docs/</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+static FILE: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:">
+  <d:response>
+    <d:href>/dav/a.txt</d:href>
+    <d:propstat>
+      <d:prop><d:resourcetype/><d:getcontentlength>1</d:getcontentlength></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+fn config(server: &ScriptedHttp) -> WebDavConfig {
+    WebDavConfig {
+        url: format!("{}/dav", server.url()),
+        username: Some("user".to_string()),
+        password: Some("secret".to_string()),
+    }
+}
+
+/// Client of the `/dav/` collection on a mock server, with the password in its configuration
+fn client(server: &ScriptedHttp) -> WebDavClient {
+    let remote = RemoteConfig {
+        backend: BackendConfig::WebDav(config(server)),
+        credentials: None,
+        root: None,
+        retry: RetryConfig {
+            max_attempts: NonZeroU32::new(3),
+            max_elapsed_secs: NonZeroU64::new(10),
+            initial_backoff_ms: NonZeroU64::new(1),
+            max_backoff_ms: NonZeroU64::new(1),
+        },
+    };
+    let store = FileCredentialStore::new(PathBuf::from("unused"), None);
+    WebDavClient::build("mock", &remote, &config(server), &store).expect("client is built")
+}
+
+fn methods(server: &ScriptedHttp) -> Vec<String> {
+    server
+        .requests()
+        .into_iter()
+        .map(|request| format!("{} {}", request.method, request.target))
+        .collect()
+}
+
+#[test]
+fn multistatus_is_parsed_regardless_of_namespace_prefix() {
+    let resources = parse_multistatus(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+        <D:multistatus xmlns:D="DAV:">
+          <D:response>
+            <D:href>https://cloud.example.com/dav/My%20Files/</D:href>
+            <D:propstat>
+              <D:prop>
+                <D:resourcetype><D:collection></D:collection></D:resourcetype>
+                <D:getlastmodified>Wed, 01 May 2024 12:00:00 GMT</D:getlastmodified>
+              </D:prop>
+              <D:status>HTTP/1.1 200 OK</D:status>
+            </D:propstat>
+          </D:response>
+          <response xmlns="DAV:">
+            <href>/dav/My%20Files/report%20%231.txt</href>
+            <propstat>
+              <prop>
+                <resourcetype/>
+                <getcontentlength>9</getcontentlength>
+                <getetag>"5f2a-b1"</getetag>
+              </prop>
+              <status>HTTP/1.1 200 OK</status>
+            </propstat>
+          </response>
+        </D:multistatus>"#,
+    )
+    .expect("multistatus is parsed");
+
+    assert_eq!(resources.len(), 2);
+    assert_eq!(resources[0].path, "/dav/My Files");
+    assert!(resources[0].is_collection);
+    assert_eq!(
+        resources[0].modified,
+        Some(Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap())
+    );
+    assert_eq!(resources[1].path, "/dav/My Files/report #1.txt");
+    assert!(!resources[1].is_collection);
+    assert_eq!(resources[1].size, 9);
+    assert_eq!(resources[1].etag.as_deref(), Some("5f2a-b1"));
+}
+
+#[test]
+fn collection_is_entry_without_size() {
+    let mut resources = parse_multistatus(COLLECTION).expect("multistatus is parsed");
+
+    let entry = resources.remove(0).into_entry();
+    assert_eq!(entry.display_name(), "docs/");
+    assert_eq!(entry.size, 0);
+}
+
+#[test]
+fn malformed_multistatus_is_rejected() {
+    let result = parse_multistatus("<d:multistatus><d:response></d:multistatus>");
+
+    assert!(matches!(result, Err(AppError::Response(_))));
+}
+
+#[test]
+fn href_is_decoded_into_path() {
+    assert_eq!(href_path("http://host:8080/dav/a%20b/"), "/dav/a b");
+    assert_eq!(href_path("/dav/%C3%A9t%C3%A9.txt"), "/dav/été.txt");
+    assert_eq!(href_path("https://host"), "");
+}
+
+#[test]
+fn collection_is_found_without_trailing_slash() {
+    let server = ScriptedHttp::start();
+    server.reply(
+        "PROPFIND",
+        "/dav/docs",
+        Reply::new(301, "").header("Location", "/dav/docs/"),
+    );
+    server.reply("PROPFIND", "/dav/docs/", Reply::new(207, COLLECTION));
+
+    let entry = client(&server)
+        .metadata(PathBuf::from("/docs"))
+        .expect("collection is found");
+
+    assert!(entry.is_folder());
+    assert_eq!(
+        methods(&server),
+        ["PROPFIND /dav/docs", "PROPFIND /dav/docs/"]
+    );
+}
+
+#[test]
+fn lost_delete_response_is_not_reported_as_not_found() {
+    let server = ScriptedHttp::start();
+    server.reply("PROPFIND", "/dav/a.txt", Reply::new(207, FILE));
+    // The file is deleted, but the response doesn't reach the client
+    server.reply("DELETE", "/dav/a.txt", Reply::new(502, "Bad Gateway"));
+    server.reply("DELETE", "/dav/a.txt", Reply::new(404, ""));
+
+    client(&server)
+        .delete(PathBuf::from("/a.txt"))
+        .expect("file is deleted");
+
+    assert_eq!(
+        methods(&server),
+        [
+            "PROPFIND /dav/a.txt",
+            "DELETE /dav/a.txt",
+            "DELETE /dav/a.txt"
+        ]
+    );
+}
+
+#[test]
+fn file_deleted_meanwhile_is_reported_as_not_found() {
+    let server = ScriptedHttp::start();
+    server.reply("PROPFIND", "/dav/a.txt", Reply::new(207, FILE));
+    server.reply("DELETE", "/dav/a.txt", Reply::new(404, ""));
+
+    let result = client(&server).delete(PathBuf::from("/a.txt"));
+
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
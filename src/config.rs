@@ -0,0 +1,236 @@
+use crate::cloud_client::retry::RetryPolicy;
+use crate::errors::AppError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Name of the remote used when there is no configuration file
+static DEFAULT_REMOTE_NAME: &str = "dropbox";
+
+/// Content of the configuration file
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    /// Remote used when none is specified with `--remote`
+    pub default_remote: Option<String>,
+    #[serde(default)]
+    pub remotes: BTreeMap<String, RemoteConfig>,
+}
+
+/// Cloud storage with its own account, root folder and settings
+#[derive(Deserialize, Debug, Default)]
+pub struct RemoteConfig {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
+    /// Key of the remote credentials in the credential store, the remote name by default
+    pub credentials: Option<String>,
+    /// Folder on the cloud storage all paths of the remote are relative to
+    pub root: Option<PathBuf>,
+    #[serde(default)]
+    pub retry: RetryConfig,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "backend", rename_all = "lowercase")]
+pub enum BackendConfig {
+    Dropbox(DropboxConfig),
+    #[serde(rename = "drive")]
+    GoogleDrive(GoogleDriveConfig),
+    /// Folder on the local machine set with `root`
+    Local,
+    OneDrive(OneDriveConfig),
+    S3(S3Config),
+    Sftp(SftpConfig),
+    #[serde(rename = "webdav")]
+    WebDav(WebDavConfig),
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        BackendConfig::Dropbox(DropboxConfig::default())
+    }
+}
+
+/// Settings of Dropbox remote, environment variables are used for the absent ones
+#[derive(Deserialize, Debug, Default)]
+pub struct DropboxConfig {
+    /// App key used to log in and refresh access tokens
+    pub app_key: Option<String>,
+    pub upload_chunk_size: Option<NonZeroU64>,
+    pub upload_session_threshold: Option<NonZeroU64>,
+    /// Base URLs of API and content hosts, e.g. of a mock server
+    pub api_url: Option<String>,
+    pub content_url: Option<String>,
+}
+
+/// Settings of Google Drive remote, `GOOGLE_CLIENT_ID` and `GOOGLE_CLIENT_SECRET`
+/// are used for the absent client credentials
+#[derive(Deserialize, Debug, Default)]
+pub struct GoogleDriveConfig {
+    /// OAuth client of desktop application used to log in and refresh access tokens
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Formats Google Docs editors files are exported to by their kind,
+    /// e.g. `document = "pdf"`, as file extension or MIME type
+    #[serde(default)]
+    pub export: BTreeMap<String, String>,
+    /// Size of a single resumable upload request, rounded down to a multiple of 256 KiB
+    pub upload_chunk_size: Option<NonZeroU64>,
+    /// Base URLs of Drive API, upload API and OAuth token endpoint, e.g. of a mock server
+    pub api_url: Option<String>,
+    pub upload_url: Option<String>,
+    pub token_url: Option<String>,
+}
+
+/// Settings of OneDrive remote accessed with Microsoft Graph, `ONEDRIVE_CLIENT_ID`
+/// is used for the absent client id
+#[derive(Deserialize, Debug, Default)]
+pub struct OneDriveConfig {
+    /// Application registered in Microsoft Entra ID with public client flows allowed
+    pub client_id: Option<String>,
+    /// `common` by default, `organizations` or tenant id restricts the accounts to log in with
+    pub tenant: Option<String>,
+    /// Files larger than this are uploaded with upload sessions
+    pub upload_session_threshold: Option<NonZeroU64>,
+    /// Size of a single upload session request, rounded down to a multiple of 320 KiB
+    pub upload_chunk_size: Option<NonZeroU64>,
+    /// Base URLs of Graph API and of the identity platform, e.g. of a mock server
+    pub api_url: Option<String>,
+    pub auth_url: Option<String>,
+}
+
+/// Settings of S3-compatible object storage, credentials are read from
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and `AWS_SESSION_TOKEN` if absent
+#[derive(Deserialize, Debug)]
+pub struct S3Config {
+    pub bucket: String,
+    /// `us-east-1` by default
+    pub region: Option<String>,
+    /// AWS endpoint of the region by default, e.g. `http://localhost:9000` for local MinIO
+    pub endpoint: Option<String>,
+    /// Address bucket in the path instead of the host name, as MinIO expects
+    #[serde(default)]
+    pub path_style: bool,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    /// Files larger than this are uploaded in parts
+    pub multipart_threshold: Option<NonZeroU64>,
+    /// Size of every part but the last one, at least 5 MiB
+    pub part_size: Option<NonZeroU64>,
+}
+
+/// Settings of SSH server accessed over SFTP, `root` is relative to the home folder unless absolute
+#[derive(Deserialize, Debug)]
+pub struct SftpConfig {
+    pub host: String,
+    /// 22 by default
+    pub port: Option<u16>,
+    /// `USER` environment variable by default
+    pub username: Option<String>,
+    /// Private key used to authenticate, keys of SSH agent are tried without it
+    pub private_key: Option<PathBuf>,
+    /// Passphrase of the private key
+    pub passphrase: Option<String>,
+    /// `~/.ssh/known_hosts` by default, unknown hosts are refused
+    pub known_hosts: Option<PathBuf>,
+}
+
+/// Settings of WebDAV server, e.g. Nextcloud or ownCloud
+#[derive(Deserialize, Debug)]
+pub struct WebDavConfig {
+    /// URL of the collection used as the remote root,
+    /// e.g. `https://cloud.example.com/remote.php/dav/files/<user>/`
+    pub url: String,
+    /// User name of basic authentication, bearer token is used without it
+    pub username: Option<String>,
+    /// Password, or bearer token without `username`. The one stored by `csu login` is used if absent.
+    pub password: Option<String>,
+}
+
+/// Overrides of the retry policy, environment variables are used for the absent ones
+#[derive(Deserialize, Debug, Default)]
+pub struct RetryConfig {
+    pub max_attempts: Option<NonZeroU32>,
+    pub max_elapsed_secs: Option<NonZeroU64>,
+    pub initial_backoff_ms: Option<NonZeroU64>,
+    pub max_backoff_ms: Option<NonZeroU64>,
+}
+
+impl RetryConfig {
+    pub fn policy(&self) -> Result<RetryPolicy, AppError> {
+        let policy = RetryPolicy::from_env()?;
+        Ok(RetryPolicy {
+            max_attempts: self
+                .max_attempts
+                .map_or(policy.max_attempts, NonZeroU32::get),
+            max_elapsed: self
+                .max_elapsed_secs
+                .map_or(policy.max_elapsed, |secs| Duration::from_secs(secs.get())),
+            initial_backoff: self
+                .initial_backoff_ms
+                .map_or(policy.initial_backoff, |ms| Duration::from_millis(ms.get())),
+            max_backoff: self
+                .max_backoff_ms
+                .map_or(policy.max_backoff, |ms| Duration::from_millis(ms.get())),
+        })
+    }
+}
+
+impl RemoteConfig {
+    pub fn credential_key<'a>(&'a self, name: &'a str) -> &'a str {
+        self.credentials.as_deref().unwrap_or(name)
+    }
+}
+
+impl Config {
+    /// Reads `CSU_CONFIG` or `config.toml` in the user's config directory.
+    /// Without configuration file, a single Dropbox remote configured with environment variables is used.
+    pub fn load() -> Result<Config, AppError> {
+        let path = match std::env::var("CSU_CONFIG") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => match dirs::config_dir() {
+                Some(directory) => directory.join(crate::APPLICATION_NAME).join("config.toml"),
+                None => return Ok(Self::implicit()),
+            },
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                toml::from_str(&content).map_err(|error| AppError::Config(error.to_string()))
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::implicit()),
+            Err(error) => Err(AppError::Io(error)),
+        }
+    }
+
+    fn implicit() -> Config {
+        Config {
+            default_remote: Some(DEFAULT_REMOTE_NAME.to_string()),
+            remotes: BTreeMap::from([(DEFAULT_REMOTE_NAME.to_string(), RemoteConfig::default())]),
+        }
+    }
+
+    /// Returns remote with the given name, or the default one.
+    /// A single configured remote is the default one even if it is not specified.
+    pub fn remote<'a>(
+        &'a self,
+        name: Option<&'a str>,
+    ) -> Result<(&'a str, &'a RemoteConfig), AppError> {
+        let name = match name.or(self.default_remote.as_deref()) {
+            Some(name) => name,
+            None if self.remotes.len() == 1 => self.remotes.keys().next().unwrap(),
+            None => {
+                return Err(AppError::Config(
+                    "specify remote with --remote or default_remote".to_string(),
+                ))
+            }
+        };
+        self.remotes
+            .get_key_value(name)
+            .map(|(name, remote)| (name.as_str(), remote))
+            .ok_or_else(|| AppError::Config(format!("there is no remote named {name}")))
+    }
+}
@@ -0,0 +1,198 @@
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use crate::utilities::files::partial_path;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::fs::{self, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 12;
+const KEY_DERIVATION_ROUNDS: u32 = 600_000;
+
+/// Content of a credential file, encrypted when the store has a passphrase
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "lowercase")]
+enum StoredSecret {
+    Plain {
+        secret: String,
+    },
+    Encrypted {
+        salt: String,
+        nonce: String,
+        ciphertext: String,
+    },
+}
+
+/// Keeps every secret in a separate file, readable and writable only by the owner.
+/// With a passphrase, secrets are encrypted with ChaCha20-Poly1305 using a key derived with PBKDF2.
+pub struct FileCredentialStore {
+    directory: PathBuf,
+    passphrase: Option<String>,
+}
+
+impl FileCredentialStore {
+    pub fn new(directory: PathBuf, passphrase: Option<String>) -> Self {
+        Self {
+            directory,
+            passphrase,
+        }
+    }
+
+    /// Uses `CSU_CREDENTIALS_DIR` or `credentials` folder in the user's config directory,
+    /// and `CSU_CREDENTIALS_PASSPHRASE` to encrypt secrets if it is set
+    pub fn from_env() -> Result<FileCredentialStore, AppError> {
+        let directory = match std::env::var("CSU_CREDENTIALS_DIR") {
+            Ok(directory) => PathBuf::from(directory),
+            Err(_) => dirs::config_dir()
+                .ok_or_else(|| {
+                    AppError::Credentials("unable to find user's config directory".to_string())
+                })?
+                .join(crate::APPLICATION_NAME)
+                .join("credentials"),
+        };
+        let passphrase = std::env::var("CSU_CREDENTIALS_PASSPHRASE").ok();
+        Ok(Self::new(directory, passphrase))
+    }
+
+    /// Returns path of the file with the secret, rejecting keys leading out of the directory
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        if key.is_empty() || key.contains(['/', '\\']) || key.contains("..") {
+            return Err(AppError::Credentials(format!(
+                "invalid credential key {key:?}"
+            )));
+        }
+        Ok(self.directory.join(format!("{key}.json")))
+    }
+
+    fn cipher(&self, salt: &[u8]) -> Result<ChaCha20Poly1305, AppError> {
+        let passphrase = self.passphrase.as_ref().ok_or_else(|| {
+            AppError::Credentials("passphrase is required to decrypt credentials".to_string())
+        })?;
+        let mut key = Key::default();
+        pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), salt, KEY_DERIVATION_ROUNDS, &mut key);
+        Ok(ChaCha20Poly1305::new(&key))
+    }
+
+    fn encrypt(&self, secret: &str) -> Result<StoredSecret, AppError> {
+        if self.passphrase.is_none() {
+            return Ok(StoredSecret::Plain {
+                secret: secret.to_string(),
+            });
+        }
+
+        let mut salt = [0; SALT_LENGTH];
+        let mut nonce = [0; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher(&salt)?
+            .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| AppError::Credentials("unable to encrypt secret".to_string()))?;
+
+        Ok(StoredSecret::Encrypted {
+            salt: STANDARD.encode(salt),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        })
+    }
+
+    fn decrypt(&self, stored: StoredSecret) -> Result<String, AppError> {
+        let (salt, nonce, ciphertext) = match stored {
+            StoredSecret::Plain { secret } => return Ok(secret),
+            StoredSecret::Encrypted {
+                salt,
+                nonce,
+                ciphertext,
+            } => (salt, nonce, ciphertext),
+        };
+
+        let decode = |value: String| {
+            STANDARD
+                .decode(value)
+                .map_err(|_| AppError::Credentials("corrupted credential file".to_string()))
+        };
+        let nonce = decode(nonce)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(AppError::Credentials(
+                "corrupted credential file".to_string(),
+            ));
+        }
+        let secret = self
+            .cipher(&decode(salt)?)?
+            .decrypt(Nonce::from_slice(&nonce), decode(ciphertext)?.as_ref())
+            .map_err(|_| {
+                AppError::Credentials("wrong passphrase or corrupted credential file".to_string())
+            })?;
+        String::from_utf8(secret)
+            .map_err(|_| AppError::Credentials("corrupted credential file".to_string()))
+    }
+}
+
+impl CredentialStore for FileCredentialStore {
+    fn load(&self, key: &str) -> Result<Option<String>, AppError> {
+        let content = match fs::read_to_string(self.path(key)?) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(AppError::Io(error)),
+        };
+        let stored = serde_json::from_str(&content)
+            .map_err(|error| AppError::Credentials(error.to_string()))?;
+        self.decrypt(stored).map(Some)
+    }
+
+    fn save(&self, key: &str, secret: &str) -> Result<(), AppError> {
+        let path = self.path(key)?;
+        create_private_directory(&self.directory)?;
+        let content = serde_json::to_string_pretty(&self.encrypt(secret)?)
+            .map_err(|error| AppError::Credentials(error.to_string()))?;
+
+        // Replacing the file at once keeps the previous secret if writing fails
+        let partial_path = partial_path(&path).map_err(AppError::Io)?;
+        let result = write_private_file(&partial_path, content.as_bytes())
+            .and_then(|_| fs::rename(&partial_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&partial_path);
+        }
+        result.map_err(AppError::Io)
+    }
+
+    fn delete(&self, key: &str) -> Result<(), AppError> {
+        match fs::remove_file(self.path(key)?) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(AppError::Io(error)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn create_private_directory(directory: &Path) -> Result<(), AppError> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    builder.mode(0o700);
+    builder.create(directory).map_err(AppError::Io)
+}
+
+/// Creates a new file readable and writable only by the owner, replacing a stale one.
+/// The file is never opened with wider permissions, even for a moment.
+fn write_private_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+        _ => {}
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
@@ -0,0 +1,13 @@
+use crate::errors::AppError;
+
+pub mod file;
+#[cfg(test)]
+mod tests;
+
+/// Storage of secrets (e.g. refresh tokens) identified by a key, such as the cloud storage name
+pub trait CredentialStore {
+    fn load(&self, key: &str) -> Result<Option<String>, AppError>;
+    fn save(&self, key: &str, secret: &str) -> Result<(), AppError>;
+    /// Deletes the secret, doing nothing if there is none
+    fn delete(&self, key: &str) -> Result<(), AppError>;
+}
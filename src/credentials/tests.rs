@@ -0,0 +1,100 @@
+use crate::credentials::file::FileCredentialStore;
+use crate::credentials::CredentialStore;
+use crate::errors::AppError;
+use std::fs;
+use tempfile::TempDir;
+
+fn store(passphrase: Option<&str>) -> (FileCredentialStore, TempDir) {
+    let directory = tempfile::tempdir().expect("temporary directory is created");
+    let store = FileCredentialStore::new(
+        directory.path().join("credentials"),
+        passphrase.map(str::to_string),
+    );
+    (store, directory)
+}
+
+#[test]
+fn plain_secret_is_loaded_as_saved() {
+    let (store, _directory) = store(None);
+
+    assert!(store.load("dropbox").unwrap().is_none());
+    store.save("dropbox", "refresh token").unwrap();
+    assert_eq!(
+        store.load("dropbox").unwrap().as_deref(),
+        Some("refresh token")
+    );
+
+    store.delete("dropbox").unwrap();
+    assert!(store.load("dropbox").unwrap().is_none());
+}
+
+#[test]
+fn encrypted_secret_is_loaded_as_saved() {
+    let (store, directory) = store(Some("passphrase"));
+
+    store.save("dropbox", "refresh token").unwrap();
+    let content = fs::read_to_string(directory.path().join("credentials/dropbox.json")).unwrap();
+    assert!(!content.contains("refresh token"));
+    assert_eq!(
+        store.load("dropbox").unwrap().as_deref(),
+        Some("refresh token")
+    );
+}
+
+#[test]
+fn wrong_passphrase_is_reported() {
+    let (store, directory) = store(Some("passphrase"));
+    store.save("dropbox", "refresh token").unwrap();
+
+    let other_store = FileCredentialStore::new(
+        directory.path().join("credentials"),
+        Some("other".to_string()),
+    );
+    assert!(matches!(
+        other_store.load("dropbox"),
+        Err(AppError::Credentials(message)) if message.starts_with("wrong passphrase")
+    ));
+}
+
+#[test]
+fn saving_replaces_previous_secret() {
+    let (store, directory) = store(None);
+
+    store.save("dropbox", "old token").unwrap();
+    store.save("dropbox", "new token").unwrap();
+
+    assert_eq!(store.load("dropbox").unwrap().as_deref(), Some("new token"));
+    let entries = fs::read_dir(directory.path().join("credentials")).unwrap();
+    assert_eq!(entries.count(), 1);
+}
+
+#[test]
+fn keys_leading_out_of_directory_are_rejected() {
+    let (store, directory) = store(None);
+
+    for key in ["../dropbox", "nested/dropbox", "..", ""] {
+        assert!(matches!(
+            store.save(key, "refresh token"),
+            Err(AppError::Credentials(_))
+        ));
+        assert!(matches!(store.load(key), Err(AppError::Credentials(_))));
+        assert!(matches!(store.delete(key), Err(AppError::Credentials(_))));
+    }
+    assert!(!directory.path().join("dropbox.json").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn secret_is_private_to_owner() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (store, directory) = store(None);
+    store.save("dropbox", "refresh token").unwrap();
+
+    let mode = |path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(directory.path().join("credentials")), 0o700);
+    assert_eq!(
+        mode(directory.path().join("credentials/dropbox.json")),
+        0o600
+    );
+}
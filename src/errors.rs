@@ -5,8 +5,26 @@ pub enum AppError {
     #[error("{0}")]
     ParseCommand(#[from] clap::error::Error),
 
-    #[error("Access token is absent")]
-    AbsentAccessToken,
+    #[error("Access token is absent, run `csu login` or set {0}")]
+    AbsentAccessToken(String),
+
+    #[error("Dropbox app key is absent")]
+    AbsentAppKey,
+
+    #[error("Authorization failed: {0}")]
+    Authorization(String),
+
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    #[error("Remote error: {0}")]
+    Remote(String),
+
+    #[error("Content verification failed: {0}")]
+    Verification(String),
+
+    #[error("Credential store error: {0}")]
+    Credentials(String),
 
     #[error("Failed to prepare cloud client: {0}")]
     PrepareClient(String),
@@ -23,16 +41,51 @@ pub enum AppError {
     #[error("Request error: {0}")]
     Request(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Insufficient space on cloud storage")]
+    InsufficientSpace,
+
+    #[error("Access token has expired")]
+    ExpiredAccessToken,
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Too many requests{}", describe_retry_after(.retry_after))]
+    RateLimited { retry_after: Option<u64> },
+
+    #[error("Server error {status}: {message}")]
+    Server { status: u16, message: String },
+
+    #[error("Cloud storage error: {0}")]
+    Api(String),
+
     #[error("Response error: {0}")]
     Response(String),
 
+    #[error("Upload session is no longer available")]
+    UploadSessionLost,
+
+    #[error("Upload session expects content from offset {0}")]
+    IncorrectUploadOffset(u64),
+
+    #[error("Upload state error: {0}")]
+    UploadState(String),
+
     #[error("Io error: {0}")]
     Io(#[from] std::io::Error),
 }
 
-pub static  PREPARE_AUTHORIZATION_HEADER_ERROR: &str = "unable to prepare authorization header value";
+fn describe_retry_after(retry_after: &Option<u64>) -> String {
+    retry_after
+        .map(|seconds| format!(", retry after {seconds} seconds"))
+        .unwrap_or_default()
+}
+
 pub static BUILD_REQUEST_CLIENT_ERROR: &str = "unable to build request client";
 pub static RESPONSE_BODY_ERROR: &str = "unable to get response content";
-pub static BAD_REQUEST_ERROR: &str = "check your input paths";
-pub static UNAUTHORIZED_ERROR: &str = "check your input paths";
-pub static OTHER_ERROR: &str = "something went wrong";
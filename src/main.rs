@@ -1,6 +1,8 @@
 mod app;
 mod cli;
 mod cloud_client;
+mod config;
+mod credentials;
 mod errors;
 mod logger;
 mod tui;
@@ -11,9 +13,17 @@ use std::{error::Error, io};
 static APPLICATION_NAME: &str = "csu";
 
 use crate::app::App;
-use crate::cloud_client::dropbox::client::DropboxClient;
+use crate::cli::{StartupCli, StartupCommand};
+use crate::cloud_client::dropbox::oauth;
+use crate::cloud_client::google_drive::oauth as google_oauth;
+use crate::cloud_client::onedrive::oauth as onedrive_oauth;
+use crate::cloud_client::remotes::Remotes;
+use crate::cloud_client::webdav::auth as webdav_auth;
+use crate::config::{BackendConfig, Config};
+use crate::credentials::file::FileCredentialStore;
 use crate::logger::setup_logger;
 use crate::tui::run_app;
+use clap::Parser;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
@@ -26,7 +36,45 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     setup_logger();
 
-    let cloud_client = DropboxClient::build()?;
+    let credential_store = FileCredentialStore::from_env()?;
+    let config = Config::load()?;
+
+    let startup_cli = StartupCli::parse();
+    let (remote_name, remote) = config.remote(startup_cli.remote.as_deref())?;
+
+    if let Some(command) = startup_cli.command {
+        let credential_key = remote.credential_key(remote_name);
+        match (&remote.backend, command) {
+            (BackendConfig::Dropbox(dropbox), StartupCommand::Login) => {
+                oauth::login(&credential_store, credential_key, dropbox)?
+            }
+            (BackendConfig::Dropbox(dropbox), StartupCommand::Logout) => {
+                oauth::logout(&credential_store, credential_key, dropbox)?
+            }
+            (BackendConfig::GoogleDrive(drive), StartupCommand::Login) => {
+                google_oauth::login(&credential_store, credential_key, drive)?
+            }
+            (BackendConfig::GoogleDrive(_), StartupCommand::Logout) => {
+                google_oauth::logout(&credential_store, credential_key)?
+            }
+            (BackendConfig::OneDrive(onedrive), StartupCommand::Login) => {
+                onedrive_oauth::login(&credential_store, credential_key, onedrive)?
+            }
+            (BackendConfig::OneDrive(_), StartupCommand::Logout) => {
+                onedrive_oauth::logout(&credential_store, credential_key)?
+            }
+            (BackendConfig::WebDav(webdav), StartupCommand::Login) => {
+                webdav_auth::login(&credential_store, credential_key, webdav)?
+            }
+            (BackendConfig::WebDav(_), StartupCommand::Logout) => {
+                webdav_auth::logout(&credential_store, credential_key)?
+            }
+            _ => println!("Remote {remote_name} doesn't require logging in"),
+        }
+        return Ok(());
+    }
+
+    let remotes = Remotes::build(&config, remote_name, &credential_store)?;
 
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -34,7 +82,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let app = App::new(cloud_client);
+    let app = App::new(remotes);
     let res = run_app(&mut terminal, app);
 
     disable_raw_mode()?;
@@ -1,5 +1,4 @@
 use crate::app::App;
-use crate::cloud_client::CloudClient;
 use crossterm::event;
 use crossterm::event::{Event, KeyCode, KeyEventKind};
 use ratatui::{
@@ -13,7 +12,7 @@ pub enum WorkMode {
     Edit,
 }
 
-pub fn ui<C: CloudClient>(frame: &mut Frame, app: &App<C>) {
+pub fn ui(frame: &mut Frame, app: &App) {
     let main_layout = Layout::vertical([
         Constraint::Length(1),
         Constraint::Length(3),
@@ -29,7 +28,9 @@ pub fn ui<C: CloudClient>(frame: &mut Frame, app: &App<C>) {
                 "q".bold(),
                 " to exit, ".into(),
                 "e".bold(),
-                " to start editing.".bold(),
+                " to start editing, ".bold(),
+                "Tab".bold(),
+                " to switch remote.".into(),
             ],
             Style::default().add_modifier(Modifier::RAPID_BLINK),
         ),
@@ -77,19 +78,30 @@ pub fn ui<C: CloudClient>(frame: &mut Frame, app: &App<C>) {
     let info_layout = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
         .split(storages_area);
 
-    let local_entries = List::new(app.workspace_data.local_entries.iter().cloned())
-        .block(Block::default().borders(Borders::ALL).title("Local files"));
+    let local_entries = List::new(
+        app.workspace_data
+            .local_entries
+            .iter()
+            .map(|entry| entry.display_name()),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Local files"));
     frame.render_widget(local_entries, info_layout[0]);
 
-    let cloud_entries = List::new(app.workspace_data.cloud_entries.iter().cloned())
-        .block(Block::default().borders(Borders::ALL).title("Cloud files"));
+    let cloud_entries = List::new(
+        app.workspace_data
+            .cloud_entries
+            .iter()
+            .map(|entry| entry.display_name()),
+    )
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Cloud files ({})", app.remotes.current_name())),
+    );
     frame.render_widget(cloud_entries, info_layout[1]);
 }
 
-pub fn run_app<B: Backend, C: CloudClient>(
-    terminal: &mut Terminal<B>,
-    mut app: App<C>,
-) -> io::Result<()> {
+pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
     loop {
         terminal.draw(|f| ui(f, &app))?;
 
@@ -102,6 +114,7 @@ pub fn run_app<B: Backend, C: CloudClient>(
                     KeyCode::Char('q') => {
                         return Ok(());
                     }
+                    KeyCode::Tab => app.switch_to_next_remote(),
                     _ => {}
                 },
                 WorkMode::Edit if key.kind == KeyEventKind::Press => match key.code {
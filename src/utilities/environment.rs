@@ -0,0 +1,13 @@
+use crate::errors::AppError;
+
+/// Reads positive number from environment variable, falling back to default when it is absent
+pub fn read_positive_variable(name: &str, default: u64) -> Result<u64, AppError> {
+    match std::env::var(name) {
+        Ok(value) => value
+            .parse()
+            .ok()
+            .filter(|number| *number > 0)
+            .ok_or_else(|| AppError::PrepareClient(format!("invalid value of {name}"))),
+        Err(_) => Ok(default),
+    }
+}
@@ -1,19 +1,130 @@
-use std::path::Path;
+use crate::cloud_client::entry::{Entry, EntryKind};
+use chrono::{DateTime, Utc};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-pub fn get_path_entries(path: &Path) -> Vec<String> {
+/// Size of the buffer used to copy downloaded content to disk
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+pub fn get_path_entries(path: &Path) -> Vec<Entry> {
     WalkDir::new(path)
         .max_depth(1)
         .into_iter()
         .filter_map(|e| e.ok())
         .skip(1) // Skip the first entry (base directory)
         .map(|entry| {
-            let file_name = entry.file_name().to_string_lossy().to_string();
-            if entry.file_type().is_dir() {
-                file_name + "/" // Append "/" to directories
-            } else {
-                file_name
+            let metadata = entry.metadata().ok();
+            let is_dir = entry.file_type().is_dir();
+            Entry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                kind: if is_dir {
+                    EntryKind::Folder
+                } else {
+                    EntryKind::File
+                },
+                size: match &metadata {
+                    Some(metadata) if !is_dir => metadata.len(),
+                    _ => 0,
+                },
+                modified: metadata
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map(DateTime::<Utc>::from),
+                hash: None,
+                id: None,
             }
         })
         .collect()
 }
+
+/// Returns path of the temporary file placed next to `path` while it is being written
+pub fn partial_path(path: &Path) -> io::Result<PathBuf> {
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        )
+    })?;
+    let mut partial_name = file_name.to_os_string();
+    partial_name.push(".part");
+    Ok(path.with_file_name(partial_name))
+}
+
+/// Copies `reader` into the temporary file next to `path` in fixed-size chunks
+/// and renames it into place once everything has been written.
+/// The temporary file is removed if copying fails, so `path` is never left truncated.
+pub fn write_atomically<R: Read + ?Sized>(reader: &mut R, path: &Path) -> io::Result<u64> {
+    write_atomically_checked(reader, path, None)
+}
+
+/// Same as `write_atomically`, but `path` is replaced only if exactly `size` bytes were read
+pub fn write_atomically_exact<R: Read + ?Sized>(
+    reader: &mut R,
+    path: &Path,
+    size: u64,
+) -> io::Result<u64> {
+    write_atomically_checked(reader, path, Some(size))
+}
+
+fn write_atomically_checked<R: Read + ?Sized>(
+    reader: &mut R,
+    path: &Path,
+    size: Option<u64>,
+) -> io::Result<u64> {
+    let partial_path = partial_path(path)?;
+    let result = write_partial(reader, &partial_path, path, false, size);
+    if result.is_err() {
+        let _ = fs::remove_file(&partial_path);
+    }
+    result
+}
+
+/// Same as `write_atomically`, but the temporary file is kept if copying fails,
+/// so the transfer can be resumed later.
+/// With `append` set, the content is added to the end of the already written temporary file.
+pub fn write_resumable<R: Read>(reader: &mut R, path: &Path, append: bool) -> io::Result<u64> {
+    let partial_path = partial_path(path)?;
+    write_partial(reader, &partial_path, path, append, None)
+}
+
+fn write_partial<R: Read + ?Sized>(
+    reader: &mut R,
+    partial_path: &Path,
+    path: &Path,
+    append: bool,
+    size: Option<u64>,
+) -> io::Result<u64> {
+    let written = copy_in_chunks(reader, partial_path, append)?;
+    if let Some(size) = size.filter(|size| *size != written) {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("expected {size} bytes, but {written} were written"),
+        ));
+    }
+    fs::rename(partial_path, path)?;
+    Ok(written)
+}
+
+fn copy_in_chunks<R: Read + ?Sized>(reader: &mut R, path: &Path, append: bool) -> io::Result<u64> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    let mut buffer = vec![0; COPY_CHUNK_SIZE];
+    let mut written = 0;
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+        file.write_all(&buffer[..read])?;
+        written += read as u64;
+    }
+    file.sync_all()?;
+    Ok(written)
+}
@@ -1 +1,2 @@
+pub mod environment;
 pub mod files;